@@ -0,0 +1,92 @@
+//! Benchmarks for error construction and rendering.
+//!
+//! Run with `cargo bench`. The rendering benchmarks run inside a request ID
+//! scope, as they would behind the request_context middleware.
+
+use axum::response::IntoResponse;
+use criterion::{BatchSize, Criterion, black_box, criterion_group, criterion_main};
+use eywa_errors::{AppError, ValidationErrorBuilder, ValidationErrors, set_request_id};
+use uuid::Uuid;
+
+fn validation_error(fields: usize) -> AppError {
+    let mut errors = ValidationErrors::with_capacity(fields);
+    for index in 0..fields {
+        errors.add_with_value(
+            format!("field_{index}"),
+            "invalid_format",
+            "Must be a valid value",
+            serde_json::json!({ "value": index }),
+        );
+    }
+    AppError::Validation(errors)
+}
+
+fn construction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("construction");
+
+    group.bench_function("not_found", |b| {
+        b.iter(|| eywa_errors::not_found(black_box("user"), black_box("42")))
+    });
+    group.bench_function("validation_builder_3_fields", |b| {
+        b.iter(|| {
+            ValidationErrorBuilder::new()
+                .field("email", "invalid_format", "Must be a valid email")
+                .field("name", "too_short", "Must be at least 3 characters")
+                .field("age", "out_of_range", "Must be at least 18")
+                .build()
+                .unwrap_err()
+        })
+    });
+
+    group.finish();
+}
+
+fn problem_details(c: &mut Criterion) {
+    let mut group = c.benchmark_group("problem_details");
+    let request_id = Uuid::new_v4();
+
+    group.bench_function("to_problem_details/not_found", |b| {
+        let error = eywa_errors::not_found("user", "42");
+        b.iter(|| set_request_id(request_id, || black_box(&error).to_problem_details()))
+    });
+    for fields in [1, 10] {
+        group.bench_function(format!("to_problem_details/validation_{fields}"), |b| {
+            let error = validation_error(fields);
+            b.iter(|| set_request_id(request_id, || black_box(&error).to_problem_details()))
+        });
+        group.bench_function(format!("into_problem_details/validation_{fields}"), |b| {
+            b.iter_batched(
+                || validation_error(fields),
+                |error| set_request_id(request_id, || error.into_problem_details()),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+fn into_response(c: &mut Criterion) {
+    let mut group = c.benchmark_group("into_response");
+    let request_id = Uuid::new_v4();
+
+    let mut bench = |name: &str, make: fn() -> AppError| {
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                make,
+                |error| set_request_id(request_id, || error.into_response()),
+                BatchSize::SmallInput,
+            )
+        });
+    };
+
+    bench("unauthorized", eywa_errors::unauthorized);
+    bench("not_found", || eywa_errors::not_found("user", "42"));
+    bench("validation_1", || validation_error(1));
+    bench("validation_10", || validation_error(10));
+
+    group.finish();
+}
+
+criterion_group!(benches, construction, problem_details, into_response);
+criterion_main!(benches);
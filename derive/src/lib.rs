@@ -0,0 +1,147 @@
+//! `#[derive(IntoAppError)]` for eywa-errors; use it through the `derive`
+//! feature of `eywa-errors`, which documents the attribute.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Expr, Fields, Ident, Variant, parse_macro_input};
+
+/// Helpers called with the variant's `Display` text.
+const MESSAGE_HELPERS: &[&str] = &[
+    "bad_request",
+    "conflict",
+    "forbidden",
+    "service_unavailable",
+    "gateway_timeout",
+    "internal_error",
+];
+
+#[proc_macro_derive(IntoAppError, attributes(app_error))]
+pub fn derive_into_app_error(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "IntoAppError can only be derived for enums",
+        ));
+    };
+
+    let name = &input.ident;
+    let arms = data
+        .variants
+        .iter()
+        .map(|variant| arm(name, variant))
+        .collect::<syn::Result<Vec<_>>>()?;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::core::convert::From<#name #ty_generics> for ::eywa_errors::AppError
+        #where_clause
+        {
+            fn from(error: #name #ty_generics) -> Self {
+                match error {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+/// What a variant maps to, from its `#[app_error(...)]` attribute.
+enum Mapping {
+    From,
+    NotFound(Expr),
+    Unauthorized,
+    Message(Ident),
+}
+
+fn mapping(variant: &Variant) -> syn::Result<Mapping> {
+    let mut attrs = variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("app_error"));
+    let Some(attr) = attrs.next() else {
+        return Err(Error::new_spanned(
+            &variant.ident,
+            "missing `#[app_error(...)]` on this variant",
+        ));
+    };
+    if let Some(duplicate) = attrs.next() {
+        return Err(Error::new_spanned(
+            duplicate,
+            "duplicate `#[app_error(...)]`",
+        ));
+    }
+
+    let mut mapping = None;
+    attr.parse_nested_meta(|meta| {
+        if mapping.is_some() {
+            return Err(meta.error("expected a single mapping"));
+        }
+        let Some(ident) = meta.path.get_ident() else {
+            return Err(meta.error("expected a mapping such as `not_found = \"user\"`"));
+        };
+        mapping = Some(match ident.to_string().as_str() {
+            "from" => Mapping::From,
+            "unauthorized" => Mapping::Unauthorized,
+            "not_found" => Mapping::NotFound(meta.value()?.parse()?),
+            helper if MESSAGE_HELPERS.contains(&helper) => Mapping::Message(ident.clone()),
+            _ => return Err(meta.error("unknown mapping")),
+        });
+        Ok(())
+    })?;
+    mapping.ok_or_else(|| Error::new_spanned(attr, "expected a mapping"))
+}
+
+fn arm(name: &Ident, variant: &Variant) -> syn::Result<TokenStream2> {
+    let ident = &variant.ident;
+
+    Ok(match mapping(variant)? {
+        Mapping::Unauthorized => quote! {
+            #name::#ident { .. } => ::eywa_errors::unauthorized(),
+        },
+        Mapping::Message(helper) if helper == "forbidden" => quote! {
+            error @ #name::#ident { .. } => ::eywa_errors::forbidden(&error.to_string()),
+        },
+        Mapping::Message(helper) => quote! {
+            error @ #name::#ident { .. } => ::eywa_errors::#helper(error.to_string()),
+        },
+        Mapping::From => {
+            let pattern = single_field(name, variant)?;
+            quote! {
+                #pattern => ::eywa_errors::AppError::from(field),
+            }
+        }
+        Mapping::NotFound(resource) => {
+            let pattern = single_field(name, variant)?;
+            quote! {
+                #pattern => ::eywa_errors::not_found(#resource, field.to_string()),
+            }
+        }
+    })
+}
+
+/// A pattern binding the variant's only field to `field`.
+fn single_field(name: &Ident, variant: &Variant) -> syn::Result<TokenStream2> {
+    let ident = &variant.ident;
+
+    match &variant.fields {
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Ok(quote! {
+            #name::#ident(field)
+        }),
+        Fields::Named(fields) if fields.named.len() == 1 => {
+            let member = &fields.named[0].ident;
+            Ok(quote! { #name::#ident { #member: field } })
+        }
+        _ => Err(Error::new_spanned(
+            &variant.fields,
+            "this mapping needs a variant with exactly one field",
+        )),
+    }
+}
@@ -0,0 +1,36 @@
+//! actix-web integration.
+//!
+//! Renders `AppError` through the same pipeline as the axum `IntoResponse`
+//! implementation: identical problem+json body, headers, logging, and audit.
+
+use actix_web::http::StatusCode;
+use actix_web::http::header::{CONTENT_TYPE, HeaderValue};
+use actix_web::{HttpResponse, ResponseError};
+
+use super::app_error::AppError;
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        let (status, _) = self.status_and_title();
+        StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let rendered = self.clone().render();
+        let status = StatusCode::from_u16(rendered.status.as_u16())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+        let mut response = HttpResponse::build(status);
+        for (name, value) in &rendered.headers {
+            if let Ok(value) = HeaderValue::from_bytes(value.as_bytes()) {
+                response.append_header((name.as_str(), value));
+            }
+        }
+        if let Some(content_type) = &rendered.content_type
+            && let Ok(content_type) = HeaderValue::from_bytes(content_type.as_bytes())
+        {
+            response.insert_header((CONTENT_TYPE, content_type));
+        }
+        response.body(rendered.body)
+    }
+}
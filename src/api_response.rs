@@ -0,0 +1,146 @@
+//! Success responses to pair with `AppError`.
+//!
+//! Handlers returning [`ApiResult<T>`] render `Ok` as JSON with the chosen
+//! status and `Err` as problem+json, so every endpoint has the same shape
+//! instead of mixing bare `Json<T>`, tuples, and status codes. Like problem
+//! bodies, success responses carry the current request ID, in the
+//! `x-request-id` header.
+//!
+//! # Example
+//! ```ignore
+//! async fn get_user(Path(id): Path<Uuid>) -> ApiResult<User> {
+//!     Ok(users::find(id).await?.into())
+//! }
+//!
+//! async fn create_user(Json(input): Json<NewUser>) -> ApiResult<User> {
+//!     let user = users::create(input).await?;
+//!     Ok(created(format!("/users/{}", user.id), user))
+//! }
+//!
+//! async fn start_export() -> ApiResult<serde_json::Value> {
+//!     let job = exports::enqueue().await?;
+//!     Ok(accepted(format!("/exports/{}", job.id)))
+//! }
+//!
+//! async fn delete_user(Path(id): Path<Uuid>) -> ApiResult<()> {
+//!     users::delete(id).await?;
+//!     Ok(ApiResponse::no_content())
+//! }
+//! ```
+
+use axum::Json;
+use axum::http::{HeaderName, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+
+use super::app_error::{AppError, CURRENT_REQUEST_ID};
+use super::context::REQUEST_ID_HEADER;
+
+/// Result of a handler: a JSON success response or a problem.
+pub type ApiResult<T> = Result<ApiResponse<T>, AppError>;
+
+/// A JSON success response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse<T> {
+    status: StatusCode,
+    location: Option<String>,
+    body: Option<T>,
+}
+
+impl<T> ApiResponse<T> {
+    /// 200 OK with `body`.
+    pub fn ok(body: T) -> Self {
+        Self {
+            status: StatusCode::OK,
+            location: None,
+            body: Some(body),
+        }
+    }
+
+    /// 201 Created with `body` and a `Location` header pointing at the new
+    /// resource.
+    pub fn created(location: impl Into<String>, body: T) -> Self {
+        Self {
+            status: StatusCode::CREATED,
+            location: Some(location.into()),
+            body: Some(body),
+        }
+    }
+
+    /// Replace the status of a response with a body (e.g. 202 Accepted).
+    pub fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+/// 201 Created with `body` and a `Location` header pointing at the new
+/// resource.
+pub fn created<T>(location: impl Into<String>, body: T) -> ApiResponse<T> {
+    ApiResponse::created(location, body)
+}
+
+/// 202 Accepted for work that continues in the background. `status_url`
+/// points at a resource reporting its progress; it is sent as `Location` and
+/// as the `status_url` member of the body.
+pub fn accepted(status_url: impl Into<String>) -> ApiResponse<serde_json::Value> {
+    let status_url = status_url.into();
+    ApiResponse {
+        status: StatusCode::ACCEPTED,
+        body: Some(serde_json::json!({ "status_url": status_url })),
+        location: Some(status_url),
+    }
+}
+
+impl ApiResponse<()> {
+    /// 204 No Content.
+    pub fn no_content() -> Self {
+        Self {
+            status: StatusCode::NO_CONTENT,
+            location: None,
+            body: None,
+        }
+    }
+}
+
+impl<T> From<T> for ApiResponse<T> {
+    fn from(body: T) -> Self {
+        Self::ok(body)
+    }
+}
+
+impl<T: Serialize> IntoResponse for ApiResponse<T> {
+    fn into_response(self) -> Response {
+        let location = match self.location.as_deref().map(HeaderValue::from_str) {
+            Some(Ok(location)) => Some(location),
+            Some(Err(_)) => {
+                return AppError::InternalServerError(
+                    "Location is not a valid header value".to_string(),
+                )
+                .into_response();
+            }
+            None => None,
+        };
+
+        let mut response = match self.body {
+            Some(body) => Json(body).into_response(),
+            None => ().into_response(),
+        };
+        // Keep the 500 of a body that failed to serialize.
+        if !response.status().is_success() {
+            return response;
+        }
+        *response.status_mut() = self.status;
+        if let Some(location) = location {
+            response.headers_mut().insert(header::LOCATION, location);
+        }
+        if let Ok(request_id) = CURRENT_REQUEST_ID.try_with(|id| id.to_string())
+            && let Ok(value) = HeaderValue::from_str(&request_id)
+        {
+            response
+                .headers_mut()
+                .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+        }
+        response
+    }
+}
@@ -1,13 +1,37 @@
+use std::backtrace::Backtrace;
+use std::borrow::Cow;
+use std::panic::Location;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
 use axum::{
-    http::StatusCode,
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header},
     response::{IntoResponse, Response},
-    Json,
 };
-use serde::Serialize;
-use thiserror::Error;
-use utoipa::ToSchema;
+use smallvec::smallvec;
 use uuid::Uuid;
 
+use super::audit::{self, AuditEvent};
+use super::config::{DetailExposure, ErrorFormat, ErrorsConfig, FieldPathStyle, get_errors_config};
+use super::config_check::ConfigErrorKind;
+use super::context;
+use super::envelope::ErrorEnvelope;
+use super::locale;
+use super::log_throttle::{self, Decision};
+use super::problem_type::ProblemType;
+use super::render::Rendered;
+use super::report::Report;
+use super::severity::Severity;
+use super::slo;
+use super::stats;
+use super::template;
+use super::timestamp;
+use super::upstream::{UpstreamDetails, UpstreamFailure};
+use super::warnings;
+use super::webhook::SignatureFailure;
+
+pub use super::problem::{FieldError, FieldErrors, ProblemDetails};
+
 tokio::task_local! {
     /// Task-local storage for the current request ID.
     /// Set by the request_context middleware in eywa-axum.
@@ -31,243 +55,1276 @@ pub fn get_request_id() -> Uuid {
 }
 
 // =============================================================================
-// RFC 7807 Problem Details
+// AppError
 // =============================================================================
 
-/// RFC 7807 Problem Details response format.
+/// Application error taxonomy rendered as RFC 7807 problem responses.
+///
+/// `AppError` is cheap to clone: foreign errors are held behind an `Arc`, so
+/// caching layers and singleflight-style fan-out can hand the same error to
+/// several waiters.
+///
+/// # Stability
+///
+/// The enum is `#[non_exhaustive]`: new variants are added in minor releases.
+/// Construct errors with the helper functions ([`not_found`],
+/// [`validation_error`], [`config_error`], ...) and classify them with
+/// [`AppError::kind`], [`AppError::status`], and [`AppError::code`] rather
+/// than matching variants.
 ///
-/// This provides a standardized way to carry machine-readable details of errors
-/// in HTTP responses. See: https://tools.ietf.org/html/rfc7807
+/// # Formatting
 ///
-/// # Example Response
-/// ```json
-/// {
-///   "type": "https://api.example.com/errors/validation-error",
-///   "title": "Validation Error",
-///   "status": 400,
-///   "detail": "The 'email' field must be a valid email address",
-///   "instance": "/users/abc123",
-///   "request_id": "550e8400-e29b-41d4-a716-446655440000",
-///   "timestamp": "2026-01-06T14:17:00Z",
-///   "errors": [
-///     { "field": "email", "code": "invalid_format", "message": "Must be a valid email" }
-///   ]
+/// `{}` is the terse message used as the response detail. `{:#}` adds the
+/// code, status, request ID, call-site location, field errors, and source
+/// chain on indented lines, for logs. `{:?}` shows the code, status, and
+/// message only, never the variant's fields, some of which (actor
+/// permissions, upstream responses) must not reach logs verbatim.
+///
+/// Migrating: `match` expressions over `AppError` need a wildcard arm, best
+/// written against [`AppError::kind`]:
+///
+/// ```ignore
+/// match error.kind() {
+///     ErrorKind::NotFound => ...,
+///     ErrorKind::Unavailable | ErrorKind::Timeout => ...,
+///     _ => ...,
 /// }
 /// ```
-#[derive(Debug, Clone, Serialize, ToSchema)]
-pub struct ProblemDetails {
-    /// URI reference that identifies the problem type.
-    /// When dereferenced, should provide human-readable documentation.
-    #[serde(rename = "type")]
-    pub error_type: String,
+///
+/// [`not_found`]: crate::not_found
+/// [`validation_error`]: crate::validation_error
+/// [`config_error`]: crate::config_error
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum AppError {
+    NotFound {
+        resource: String,
+        id: String,
+    },
 
-    /// Short, human-readable summary of the problem type.
-    pub title: String,
+    /// A resource owned by `tenant` was requested from another tenant.
+    ///
+    /// Renders exactly like `NotFound` so clients cannot probe for resources
+    /// of other tenants; the mismatch is logged as a security event.
+    TenantMismatch {
+        resource: String,
+        id: String,
+        tenant: String,
+    },
 
-    /// HTTP status code.
-    pub status: u16,
+    Validation(ValidationErrors),
 
-    /// Human-readable explanation specific to this occurrence of the problem.
-    pub detail: String,
+    ValidationField {
+        field: String,
+        message: String,
+    },
 
-    /// URI reference that identifies the specific occurrence of the problem.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub instance: Option<String>,
+    Unauthorized,
 
-    /// Unique request identifier for tracing.
-    pub request_id: String,
+    /// The actor lacks permission for `action`.
+    ///
+    /// `required_permissions` is returned to the client so it can explain
+    /// what is missing; `actor_permissions` is only logged.
+    Forbidden {
+        action: String,
+        required_permissions: Vec<String>,
+        actor_permissions: Vec<String>,
+    },
 
-    /// ISO 8601 timestamp of when the error occurred.
-    pub timestamp: String,
+    /// A webhook whose signature could not be verified.
+    InvalidSignature {
+        provider: String,
+        reason: SignatureFailure,
+    },
 
-    /// Field-level validation errors (if applicable).
-    #[serde(skip_serializing_if = "Vec::is_empty", default)]
-    pub errors: Vec<FieldError>,
-}
+    /// CSRF token missing or not matching the session.
+    CsrfFailure,
 
-/// Field-level error for validation failures.
-#[derive(Debug, Clone, Serialize, ToSchema)]
-pub struct FieldError {
-    /// The field that caused the error.
-    pub field: String,
+    /// Request `Origin` (or `Referer`) not in the allowed set.
+    InvalidOrigin {
+        origin: String,
+    },
 
-    /// Machine-readable error code.
-    pub code: String,
+    Conflict {
+        message: String,
+    },
 
-    /// Human-readable error message.
-    pub message: String,
+    /// An idempotency key was reused with a different request payload.
+    IdempotencyConflict {
+        key: String,
+    },
 
-    /// The value that was received (for debugging).
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub received: Option<serde_json::Value>,
-}
+    /// A request with the same idempotency key is still being handled.
+    IdempotencyInProgress {
+        key: String,
+    },
 
-impl FieldError {
-    /// Create a new field error.
-    pub fn new(
-        field: impl Into<String>,
-        code: impl Into<String>,
-        message: impl Into<String>,
-    ) -> Self {
-        Self {
-            field: field.into(),
-            code: code.into(),
-            message: message.into(),
-            received: None,
-        }
-    }
+    /// A conditional request (`If-Match`, `If-Unmodified-Since`) whose
+    /// precondition does not hold; `etag` is the current entity tag.
+    PreconditionFailed {
+        etag: Option<String>,
+    },
 
-    /// Create a new field error with the received value.
-    pub fn with_received(
-        field: impl Into<String>,
-        code: impl Into<String>,
-        message: impl Into<String>,
-        received: impl Into<serde_json::Value>,
-    ) -> Self {
-        Self {
-            field: field.into(),
-            code: code.into(),
-            message: message.into(),
-            received: Some(received.into()),
-        }
-    }
-}
+    /// The client's cached representation is current. Rendered as a bodiless
+    /// `304 Not Modified` and never logged.
+    NotModified {
+        etag: Option<String>,
+        last_modified: Option<SystemTime>,
+    },
 
-// =============================================================================
-// AppError
-// =============================================================================
+    /// A payment was declined by the provider; `decline_code` is the
+    /// provider's reason (e.g. `insufficient_funds`), returned to the client.
+    PaymentDeclined {
+        decline_code: Option<String>,
+    },
 
-#[derive(Debug, Error)]
-pub enum AppError {
-    #[error("Resource not found: {resource} with id: {id}")]
-    NotFound { resource: String, id: String },
+    /// The client (or we, towards a provider) sent too many requests.
+    RateLimited {
+        retry_after: Option<Duration>,
+    },
 
-    #[error("Validation error: {0}")]
-    Validation(ValidationErrors),
+    DatabaseError(Arc<sea_orm::DbErr>),
 
-    #[error("Validation error: {field} - {message}")]
-    ValidationField { field: String, message: String },
+    /// A configuration key is missing, invalid, or not allowed. Only the key
+    /// is reported, never its value.
+    ConfigError {
+        key: String,
+        kind: ConfigErrorKind,
+        source: Option<Arc<dyn std::error::Error + Send + Sync>>,
+    },
 
-    #[error("Unauthorized")]
-    Unauthorized,
+    /// A call to another service failed.
+    ///
+    /// `upstream` is logged, never returned to the client.
+    ExternalServiceError {
+        service: String,
+        upstream: UpstreamDetails,
+    },
 
-    #[error("Forbidden: {action}")]
-    Forbidden { action: String },
+    InternalServerError(String),
 
-    #[error("Conflict: {message}")]
-    Conflict { message: String },
+    BadRequest(String),
 
-    #[error("Database error: {0}")]
-    DatabaseError(#[from] sea_orm::DbErr),
+    /// A pagination cursor that could not be decoded or was tampered with.
+    InvalidCursor {
+        received: String,
+    },
 
-    #[error("Configuration error: {0}")]
-    ConfigError(String),
+    /// A pagination cursor that was valid but no longer is (its snapshot or
+    /// retention window has passed).
+    ExpiredCursor,
 
-    #[error("External service error: {service}")]
-    ExternalServiceError { service: String },
+    /// The request body exceeded the size limit (`limit` bytes, if known).
+    BodyTooLarge {
+        limit: Option<u64>,
+    },
 
-    #[error("Internal error: {0}")]
-    InternalServerError(String),
+    /// The client did not send the request (typically its body) in time.
+    BodyReadTimeout,
 
-    #[error("Bad Request: {0}")]
-    BadRequest(String),
+    /// The request body could not be decoded with its `Content-Encoding`.
+    InvalidContentEncoding {
+        encoding: Option<String>,
+    },
+
+    /// None of the media types the handler can produce is acceptable to the
+    /// client; `supported` lists them.
+    NotAcceptable {
+        supported: Vec<String>,
+    },
+
+    /// The resource does not support the request method; `allowed` is sent
+    /// in the `Allow` header.
+    MethodNotAllowed {
+        allowed: Vec<Method>,
+    },
+
+    /// The request headers exceeded the size limit (`limit` bytes, if known).
+    HeadersTooLarge {
+        limit: Option<usize>,
+    },
+
+    /// The request URI exceeded the length limit (`limit` bytes, if known).
+    UriTooLong {
+        limit: Option<usize>,
+    },
+
+    /// The server does not support the HTTP version of the request.
+    HttpVersionNotSupported,
+
+    /// The request must be retried over `protocol` (e.g. `h2c`), which is
+    /// sent in the `Upgrade` header.
+    UpgradeRequired {
+        protocol: String,
+    },
 
-    #[error("Service unavailable: {0}")]
     ServiceUnavailable(String),
+
+    GatewayTimeout(String),
+
+    /// The caller's request deadline passed before the work completed.
+    DeadlineExceeded,
+
+    /// The client closed the connection or cancelled the request before the
+    /// response was sent. Rendered as 499 and only logged at debug level.
+    ClientDisconnected,
+
+    /// Traffic shed because the service is at capacity, as opposed to a
+    /// dependency outage (`ServiceUnavailable`).
+    Overloaded {
+        queue_depth: Option<usize>,
+        retry_after: Duration,
+    },
+
+    /// The instance is shutting down and no longer accepts requests.
+    Draining {
+        retry_after: Duration,
+    },
+
+    /// An error received from another service, rendered as it was reported.
+    Remote(Box<ErrorEnvelope>),
+
+    /// An error with call-site overrides, rendered as the wrapped error.
+    ///
+    /// Built by [`AppError::with_severity`]; classify it through the
+    /// accessors, which see through the wrapper.
+    Annotated(Box<Annotated>),
+}
+
+/// An [`AppError`] together with overrides chosen at the call site.
+#[derive(Debug, Clone)]
+pub struct Annotated {
+    /// The wrapped error.
+    pub error: AppError,
+
+    /// Severity overriding the variant's default.
+    pub severity: Severity,
+
+    /// Whether the error event was emitted where the error was created
+    /// (see [`AppError::log_here`]); rendering then only logs a summary.
+    pub logged: bool,
+
+    /// Where the override was applied, shown by the `{:#}` format.
+    pub location: Option<&'static Location<'static>>,
+
+    /// Backtrace captured where the error was created, when
+    /// [`ErrorsConfig::backtraces`] is enabled.
+    pub backtrace: Option<Arc<Backtrace>>,
 }
 
 impl AppError {
-    /// Get the error type URI for this error.
-    fn error_type_uri(&self) -> &'static str {
+    /// The one-line message, as rendered in the response detail.
+    fn fmt_terse(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            AppError::NotFound { .. } => "https://errors.eywa.dev/not-found",
-            AppError::Validation(_) | AppError::ValidationField { .. } => {
-                "https://errors.eywa.dev/validation-error"
+            AppError::NotFound { resource, id } => {
+                write!(f, "Resource not found: {resource} with id: {id}")
             }
-            AppError::Unauthorized => "https://errors.eywa.dev/unauthorized",
-            AppError::Forbidden { .. } => "https://errors.eywa.dev/forbidden",
-            AppError::Conflict { .. } => "https://errors.eywa.dev/conflict",
-            AppError::DatabaseError(_) => "https://errors.eywa.dev/database-error",
-            AppError::ConfigError(_) => "https://errors.eywa.dev/config-error",
-            AppError::ExternalServiceError { .. } => {
-                "https://errors.eywa.dev/external-service-error"
+            AppError::TenantMismatch { resource, id, .. } => {
+                write!(f, "Resource not found: {resource} with id: {id}")
             }
-            AppError::InternalServerError(_) => "https://errors.eywa.dev/internal-error",
-            AppError::BadRequest(_) => "https://errors.eywa.dev/bad-request",
-            AppError::ServiceUnavailable(_) => "https://errors.eywa.dev/service-unavailable",
+            AppError::Validation(errors) => write!(f, "Validation error: {errors}"),
+            AppError::ValidationField { field, message } => {
+                write!(f, "Validation error: {field} - {message}")
+            }
+            AppError::Unauthorized => f.write_str("Unauthorized"),
+            AppError::Forbidden { action, .. } => write!(f, "Forbidden: {action}"),
+            AppError::InvalidSignature { provider, reason } => {
+                write!(f, "Invalid {provider} webhook signature: {reason}")
+            }
+            AppError::CsrfFailure => f.write_str("CSRF verification failed"),
+            AppError::InvalidOrigin { origin } => write!(f, "Invalid origin: {origin}"),
+            AppError::Conflict { message } => write!(f, "Conflict: {message}"),
+            AppError::IdempotencyConflict { key } => write!(
+                f,
+                "Idempotency key {key} was already used for a different request"
+            ),
+            AppError::IdempotencyInProgress { key } => write!(
+                f,
+                "A request with idempotency key {key} is still in progress"
+            ),
+            AppError::PreconditionFailed { .. } => f.write_str("Precondition failed"),
+            AppError::NotModified { .. } => f.write_str("Not modified"),
+            AppError::PaymentDeclined { .. } => f.write_str("Payment declined"),
+            AppError::RateLimited { .. } => f.write_str("Rate limited"),
+            AppError::DatabaseError(error) => write!(f, "Database error: {error}"),
+            AppError::ConfigError { key, kind, .. } => {
+                write!(f, "Configuration error: {key} is {kind}")
+            }
+            AppError::ExternalServiceError { service, .. } => {
+                write!(f, "External service error: {service}")
+            }
+            AppError::InternalServerError(message) => write!(f, "Internal error: {message}"),
+            AppError::BadRequest(message) => write!(f, "Bad Request: {message}"),
+            AppError::InvalidCursor { received } => {
+                write!(f, "Invalid pagination cursor: {received}")
+            }
+            AppError::ExpiredCursor => f.write_str("Pagination cursor expired"),
+            AppError::BodyTooLarge { .. } => f.write_str("Request body too large"),
+            AppError::BodyReadTimeout => f.write_str("Timed out reading the request body"),
+            AppError::InvalidContentEncoding { encoding } => write!(
+                f,
+                "Request body could not be decoded{}",
+                encoding
+                    .as_deref()
+                    .map(|encoding| format!(" as {encoding}"))
+                    .unwrap_or_default()
+            ),
+            AppError::NotAcceptable { supported } => write!(
+                f,
+                "None of the supported media types is acceptable: {}",
+                supported.join(", ")
+            ),
+            AppError::MethodNotAllowed { .. } => f.write_str("Method not allowed"),
+            AppError::HeadersTooLarge { .. } => f.write_str("Request header fields too large"),
+            AppError::UriTooLong { .. } => f.write_str("Request URI too long"),
+            AppError::HttpVersionNotSupported => f.write_str("HTTP version not supported"),
+            AppError::UpgradeRequired { protocol } => write!(f, "Upgrade to {protocol} required"),
+            AppError::ServiceUnavailable(message) => write!(f, "Service unavailable: {message}"),
+            AppError::GatewayTimeout(message) => write!(f, "Gateway timeout: {message}"),
+            AppError::DeadlineExceeded => f.write_str("Deadline exceeded"),
+            AppError::ClientDisconnected => f.write_str("Client closed the request"),
+            AppError::Overloaded { .. } => f.write_str("Service overloaded"),
+            AppError::Draining { .. } => f.write_str("Service is shutting down"),
+            AppError::Remote(envelope) => f.write_str(&envelope.detail),
+            AppError::Annotated(annotated) => annotated.error.fmt_terse(f),
         }
     }
 
-    /// Get the HTTP status code and title for this error.
-    fn status_and_title(&self) -> (StatusCode, &'static str) {
+    /// Where the error was annotated with [`AppError::with_severity`] or
+    /// [`AppError::log_here`], if it was.
+    pub(crate) fn location(&self) -> Option<&'static Location<'static>> {
+        match self {
+            AppError::Annotated(annotated) => annotated.location,
+            _ => None,
+        }
+    }
+
+    /// Backtrace of where the error was created, if one was captured (see
+    /// [`ErrorsConfig::backtraces`]).
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        match self {
+            AppError::Annotated(annotated) => annotated.backtrace.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            Report::at(self, self.location()).write_block(f)
+        } else {
+            self.fmt_terse(f)
+        }
+    }
+}
+
+impl std::fmt::Debug for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppError")
+            .field("code", &self.code())
+            .field("status", &self.status_and_title().0.as_u16())
+            .field("message", &self.to_string())
+            .finish()
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::DatabaseError(error) => Some(error.as_ref()),
+            AppError::ConfigError { source, .. } => source
+                .as_deref()
+                .map(|source| source as &(dyn std::error::Error + 'static)),
+            AppError::Annotated(annotated) => annotated.error.source(),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Annotated {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for Annotated {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+impl AppError {
+    /// This error with any call-site overrides peeled off.
+    pub(crate) fn base(&self) -> &AppError {
+        match self {
+            AppError::Annotated(annotated) => annotated.error.base(),
+            _ => self,
+        }
+    }
+}
+
+impl AppError {
+    /// Get the problem type of this error.
+    pub fn problem_type(&self) -> ProblemType {
         match self {
-            AppError::NotFound { .. } => (StatusCode::NOT_FOUND, "Not Found"),
+            AppError::Annotated(annotated) => annotated.error.problem_type(),
+            AppError::NotFound { .. } | AppError::TenantMismatch { .. } => ProblemType::NOT_FOUND,
             AppError::Validation(_) | AppError::ValidationField { .. } => {
-                (StatusCode::BAD_REQUEST, "Validation Error")
-            }
-            AppError::BadRequest(_) => (StatusCode::BAD_REQUEST, "Bad Request"),
-            AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized"),
-            AppError::Forbidden { .. } => (StatusCode::FORBIDDEN, "Forbidden"),
-            AppError::Conflict { .. } => (StatusCode::CONFLICT, "Conflict"),
-            AppError::DatabaseError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database Error"),
-            AppError::ConfigError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Configuration Error"),
-            AppError::ExternalServiceError { .. } => {
-                (StatusCode::BAD_GATEWAY, "External Service Error")
-            }
-            AppError::InternalServerError(_) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
-            }
-            AppError::ServiceUnavailable(_) => {
-                (StatusCode::SERVICE_UNAVAILABLE, "Service Unavailable")
+                ProblemType::VALIDATION_ERROR
             }
+            AppError::Unauthorized => ProblemType::UNAUTHORIZED,
+            AppError::Forbidden { .. } => ProblemType::FORBIDDEN,
+            AppError::InvalidSignature { .. } => ProblemType::INVALID_SIGNATURE,
+            AppError::CsrfFailure => ProblemType::CSRF_FAILURE,
+            AppError::InvalidOrigin { .. } => ProblemType::INVALID_ORIGIN,
+            AppError::Conflict { .. } => ProblemType::CONFLICT,
+            AppError::IdempotencyConflict { .. } => ProblemType::IDEMPOTENCY_CONFLICT,
+            AppError::IdempotencyInProgress { .. } => ProblemType::IDEMPOTENCY_IN_PROGRESS,
+            AppError::PreconditionFailed { .. } => ProblemType::PRECONDITION_FAILED,
+            AppError::NotModified { .. } => ProblemType::NOT_MODIFIED,
+            AppError::PaymentDeclined { .. } => ProblemType::PAYMENT_DECLINED,
+            AppError::RateLimited { .. } => ProblemType::RATE_LIMITED,
+            AppError::DatabaseError(_) => ProblemType::DATABASE_ERROR,
+            AppError::ConfigError { .. } => ProblemType::CONFIG_ERROR,
+            AppError::ExternalServiceError { .. } => ProblemType::EXTERNAL_SERVICE_ERROR,
+            AppError::InternalServerError(_) => ProblemType::INTERNAL_ERROR,
+            AppError::BadRequest(_) => ProblemType::BAD_REQUEST,
+            AppError::InvalidCursor { .. } => ProblemType::INVALID_CURSOR,
+            AppError::ExpiredCursor => ProblemType::EXPIRED_CURSOR,
+            AppError::BodyTooLarge { .. } => ProblemType::BODY_TOO_LARGE,
+            AppError::BodyReadTimeout => ProblemType::BODY_READ_TIMEOUT,
+            AppError::InvalidContentEncoding { .. } => ProblemType::INVALID_CONTENT_ENCODING,
+            AppError::NotAcceptable { .. } => ProblemType::NOT_ACCEPTABLE,
+            AppError::MethodNotAllowed { .. } => ProblemType::METHOD_NOT_ALLOWED,
+            AppError::HeadersTooLarge { .. } => ProblemType::HEADERS_TOO_LARGE,
+            AppError::UriTooLong { .. } => ProblemType::URI_TOO_LONG,
+            AppError::HttpVersionNotSupported => ProblemType::HTTP_VERSION_NOT_SUPPORTED,
+            AppError::UpgradeRequired { .. } => ProblemType::UPGRADE_REQUIRED,
+            AppError::ServiceUnavailable(_) => ProblemType::SERVICE_UNAVAILABLE,
+            AppError::GatewayTimeout(_) => ProblemType::GATEWAY_TIMEOUT,
+            AppError::DeadlineExceeded => ProblemType::DEADLINE_EXCEEDED,
+            AppError::ClientDisconnected => ProblemType::CLIENT_DISCONNECTED,
+            AppError::Overloaded { .. } => ProblemType::OVERLOADED,
+            AppError::Draining { .. } => ProblemType::DRAINING,
+            AppError::Remote(_) => ProblemType::REMOTE_ERROR,
         }
     }
 
-    /// Convert to ProblemDetails.
-    pub fn to_problem_details(&self) -> ProblemDetails {
-        let (status, title) = self.status_and_title();
-        let request_id = get_request_id();
+    /// Get the machine-readable error code for this error.
+    pub fn code(&self) -> &str {
+        match self.base() {
+            AppError::Remote(envelope) => &envelope.code,
+            _ => self.variant_code(),
+        }
+    }
+
+    /// The HTTP status this error renders with.
+    pub fn status(&self) -> StatusCode {
+        self.status_and_title().0
+    }
+
+    /// The problem title this error renders with.
+    pub fn title(&self) -> &str {
+        match self.base() {
+            AppError::Remote(envelope) => &envelope.title,
+            _ => self.status_and_title().1,
+        }
+    }
 
-        let errors = match self {
+    /// The problem `type` URI this error renders with, under the configured
+    /// base URI.
+    pub fn type_uri(&self) -> Cow<'static, str> {
+        match self.base() {
+            AppError::Remote(envelope) => Cow::Owned(envelope.error_type.clone()),
+            _ => get_errors_config().type_uri(&self.problem_type()),
+        }
+    }
+
+    /// Get the error code defined by this variant.
+    pub(crate) fn variant_code(&self) -> &'static str {
+        self.problem_type().code
+    }
+
+    /// Get the HTTP status code and title for this error.
+    pub(crate) fn status_and_title(&self) -> (StatusCode, &'static str) {
+        let problem_type = self.problem_type();
+
+        let status = match self.base() {
+            AppError::InvalidSignature { provider, .. } => get_errors_config()
+                .signature_failure_status_for(provider)
+                .unwrap_or(problem_type.default_status),
+            AppError::Remote(envelope) => {
+                StatusCode::from_u16(envelope.status).unwrap_or(problem_type.default_status)
+            }
+            _ => problem_type.default_status,
+        };
+
+        (status, problem_type.title)
+    }
+
+    /// Compare two errors by their rendered problem, ignoring `request_id`
+    /// and `timestamp`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let err = service.get_user(id).await.unwrap_err();
+    /// assert!(err.matches(&not_found("user", id.to_string())));
+    /// ```
+    pub fn matches(&self, other: &AppError) -> bool {
+        self.to_problem_details()
+            .matches(&other.to_problem_details())
+    }
+
+    /// Convert to ProblemDetails, cloning any field errors.
+    ///
+    /// Prefer [`AppError::into_problem_details`] when the error is no longer needed.
+    pub fn to_problem_details(&self) -> ProblemDetails {
+        let errors = match self.base() {
             AppError::Validation(v) => v.errors.clone(),
             AppError::ValidationField { field, message } => {
-                vec![FieldError::new(field, "validation_error", message)]
+                smallvec![FieldError::new(field, "validation_error", message)]
             }
-            _ => Vec::new(),
+            AppError::Remote(envelope) => envelope.fields.clone(),
+            _ => FieldErrors::new(),
         };
 
+        let mut problem = self.problem_details_with(self.to_string(), errors);
+        get_errors_config().body_limits.enforce(&mut problem);
+        problem
+    }
+
+    /// Convert to ProblemDetails, moving field errors instead of cloning them.
+    pub fn into_problem_details(self) -> ProblemDetails {
+        let detail = self.to_string();
+        self.into_problem_details_with(detail)
+    }
+
+    /// Convert to ProblemDetails as a client would receive it: scrubbed and
+    /// with the configured detail exposure applied.
+    ///
+    /// Use this instead of [`AppError::to_problem_details`] for anything sent
+    /// outside the process by other means than `IntoResponse` (message bus
+    /// replies and headers, dead-letter metadata).
+    pub fn to_public_problem_details(&self) -> ProblemDetails {
+        let mut problem = self.to_problem_details();
+        make_public(&get_errors_config(), &mut problem);
+        problem
+    }
+
+    /// Consuming variant of [`AppError::to_public_problem_details`].
+    pub fn into_public_problem_details(self) -> ProblemDetails {
+        let mut problem = self.into_problem_details();
+        make_public(&get_errors_config(), &mut problem);
+        problem
+    }
+
+    /// Convert to ProblemDetails with a precomputed detail.
+    fn into_problem_details_with(self, detail: String) -> ProblemDetails {
+        if let AppError::Annotated(annotated) = self {
+            return annotated.error.into_problem_details_with(detail);
+        }
+
+        let mut problem = self.problem_details_with(detail, FieldErrors::new());
+
+        problem.errors = match self {
+            AppError::Validation(v) => v.errors,
+            AppError::ValidationField { field, message } => {
+                smallvec![FieldError::new(field, "validation_error", message)]
+            }
+            AppError::Remote(envelope) => envelope.fields,
+            _ => FieldErrors::new(),
+        };
+        get_errors_config().body_limits.enforce(&mut problem);
+
+        problem
+    }
+
+    fn problem_details_with(&self, detail: String, errors: FieldErrors) -> ProblemDetails {
+        let (status, title) = self.status_and_title();
+        let request_id = get_request_id();
+
+        if let AppError::Remote(envelope) = self.base() {
+            return ProblemDetails {
+                error_type: Cow::Owned(envelope.error_type.clone()),
+                title: Cow::Owned(envelope.title.clone()),
+                status: status.as_u16(),
+                code: Cow::Owned(envelope.code.clone()),
+                detail,
+                instance: None,
+                request_id: request_id.to_string(),
+                timestamp: timestamp::now(),
+                errors,
+                extensions: envelope.extensions.clone(),
+            };
+        }
+
         ProblemDetails {
-            error_type: self.error_type_uri().to_string(),
-            title: title.to_string(),
+            error_type: get_errors_config().type_uri(&self.problem_type()),
+            title: Cow::Borrowed(title),
             status: status.as_u16(),
-            detail: self.to_string(),
+            code: Cow::Borrowed(self.variant_code()),
+            detail,
             instance: None,
             request_id: request_id.to_string(),
-            timestamp: chrono::Utc::now().to_rfc3339(),
+            timestamp: timestamp::now(),
             errors,
+            extensions: self.extensions(),
+        }
+    }
+
+    /// Variant-specific problem members.
+    fn extensions(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut extensions = serde_json::Map::new();
+        let error = self.base();
+
+        if let AppError::Overloaded {
+            queue_depth,
+            retry_after,
+        } = error
+        {
+            extensions.insert("retry_after".into(), retry_after_secs(*retry_after).into());
+            if let Some(depth) = queue_depth {
+                extensions.insert("queue_depth".into(), (*depth).into());
+            }
+        }
+        match error {
+            AppError::InvalidCursor { .. } => {
+                extensions.insert("restart_pagination".into(), false.into());
+            }
+            AppError::ExpiredCursor => {
+                extensions.insert("restart_pagination".into(), true.into());
+            }
+            _ => {}
+        }
+        if let AppError::PaymentDeclined {
+            decline_code: Some(decline_code),
+        } = error
+        {
+            extensions.insert("decline_code".into(), decline_code.clone().into());
+        }
+        if let AppError::Forbidden {
+            required_permissions,
+            ..
+        } = error
+            && !required_permissions.is_empty()
+        {
+            extensions.insert(
+                "required_permissions".into(),
+                required_permissions.clone().into(),
+            );
+        }
+        if let AppError::InvalidSignature { provider, reason } = error {
+            extensions.insert("provider".into(), provider.clone().into());
+            extensions.insert("reason".into(), reason.code().into());
+        }
+        if let AppError::ExternalServiceError { upstream, .. } = error
+            && let Some(failure) = upstream.failure
+        {
+            extensions.insert("reason".into(), failure.code().into());
+        }
+        if let AppError::MethodNotAllowed { allowed } = error {
+            let allowed: Vec<&str> = allowed.iter().map(Method::as_str).collect();
+            extensions.insert("allowed".into(), allowed.into());
+        }
+        if let AppError::NotAcceptable { supported } = error {
+            extensions.insert("supported".into(), supported.clone().into());
+        }
+        match error {
+            AppError::BodyTooLarge { limit: Some(limit) } => {
+                extensions.insert("max_body_size".into(), (*limit).into());
+            }
+            AppError::HeadersTooLarge { limit: Some(limit) } => {
+                extensions.insert("max_header_size".into(), (*limit).into());
+            }
+            AppError::UriTooLong { limit: Some(limit) } => {
+                extensions.insert("max_uri_length".into(), (*limit).into());
+            }
+            AppError::IdempotencyConflict { key } | AppError::IdempotencyInProgress { key } => {
+                extensions.insert("idempotency_key".into(), key.clone().into());
+            }
+            AppError::UpgradeRequired { protocol } => {
+                extensions.insert("upgrade".into(), protocol.clone().into());
+            }
+            _ => {}
+        }
+
+        extensions
+    }
+
+    /// How long the client should wait before retrying, if the error says.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self.base() {
+            AppError::Overloaded { retry_after, .. } | AppError::Draining { retry_after } => {
+                Some(*retry_after)
+            }
+            AppError::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Response headers that accompany the problem body.
+    pub(crate) fn response_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        if let Some(retry_after) = self.retry_after() {
+            headers.insert(
+                header::RETRY_AFTER,
+                HeaderValue::from(retry_after_secs(retry_after)),
+            );
+        }
+        if let AppError::MethodNotAllowed { allowed } = self.base() {
+            let allowed: Vec<&str> = allowed.iter().map(Method::as_str).collect();
+            if let Ok(value) = HeaderValue::from_str(&allowed.join(", ")) {
+                headers.insert(header::ALLOW, value);
+            }
+        }
+        if let AppError::UpgradeRequired { protocol } = self.base()
+            && let Ok(value) = HeaderValue::from_str(protocol)
+        {
+            // RFC 9110 §15.5.22: a 426 must say what to upgrade to.
+            headers.insert(header::UPGRADE, value);
+            headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+        }
+        match self.base() {
+            // Push keep-alive clients onto another instance.
+            AppError::Draining { .. }
+            // The rest of a slow body is not worth waiting for (RFC 9110 §15.5.9).
+            | AppError::BodyReadTimeout => {
+                headers.insert(header::CONNECTION, HeaderValue::from_static("close"));
+            }
+            _ => {}
+        }
+        if let AppError::PreconditionFailed { etag } | AppError::NotModified { etag, .. } =
+            self.base()
+            && let Some(etag) = etag
+            && let Ok(value) = HeaderValue::from_str(etag)
+        {
+            headers.insert(header::ETAG, value);
+        }
+        if let AppError::NotModified {
+            last_modified: Some(last_modified),
+            ..
+        } = self.base()
+            && let Ok(value) = HeaderValue::from_str(&httpdate::fmt_http_date(*last_modified))
+        {
+            headers.insert(header::LAST_MODIFIED, value);
+        }
+
+        headers
+    }
+}
+
+/// Whole seconds for `Retry-After`, rounded up so clients never retry early.
+fn retry_after_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+// =============================================================================
+// Predicates
+// =============================================================================
+
+impl AppError {
+    /// Whether this is a `NotFound` error.
+    pub fn is_not_found(&self) -> bool {
+        matches!(self.base(), AppError::NotFound { .. })
+    }
+
+    /// Whether this is a `TenantMismatch` error (rendered as 404).
+    pub fn is_tenant_mismatch(&self) -> bool {
+        matches!(self.base(), AppError::TenantMismatch { .. })
+    }
+
+    /// Whether this is a validation error (`Validation` or `ValidationField`).
+    pub fn is_validation(&self) -> bool {
+        matches!(
+            self.base(),
+            AppError::Validation(_) | AppError::ValidationField { .. }
+        )
+    }
+
+    /// Whether this is an `Unauthorized` error.
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(
+            self.base(),
+            AppError::Unauthorized
+                | AppError::CsrfFailure
+                | AppError::ExpiredCursor
+                | AppError::BodyReadTimeout
+                | AppError::HttpVersionNotSupported
+                | AppError::DeadlineExceeded
+                | AppError::ClientDisconnected
+        )
+    }
+
+    /// Whether this is a `Forbidden` error.
+    pub fn is_forbidden(&self) -> bool {
+        matches!(self.base(), AppError::Forbidden { .. })
+    }
+
+    /// Whether this is a `Conflict` error.
+    pub fn is_conflict(&self) -> bool {
+        matches!(self.base(), AppError::Conflict { .. })
+    }
+
+    /// Whether this is an `Overloaded` error.
+    pub fn is_overloaded(&self) -> bool {
+        matches!(self.base(), AppError::Overloaded { .. })
+    }
+
+    /// Whether this is a `BadRequest` error.
+    pub fn is_bad_request(&self) -> bool {
+        matches!(self.base(), AppError::BadRequest(_))
+    }
+
+    /// Whether this error renders with a 4xx status.
+    pub fn is_client_error(&self) -> bool {
+        self.status_and_title().0.is_client_error()
+    }
+
+    /// Whether this error renders with a 5xx status.
+    pub fn is_server_error(&self) -> bool {
+        self.status_and_title().0.is_server_error()
+    }
+
+    /// Codes of all field errors, in order. Empty for non-validation errors.
+    pub fn validation_codes(&self) -> Vec<&str> {
+        match self.base() {
+            AppError::Validation(v) => v.errors.iter().map(|e| e.code.as_ref()).collect(),
+            AppError::ValidationField { .. } => vec!["validation_error"],
+            _ => Vec::new(),
+        }
+    }
+
+    /// The first field error reported for `field`, if any.
+    pub fn field_error_for(&self, field: &str) -> Option<FieldError> {
+        match self.base() {
+            AppError::Validation(v) => v.errors.iter().find(|e| e.field == field).cloned(),
+            AppError::ValidationField {
+                field: name,
+                message,
+            } if name == field => Some(FieldError::new(name, "validation_error", message)),
+            _ => None,
         }
     }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        self.render().into_response()
+    }
+}
+
+impl AppError {
+    /// Render into a problem+json `http::Response`, for services that don't
+    /// use axum (hyper-direct, tower services, Lambda handlers).
+    ///
+    /// Logging, auditing, and scrubbing behave as for `IntoResponse`.
+    pub fn into_http_response(self) -> http::Response<bytes::Bytes> {
+        self.render().into_http_response()
+    }
+
+    /// Log, audit, and serialize this error into a framework-neutral response.
+    ///
+    /// Shared by every framework integration so they behave identically.
+    pub(crate) fn render(self) -> Rendered {
         let (status, _) = self.status_and_title();
-        let problem = self.to_problem_details();
-
-        tracing::error!(
-            status = %status,
-            error_type = %problem.error_type,
-            detail = %problem.detail,
-            request_id = %problem.request_id,
-            "Error occurred"
+
+        if let AppError::NotModified { .. } = self.base() {
+            return Rendered::empty(status).headers(self.response_headers());
+        }
+
+        let severity = self.severity();
+        // Client disconnects are routine; they don't warrant a full event.
+        let summary_only = self.is_logged() || matches!(self.base(), AppError::ClientDisconnected);
+        let config = get_errors_config();
+        let audit_event = AuditEvent::for_error(&self, status.as_u16());
+        let tenant_violation = audit::TenantViolation::for_error(&self);
+        let upstream_failure = UpstreamFailure::for_error(&self);
+        if config.slo.is_some() || config.error_stats {
+            let route = context::request_route();
+            if let Some(policy) = &config.slo
+                && status.is_server_error()
+            {
+                slo::record_failure(policy, &route);
+            }
+            if config.error_stats {
+                stats::record(&route, status.as_u16(), self.code());
+            }
+        }
+        let mut headers = self.response_headers();
+        if let Some(directive) = config.cache_control_for(status) {
+            headers.insert(header::CACHE_CONTROL, directive);
+        }
+        if let Some(policy) = &config.cors {
+            let origin = context::request_origin();
+            headers.extend(policy.headers_for(origin.as_deref()));
+        }
+        vary(&config, &mut headers);
+        let format = context::requested_error_format().unwrap_or(config.error_format);
+        let profiles = context::requested_profiles();
+        let languages = match &config.localizer {
+            Some(_) => context::requested_languages(),
+            None => Vec::new(),
+        };
+
+        // Templates cannot carry per-request warnings, translations or
+        // backtraces, and leave no problem to remember.
+        if self.is_parameterless()
+            && !config.backtraces
+            && format == ErrorFormat::Problem
+            && profiles.is_empty()
+            && languages.is_empty()
+            && config.recent_errors == 0
+            && !warnings::has_warnings()
+        {
+            let template = template::get_or_build(&config, &self);
+            let request_id = get_request_id().to_string();
+
+            log_error(
+                &config,
+                severity,
+                status,
+                &template.code,
+                &template.detail,
+                &request_id,
+                summary_only,
+            );
+            if let Some(event) = audit_event {
+                audit::emit(event, &request_id);
+            }
+
+            let body = template.render(&request_id, timestamp::now().as_ref());
+            return Rendered::new(status, body).headers(headers);
+        }
+
+        // The Display text is only needed if it is logged or returned.
+        let detail = match config.detail_exposure_for(self.code()) {
+            Some(DetailExposure::Canned(message)) if !log_enabled(&config, severity) => {
+                message.clone()
+            }
+            _ => self.to_string(),
+        };
+        let diagnostics = Diagnostics::of(&config, &self);
+        let mut problem = self.into_problem_details_with(detail);
+        diagnostics.attach(&config, &mut problem);
+        warnings::attach(&mut problem);
+        if config.field_paths == FieldPathStyle::JsonPointer {
+            for error in &mut problem.errors {
+                error.field = json_pointer(&error.field);
+            }
+        }
+
+        if let Some(scrubber) = &config.scrubber {
+            scrubber.scrub_problem(&mut problem);
+        }
+
+        log_error(
+            &config,
+            severity,
+            status,
+            &problem.code,
+            &problem.detail,
+            &problem.request_id,
+            summary_only,
         );
+        if let Some(event) = audit_event {
+            audit::emit(event, &problem.request_id);
+        }
+        if let Some(violation) = tenant_violation {
+            violation.emit(&problem.request_id);
+        }
+        if let Some(failure) = upstream_failure {
+            failure.emit(Some(&problem.request_id));
+        }
+
+        apply_exposure(&config, &mut problem);
+        if config.recent_errors > 0 {
+            stats::remember(&problem, config.recent_errors);
+        }
+        if let Some(localizer) = &config.localizer
+            && !languages.is_empty()
+            && let Some(localized) = localizer(&problem, &languages)
+        {
+            if let Ok(language) = HeaderValue::from_str(&localized.language) {
+                headers.insert(header::CONTENT_LANGUAGE, language);
+            }
+            locale::apply(&mut problem, localized, config.keep_canonical_text);
+        }
+        let rendered = match (format, config.negotiate_profile(&profiles)) {
+            #[cfg(feature = "legacy")]
+            (ErrorFormat::Legacy, _) => Rendered::from_legacy(status, &problem),
+            (ErrorFormat::Problem, Some((uri, serializer))) => {
+                Rendered::from_profile(status, &problem, uri, serializer)
+            }
+            (ErrorFormat::Problem, None) => {
+                Rendered::from_problem(status, &problem, config.field_naming)
+            }
+            #[cfg(feature = "msgpack")]
+            (ErrorFormat::MessagePack, _) => {
+                Rendered::from_msgpack(status, &problem, config.field_naming)
+            }
+            #[cfg(feature = "cbor")]
+            (ErrorFormat::Cbor, _) => Rendered::from_cbor(status, &problem, config.field_naming),
+        };
+        rendered.headers(headers)
+    }
 
-        (
+    /// Emit the error event now, inside the current span, so the log carries
+    /// the context of where the error happened. Rendering the error later
+    /// only logs a summary.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let order = repo.find(id).await.map_err(|e| AppError::from(e).log_here())?;
+    /// ```
+    #[track_caller]
+    pub fn log_here(self) -> AppError {
+        if self.is_logged() {
+            return self;
+        }
+
+        let config = get_errors_config();
+        let severity = self.severity();
+        let (status, _) = self.status_and_title();
+        let detail = self.to_string();
+        let detail = match &config.scrubber {
+            Some(scrubber) => scrubber.scrub(&detail).into_owned(),
+            None => detail,
+        };
+        log_error(
+            &config,
+            severity,
             status,
-            [(axum::http::header::CONTENT_TYPE, "application/problem+json")],
-            Json(problem),
+            self.code(),
+            &detail,
+            &get_request_id().to_string(),
+            false,
+        );
+
+        let (error, location, backtrace) = match self {
+            AppError::Annotated(annotated) => {
+                (annotated.error, annotated.location, annotated.backtrace)
+            }
+            error => (error, None, None),
+        };
+        let location = location.or(Some(Location::caller()));
+        AppError::Annotated(Box::new(Annotated {
+            error,
+            severity,
+            logged: true,
+            location,
+            backtrace,
+        }))
+    }
+
+    /// Capture a backtrace if [`ErrorsConfig::backtraces`] is set, and log at
+    /// creation if [`ErrorsConfig::log_at_creation`] is set.
+    #[track_caller]
+    pub(crate) fn created(self) -> AppError {
+        let config = get_errors_config();
+        let error = if config.backtraces {
+            AppError::Annotated(Box::new(Annotated {
+                severity: self.severity(),
+                error: self,
+                logged: false,
+                location: Some(Location::caller()),
+                backtrace: Some(Arc::new(Backtrace::force_capture())),
+            }))
+        } else {
+            self
+        };
+        if config.log_at_creation {
+            error.log_here()
+        } else {
+            error
+        }
+    }
+
+    /// Whether the error event was already emitted by [`AppError::log_here`].
+    fn is_logged(&self) -> bool {
+        matches!(self, AppError::Annotated(annotated) if annotated.logged)
+    }
+
+    /// Whether the rendered body only varies by request ID and timestamp,
+    /// making it eligible for a precomputed template.
+    pub(crate) fn is_parameterless(&self) -> bool {
+        matches!(
+            self.base(),
+            AppError::Unauthorized
+                | AppError::CsrfFailure
+                | AppError::ExpiredCursor
+                | AppError::BodyReadTimeout
+                | AppError::HttpVersionNotSupported
+                | AppError::DeadlineExceeded
+                | AppError::ClientDisconnected
         )
-            .into_response()
+    }
+}
+
+/// The `causes` and `backtrace` members of a problem, as enabled by
+/// [`ErrorsConfig::causes`] and [`ErrorsConfig::backtraces`].
+struct Diagnostics {
+    causes: Vec<String>,
+    backtrace: Option<String>,
+}
+
+impl Diagnostics {
+    fn of(config: &ErrorsConfig, error: &AppError) -> Self {
+        // A canned detail hides the error; its causes would reveal it.
+        if matches!(
+            config.detail_exposure_for(error.code()),
+            Some(DetailExposure::Canned(_))
+        ) {
+            return Self {
+                causes: Vec::new(),
+                backtrace: None,
+            };
+        }
+
+        let mut causes = Vec::new();
+        if config.causes {
+            let mut source = std::error::Error::source(error);
+            while let Some(cause) = source {
+                causes.push(cause.to_string());
+                source = cause.source();
+            }
+        }
+        let backtrace = error
+            .backtrace()
+            .filter(|_| config.backtraces)
+            .map(ToString::to_string);
+        Self { causes, backtrace }
+    }
+
+    fn attach(self, config: &ErrorsConfig, problem: &mut ProblemDetails) {
+        let scrub = |text: String| match &config.scrubber {
+            Some(scrubber) => scrubber.scrub(&text).into_owned(),
+            None => text,
+        };
+        if !self.causes.is_empty() {
+            let causes: Vec<_> = self.causes.into_iter().map(scrub).collect();
+            problem.extensions.insert("causes".into(), causes.into());
+        }
+        if let Some(backtrace) = self.backtrace {
+            problem
+                .extensions
+                .insert("backtrace".into(), backtrace.into());
+        }
+    }
+}
+
+/// Declare the request headers the rendered body depends on, so caches keep
+/// one response per format, profile and language.
+fn vary(config: &ErrorsConfig, headers: &mut HeaderMap) {
+    headers.append(header::VARY, HeaderValue::from_static("x-error-format"));
+    if !config.profiles.is_empty() {
+        headers.append(header::VARY, HeaderValue::from_static("accept"));
+    }
+    if config.localizer.is_some() {
+        headers.append(header::VARY, HeaderValue::from_static("accept-language"));
+    }
+}
+
+/// Scrub a problem and apply the detail exposure policy, as `render` does.
+pub(crate) fn make_public(config: &ErrorsConfig, problem: &mut ProblemDetails) {
+    if let Some(scrubber) = &config.scrubber {
+        scrubber.scrub_problem(problem);
+    }
+    apply_exposure(config, problem);
+}
+
+/// Replace the detail with the configured canned message, if any.
+pub(crate) fn apply_exposure(config: &ErrorsConfig, problem: &mut ProblemDetails) {
+    if let Some(DetailExposure::Canned(message)) = config.detail_exposure_for(&problem.code) {
+        problem.detail = message.clone();
+    }
+}
+
+/// Whether the error log event would be recorded: the severity meets the
+/// configured minimum and a subscriber is interested.
+fn log_enabled(config: &ErrorsConfig, severity: Severity) -> bool {
+    if severity < config.min_log_severity {
+        return false;
+    }
+    match severity {
+        Severity::Info => tracing::enabled!(tracing::Level::INFO),
+        Severity::Warning => tracing::enabled!(tracing::Level::WARN),
+        Severity::Error | Severity::Critical => tracing::enabled!(tracing::Level::ERROR),
+    }
+}
+
+/// Emit the error log event at the level for `severity`, subject to the
+/// configured log throttle. With `summary_only` (errors logged at creation,
+/// client disconnects) only a debug summary is emitted.
+///
+/// Fields use OpenTelemetry semantic-convention names (`error.type` is the
+/// error code) so collectors need no per-service remapping.
+fn log_error(
+    config: &ErrorsConfig,
+    severity: Severity,
+    status: StatusCode,
+    code: &str,
+    detail: &str,
+    request_id: &str,
+    summary_only: bool,
+) {
+    let path = context::get_request_context().and_then(|context| context.path);
+    let path = path.as_deref();
+
+    if summary_only {
+        tracing::debug!(
+            "error.type" = %code,
+            http.response.status_code = status.as_u16(),
+            url.path = path,
+            request_id = %request_id,
+            "Error response sent"
+        );
+        return;
+    }
+    if !log_enabled(config, severity) {
+        return;
+    }
+
+    if let Some(policy) = &config.log_throttle {
+        for expired in log_throttle::take_expired(policy) {
+            expired.emit();
+        }
+        let fingerprint = log_throttle::fingerprint(status.as_u16(), code);
+
+        match log_throttle::check(policy, fingerprint, status.as_u16(), code) {
+            Decision::Suppress => return,
+            Decision::Emit { suppressed } if suppressed > 0 => {
+                tracing::warn!(
+                    "error.type" = %code,
+                    exception.message = %detail,
+                    http.response.status_code = status.as_u16(),
+                    suppressed,
+                    "Suppressed {suppressed} similar errors"
+                );
+            }
+            Decision::Emit { .. } => {}
+        }
+    }
+
+    macro_rules! emit {
+        ($level:ident) => {
+            tracing::$level!(
+                severity = severity.as_str(),
+                "error.type" = %code,
+                exception.message = %detail,
+                http.response.status_code = status.as_u16(),
+                url.path = path,
+                request_id = %request_id,
+                "Error occurred"
+            )
+        };
+    }
+
+    match severity {
+        Severity::Info => emit!(info),
+        Severity::Warning => emit!(warn),
+        Severity::Error | Severity::Critical => emit!(error),
     }
 }
 
@@ -278,20 +1335,29 @@ impl IntoResponse for AppError {
 /// Collection of validation errors for multiple fields.
 #[derive(Debug, Clone, Default)]
 pub struct ValidationErrors {
-    pub errors: Vec<FieldError>,
+    pub errors: FieldErrors,
 }
 
 impl ValidationErrors {
     /// Create a new empty validation errors collection.
     pub fn new() -> Self {
-        Self { errors: Vec::new() }
+        Self {
+            errors: FieldErrors::new(),
+        }
+    }
+
+    /// Create an empty collection with room for `capacity` errors.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            errors: FieldErrors::with_capacity(capacity),
+        }
     }
 
     /// Add a field error.
     pub fn add(
         &mut self,
         field: impl Into<String>,
-        code: impl Into<String>,
+        code: impl Into<Cow<'static, str>>,
         message: impl Into<String>,
     ) {
         self.errors.push(FieldError::new(field, code, message));
@@ -301,7 +1367,7 @@ impl ValidationErrors {
     pub fn add_with_value(
         &mut self,
         field: impl Into<String>,
-        code: impl Into<String>,
+        code: impl Into<Cow<'static, str>>,
         message: impl Into<String>,
         received: impl Into<serde_json::Value>,
     ) {
@@ -319,6 +1385,33 @@ impl ValidationErrors {
         self.errors.len()
     }
 
+    /// Add the errors of an embedded struct's validator under `prefix`, so
+    /// `street` in `child` is reported as `address.street`.
+    ///
+    /// Prefixes may index into lists (`items[0]`); paths are rendered dotted
+    /// or as JSON pointers per [`ErrorsConfig::field_paths`].
+    pub fn nested(&mut self, prefix: &str, child: ValidationErrors) {
+        self.errors
+            .extend(child.errors.into_iter().map(|mut error| {
+                error.field = match error.field.as_str() {
+                    "" => prefix.to_string(),
+                    field if field.starts_with('[') => format!("{prefix}{field}"),
+                    field => format!("{prefix}.{field}"),
+                };
+                error
+            }));
+    }
+
+    /// Iterate over the field errors in the order they were added.
+    pub fn iter(&self) -> std::slice::Iter<'_, FieldError> {
+        self.errors.iter()
+    }
+
+    /// The field error at `index`, if any.
+    pub fn get(&self, index: usize) -> Option<&FieldError> {
+        self.errors.get(index)
+    }
+
     /// Convert to AppError if there are errors, otherwise Ok(()).
     pub fn into_result(self) -> Result<(), AppError> {
         if self.is_empty() {
@@ -334,33 +1427,136 @@ impl std::fmt::Display for ValidationErrors {
         let messages: Vec<_> = self
             .errors
             .iter()
-            .map(|e| format!("{}: {}", e.field, e.message))
+            .map(|e| match e.field.as_str() {
+                "" => e.message.clone(),
+                field => format!("{field}: {}", e.message),
+            })
             .collect();
         write!(f, "{}", messages.join(", "))
     }
 }
 
+/// Convert a dotted path (`items[0].name`) to a JSON pointer
+/// (`/items/0/name`), escaping `~` and `/` in segments (RFC 6901).
+fn json_pointer(path: &str) -> String {
+    let mut pointer = String::with_capacity(path.len() + 1);
+    for segment in path.split(['.', '[']) {
+        let segment = segment.strip_suffix(']').unwrap_or(segment);
+        if segment.is_empty() {
+            continue;
+        }
+        pointer.push('/');
+        pointer.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+    }
+    pointer
+}
+
+impl std::ops::Index<usize> for ValidationErrors {
+    type Output = FieldError;
+
+    fn index(&self, index: usize) -> &FieldError {
+        &self.errors[index]
+    }
+}
+
+impl FromIterator<FieldError> for ValidationErrors {
+    fn from_iter<I: IntoIterator<Item = FieldError>>(iter: I) -> Self {
+        Self {
+            errors: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<FieldError> for ValidationErrors {
+    fn extend<I: IntoIterator<Item = FieldError>>(&mut self, iter: I) {
+        self.errors.extend(iter);
+    }
+}
+
+impl IntoIterator for ValidationErrors {
+    type Item = FieldError;
+    type IntoIter = smallvec::IntoIter<[FieldError; 1]>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ValidationErrors {
+    type Item = &'a FieldError;
+    type IntoIter = std::slice::Iter<'a, FieldError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.iter()
+    }
+}
+
+impl From<sea_orm::DbErr> for AppError {
+    #[track_caller]
+    fn from(error: sea_orm::DbErr) -> Self {
+        AppError::DatabaseError(Arc::new(error)).created()
+    }
+}
+
 impl From<ValidationErrors> for AppError {
     fn from(errors: ValidationErrors) -> Self {
         AppError::Validation(errors)
     }
 }
 
+impl From<FieldError> for AppError {
+    fn from(error: FieldError) -> Self {
+        AppError::Validation(ValidationErrors {
+            errors: smallvec![error],
+        })
+    }
+}
+
 // =============================================================================
 // Legacy Compatibility (deprecated, will be removed)
 // =============================================================================
 
-/// Legacy error response format.
-///
-/// **Deprecated**: Use `ProblemDetails` instead.
-#[deprecated(since = "0.2.0", note = "Use ProblemDetails instead")]
-#[derive(Serialize, ToSchema)]
-pub struct ErrorResponse {
-    pub error: String,
-    pub message: String,
-    pub code: String,
-    pub request_id: String,
-    pub timestamp: String,
+#[cfg(feature = "legacy")]
+#[allow(deprecated)]
+pub use legacy::ErrorResponse;
+
+#[cfg(feature = "legacy")]
+#[allow(deprecated)]
+mod legacy {
+    use serde::Serialize;
+    use utoipa::ToSchema;
+
+    use super::ProblemDetails;
+
+    /// Legacy error response format.
+    ///
+    /// **Deprecated**: Use `ProblemDetails` instead.
+    #[deprecated(since = "0.2.0", note = "Use ProblemDetails instead")]
+    #[derive(Serialize, ToSchema)]
+    pub struct ErrorResponse {
+        pub error: String,
+        pub message: String,
+        pub code: String,
+        pub request_id: String,
+        pub timestamp: String,
+    }
+
+    /// Legacy rendering of a problem, used by `ErrorFormat::Legacy`.
+    impl From<&ProblemDetails> for ErrorResponse {
+        fn from(problem: &ProblemDetails) -> Self {
+            Self {
+                error: problem.title.to_string(),
+                message: problem.detail.clone(),
+                code: problem.code.to_string(),
+                request_id: problem.request_id.clone(),
+                timestamp: problem
+                    .timestamp
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+            }
+        }
+    }
 }
 
 // =============================================================================
@@ -370,3 +1566,84 @@ pub struct ErrorResponse {
 pub mod prelude {
     pub use super::{AppError, FieldError, ProblemDetails, ValidationErrors};
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn varies_on(config: &ErrorsConfig) -> Vec<String> {
+        let mut headers = HeaderMap::new();
+        vary(config, &mut headers);
+        headers
+            .get_all(header::VARY)
+            .iter()
+            .map(|value| value.to_str().unwrap().to_string())
+            .collect()
+    }
+
+    fn database_error() -> AppError {
+        AppError::DatabaseError(Arc::new(sea_orm::DbErr::Custom("connection reset".into())))
+    }
+
+    fn diagnostics(
+        config: &ErrorsConfig,
+        error: AppError,
+    ) -> serde_json::Map<String, serde_json::Value> {
+        let mut problem = error.to_problem_details();
+        Diagnostics::of(config, &error).attach(config, &mut problem);
+        problem.extensions
+    }
+
+    #[test]
+    fn causes_are_returned_only_when_enabled_and_not_canned() {
+        let extensions = diagnostics(&ErrorsConfig::new().causes(true), database_error());
+        assert_eq!(
+            extensions["causes"],
+            serde_json::json!(["Custom Error: connection reset"])
+        );
+
+        assert!(!diagnostics(&ErrorsConfig::new(), database_error()).contains_key("causes"));
+        let canned = ErrorsConfig::production().causes(true);
+        assert!(!diagnostics(&canned, database_error()).contains_key("causes"));
+    }
+
+    #[test]
+    fn captured_backtrace_is_returned_when_enabled() {
+        let error = AppError::Annotated(Box::new(Annotated {
+            severity: Severity::Error,
+            error: database_error(),
+            logged: false,
+            location: None,
+            backtrace: Some(Arc::new(Backtrace::force_capture())),
+        }));
+
+        let extensions = diagnostics(&ErrorsConfig::development(), error.clone());
+        assert!(extensions["backtrace"].is_string());
+        assert!(!diagnostics(&ErrorsConfig::new(), error).contains_key("backtrace"));
+    }
+
+    #[test]
+    fn presets_differ_in_diagnostics() {
+        let development = ErrorsConfig::development();
+        assert!(development.causes && development.backtraces);
+        let production = ErrorsConfig::production();
+        assert!(!production.causes && !production.backtraces);
+        let default = ErrorsConfig::new();
+        assert!(!default.causes && !default.backtraces);
+    }
+
+    #[test]
+    fn varies_on_negotiated_headers() {
+        assert_eq!(varies_on(&ErrorsConfig::new()), ["x-error-format"]);
+
+        let config = ErrorsConfig::new()
+            .problem_profile("https://example.com/profiles/v2", |_| {
+                serde_json::Value::Null
+            })
+            .localizer(Arc::new(|_, _| None));
+        assert_eq!(
+            varies_on(&config),
+            ["x-error-format", "accept", "accept-language"]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Audit records for authorization failures.
+//!
+//! Whenever an `Unauthorized`, `Forbidden`, or CSRF/origin error is rendered,
+//! an [`AuditEvent`] is built from the error and the current request context
+//! and handed to the configured audit hook. Without a hook the event is emitted on
+//! the `eywa_errors::audit` tracing target, separate from the error log.
+//!
+//! Cross-tenant access (`TenantMismatch`) is always logged as a security event
+//! on the `eywa_errors::security` target, with both tenant IDs, even though the
+//! client only sees a 404.
+
+use std::sync::Arc;
+
+use super::app_error::AppError;
+use super::config::get_errors_config;
+use super::context::get_request_context;
+
+/// Callback receiving audit events.
+pub type AuditHook = Arc<dyn Fn(&AuditEvent) + Send + Sync>;
+
+/// Structured audit record for a 401/403 response.
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    /// Unique request identifier for tracing.
+    pub request_id: String,
+
+    /// HTTP status code returned (401 or 403).
+    pub status: u16,
+
+    /// Authenticated principal, if known.
+    pub actor: Option<String>,
+
+    /// The action that was denied (`Forbidden`), or `csrf` / `origin <origin>`
+    /// for CSRF and origin failures.
+    pub action: Option<String>,
+
+    /// HTTP method of the request, if known.
+    pub method: Option<String>,
+
+    /// The resource (request path) being accessed, if known.
+    pub resource: Option<String>,
+
+    /// Permissions required for the action (`Forbidden` only).
+    pub required_permissions: Vec<String>,
+
+    /// Permissions the actor holds (`Forbidden` only). Never sent to clients.
+    pub actor_permissions: Vec<String>,
+}
+
+impl AuditEvent {
+    /// Build the audit event for an error, if it is an authorization failure.
+    ///
+    /// The request ID is filled in by [`emit`] once the problem is rendered.
+    pub(crate) fn for_error(error: &AppError, status: u16) -> Option<Self> {
+        let mut required_permissions = Vec::new();
+        let mut actor_permissions = Vec::new();
+
+        let action = match error.base() {
+            AppError::Unauthorized => None,
+            AppError::Forbidden {
+                action,
+                required_permissions: required,
+                actor_permissions: held,
+            } => {
+                required_permissions.clone_from(required);
+                actor_permissions.clone_from(held);
+                Some(action.clone())
+            }
+            AppError::InvalidSignature { provider, .. } => Some(format!("webhook {provider}")),
+            AppError::CsrfFailure => Some("csrf".to_string()),
+            AppError::InvalidOrigin { origin } => Some(format!("origin {origin}")),
+            _ => return None,
+        };
+        let context = get_request_context().unwrap_or_default();
+
+        Some(Self {
+            request_id: String::new(),
+            status,
+            actor: context.actor,
+            action,
+            method: context.method,
+            resource: context.path,
+            required_permissions,
+            actor_permissions,
+        })
+    }
+}
+
+/// Deliver an audit event to the configured hook.
+pub(crate) fn emit(mut event: AuditEvent, request_id: &str) {
+    event.request_id = request_id.to_string();
+
+    match &get_errors_config().audit_hook {
+        Some(hook) => hook(&event),
+        None => tracing::info!(
+            target: "eywa_errors::audit",
+            request_id = %event.request_id,
+            http.response.status_code = event.status,
+            actor = event.actor.as_deref(),
+            action = event.action.as_deref(),
+            http.request.method = event.method.as_deref(),
+            resource = event.resource.as_deref(),
+            required_permissions = ?event.required_permissions,
+            actor_permissions = ?event.actor_permissions,
+            "Authorization failure"
+        ),
+    }
+}
+
+/// Cross-tenant access attempt, logged with the IDs hidden from the client.
+pub(crate) struct TenantViolation {
+    resource: String,
+    id: String,
+    resource_tenant: String,
+}
+
+impl TenantViolation {
+    pub(crate) fn for_error(error: &AppError) -> Option<Self> {
+        match error.base() {
+            AppError::TenantMismatch {
+                resource,
+                id,
+                tenant,
+            } => Some(Self {
+                resource: resource.clone(),
+                id: id.clone(),
+                resource_tenant: tenant.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Log the violation; never throttled or sampled.
+    pub(crate) fn emit(self, request_id: &str) {
+        let context = get_request_context().unwrap_or_default();
+
+        tracing::error!(
+            target: "eywa_errors::security",
+            request_id = %request_id,
+            resource = %self.resource,
+            id = %self.id,
+            resource_tenant = %self.resource_tenant,
+            request_tenant = context.tenant.as_deref(),
+            actor = context.actor.as_deref(),
+            http.request.method = context.method.as_deref(),
+            url.path = context.path.as_deref(),
+            "Tenant isolation violation"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Conversions from axum body extraction rejections.
+//!
+//! Buffering a body can fail for reasons that deserve distinct codes: the
+//! body exceeded the size limit, the client sent it too slowly or went away,
+//! or it could not be decompressed. axum reports all of them as a
+//! `FailedToBufferBody` rejection; these conversions look inside.
+//!
+//! # Example
+//! ```ignore
+//! async fn upload(body: Result<Bytes, BytesRejection>) -> Result<StatusCode, AppError> {
+//!     let body = body?;
+//!     ...
+//! }
+//! ```
+
+use std::error::Error;
+use std::io;
+
+use axum::extract::rejection::{BytesRejection, FailedToBufferBody, StringRejection};
+
+use super::app_error::AppError;
+use super::box_error::is_disconnect;
+
+impl From<FailedToBufferBody> for AppError {
+    fn from(rejection: FailedToBufferBody) -> Self {
+        match rejection {
+            FailedToBufferBody::LengthLimitError(_) => AppError::BodyTooLarge { limit: None },
+            rejection => classify_body_error(&rejection).unwrap_or_else(|| {
+                AppError::BadRequest("Failed to read the request body".to_string())
+            }),
+        }
+    }
+}
+
+impl From<BytesRejection> for AppError {
+    fn from(rejection: BytesRejection) -> Self {
+        match rejection {
+            BytesRejection::FailedToBufferBody(rejection) => AppError::from(rejection),
+            rejection => AppError::BadRequest(rejection.body_text()),
+        }
+    }
+}
+
+impl From<StringRejection> for AppError {
+    fn from(rejection: StringRejection) -> Self {
+        match rejection {
+            StringRejection::FailedToBufferBody(rejection) => AppError::from(rejection),
+            rejection => AppError::BadRequest(rejection.body_text()),
+        }
+    }
+}
+
+/// Classify an error raised while reading a request body by walking its
+/// source chain.
+pub(crate) fn classify_body_error(error: &(dyn Error + 'static)) -> Option<AppError> {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(io_error) = error.downcast_ref::<io::Error>() {
+            match io_error.kind() {
+                io::ErrorKind::TimedOut => return Some(AppError::BodyReadTimeout),
+                kind if is_disconnect(kind) => return Some(AppError::ClientDisconnected),
+                // Decompression layers report corrupt input as invalid data.
+                io::ErrorKind::InvalidData => {
+                    return Some(AppError::InvalidContentEncoding { encoding: None });
+                }
+                _ => {}
+            }
+        }
+        #[cfg(feature = "hyper")]
+        if let Some(hyper_error) = error.downcast_ref::<hyper::Error>() {
+            if hyper_error.is_timeout() {
+                return Some(AppError::BodyReadTimeout);
+            }
+            if hyper_error.is_canceled() || hyper_error.is_incomplete_message() {
+                return Some(AppError::ClientDisconnected);
+            }
+        }
+        // `http_body_util::Limited`, reached without axum's wrapper.
+        if error.to_string() == "length limit exceeded" {
+            return Some(AppError::BodyTooLarge { limit: None });
+        }
+        current = error.source();
+    }
+    None
+}
@@ -0,0 +1,99 @@
+//! Canonical conversion of boxed errors at the response boundary.
+//!
+//! Middleware reports failures as `BoxError` (axum's `HandleErrorLayer`,
+//! tower layers, body streams). [`AppError::from_box_error`] recognizes the
+//! error types we know, hands the rest to the converter registry, and maps
+//! whatever is left to a 500 without leaking its text, so every service can
+//! use it as its one handler.
+//!
+//! # Example
+//! ```ignore
+//! let app = Router::new().route("/", get(handler)).layer(
+//!     ServiceBuilder::new()
+//!         .layer(HandleErrorLayer::new(|error: BoxError| async move {
+//!             AppError::from_box_error(error)
+//!         }))
+//!         .timeout(Duration::from_secs(10)),
+//! );
+//! ```
+
+use std::io;
+#[cfg(feature = "tower")]
+use std::time::Duration;
+
+use axum::BoxError;
+
+use super::app_error::AppError;
+
+/// `Retry-After` sent with load-shed responses.
+#[cfg(feature = "tower")]
+const SHED_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+impl AppError {
+    /// Map a boxed error to an `AppError`.
+    ///
+    /// - a boxed `AppError` is passed through unchanged
+    /// - an `axum::Error` is unwrapped and its inner error mapped
+    /// - `tower::timeout::error::Elapsed` → 504 Gateway Timeout
+    /// - `tower::load_shed::error::Overloaded` → 503 `Overloaded`
+    /// - `tower::buffer::error::Closed` / `ServiceError` → 503 Service Unavailable
+    /// - a `hyper::Error` (with the `hyper` feature) → see its `From` impl
+    /// - an `io::Error` from the client going away (reset, broken pipe) →
+    ///   499 `ClientDisconnected`
+    /// - an `io::Error` of kind `TimedOut` → 504 Gateway Timeout
+    /// - anything else → [`AppError::from_dyn`], which applies the
+    ///   registered converters or falls back to a sanitized 500
+    pub fn from_box_error(error: BoxError) -> AppError {
+        let error = match error.downcast::<AppError>() {
+            Ok(error) => return *error,
+            Err(error) => error,
+        };
+        let error = match error.downcast::<axum::Error>() {
+            Ok(error) => return AppError::from_box_error(error.into_inner()),
+            Err(error) => error,
+        };
+
+        #[cfg(feature = "tower")]
+        if error.is::<tower::timeout::error::Elapsed>() {
+            return AppError::GatewayTimeout("Request timed out".to_string());
+        } else if error.is::<tower::load_shed::error::Overloaded>() {
+            return AppError::Overloaded {
+                queue_depth: None,
+                retry_after: SHED_RETRY_AFTER,
+            };
+        } else if error.is::<tower::buffer::error::Closed>()
+            || error.is::<tower::buffer::error::ServiceError>()
+        {
+            return AppError::ServiceUnavailable("Service is not accepting requests".to_string());
+        }
+
+        #[cfg(feature = "hyper")]
+        let error = match error.downcast::<hyper::Error>() {
+            Ok(error) => return AppError::from(*error),
+            Err(error) => error,
+        };
+
+        if let Some(error) = error.downcast_ref::<io::Error>() {
+            match error.kind() {
+                io::ErrorKind::TimedOut => {
+                    return AppError::GatewayTimeout("Request timed out".to_string());
+                }
+                kind if is_disconnect(kind) => return AppError::ClientDisconnected,
+                _ => {}
+            }
+        }
+
+        AppError::from_dyn(&*error)
+    }
+}
+
+/// I/O error kinds reported when the peer closes or resets the connection.
+pub(crate) fn is_disconnect(kind: io::ErrorKind) -> bool {
+    matches!(
+        kind,
+        io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::UnexpectedEof
+    )
+}
@@ -0,0 +1,169 @@
+//! Consistency checks for the problem type catalog.
+//!
+//! The taxonomy is shared by every service, and each one can register its
+//! own types. [`verify`] checks the built-in and registered types together,
+//! so a service's test suite catches an inconsistent addition before it
+//! ships.
+//!
+//! # Example
+//! ```ignore
+//! #[test]
+//! fn error_catalog_is_consistent() {
+//!     register_problem_types();
+//!     eywa_errors::catalog::verify().unwrap();
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::problem_type::ProblemType;
+
+/// Types that are deliberately not errors: a `304` and a degraded-but-ready
+/// health report.
+const NON_ERROR_CODES: &[&str] = &["NOT_MODIFIED", "DEGRADED"];
+
+/// A problem type that breaks an invariant of the catalog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Code of the offending type.
+    pub code: &'static str,
+    /// What is wrong with it.
+    pub reason: String,
+}
+
+/// Every violation found by [`verify`].
+#[derive(Clone, PartialEq, Eq)]
+pub struct Violations(pub Vec<Violation>);
+
+impl fmt::Display for Violations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} problem type catalog violation(s):", self.0.len())?;
+        for violation in &self.0 {
+            write!(f, "\n  - {}: {}", violation.code, violation.reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Shows the list, so `verify().unwrap()` fails a test readably.
+impl fmt::Debug for Violations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for Violations {}
+
+/// Check the built-in and registered problem types:
+///
+/// - the `type` URI resolves: a non-empty lowercase slug under the default
+///   base, or an absolute URI
+/// - the code is `UPPER_SNAKE_CASE`, or `namespace.lower_snake_case`
+/// - the status is in the 4xx/5xx class, except for non-error types
+/// - no two types share a title or a URI
+pub fn verify() -> Result<(), Violations> {
+    let all = ProblemType::all();
+    let mut violations = Vec::new();
+    let mut violation = |code: &'static str, reason: String| {
+        violations.push(Violation { code, reason });
+    };
+
+    for problem_type in &all {
+        let code = problem_type.code;
+        match problem_type.slug() {
+            Some(slug) if !is_slug(slug) => {
+                violation(
+                    code,
+                    format!("type URI slug {slug:?} is not lowercase kebab-case"),
+                );
+            }
+            None if !is_absolute(problem_type.uri) => {
+                violation(
+                    code,
+                    format!("type URI {:?} is not absolute", problem_type.uri),
+                );
+            }
+            _ => {}
+        }
+        if !is_code(code) {
+            violation(
+                code,
+                "code is neither UPPER_SNAKE_CASE nor namespace.lower_snake_case".to_string(),
+            );
+        }
+        let status = problem_type.default_status;
+        let is_error = status.is_client_error() || status.is_server_error();
+        if !is_error && !NON_ERROR_CODES.contains(&code) {
+            violation(
+                code,
+                format!("status {} is not an error status", status.as_u16()),
+            );
+        }
+    }
+
+    let mut titles: HashMap<&str, &str> = HashMap::new();
+    let mut uris: HashMap<&str, &str> = HashMap::new();
+    for problem_type in &all {
+        if let Some(other) = titles.insert(problem_type.title, problem_type.code) {
+            violation(
+                problem_type.code,
+                format!("title {:?} is also used by {other}", problem_type.title),
+            );
+        }
+        // `about:blank` stands for "no specific type" and may repeat.
+        if problem_type.uri != "about:blank"
+            && let Some(other) = uris.insert(problem_type.uri, problem_type.code)
+        {
+            violation(
+                problem_type.code,
+                format!("type URI is also used by {other}"),
+            );
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(Violations(violations))
+    }
+}
+
+fn is_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && slug.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+}
+
+fn is_absolute(uri: &str) -> bool {
+    uri.split_once(':').is_some_and(|(scheme, rest)| {
+        !rest.is_empty()
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+fn is_code(code: &str) -> bool {
+    let snake = |part: &str, upper: bool| {
+        !part.is_empty()
+            && part.chars().all(|c| {
+                c == '_'
+                    || c.is_ascii_digit()
+                    || if upper {
+                        c.is_ascii_uppercase()
+                    } else {
+                        c.is_ascii_lowercase()
+                    }
+            })
+    };
+    match code.split_once('.') {
+        Some((namespace, name)) => snake(namespace, false) && snake(name, false),
+        None => snake(code, true),
+    }
+}
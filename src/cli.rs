@@ -0,0 +1,90 @@
+//! Exit codes and error reports for command-line tools.
+//!
+//! Exit codes follow the BSD `sysexits.h` conventions so shell scripts and
+//! supervisors can tell usage mistakes from outages.
+
+use std::io::IsTerminal;
+
+use super::app_error::AppError;
+
+/// Command line usage error (`EX_USAGE`).
+pub const EX_USAGE: i32 = 64;
+/// Input data was incorrect (`EX_DATAERR`).
+pub const EX_DATAERR: i32 = 65;
+/// Input did not exist (`EX_NOINPUT`).
+pub const EX_NOINPUT: i32 = 66;
+/// A required service is unavailable (`EX_UNAVAILABLE`).
+pub const EX_UNAVAILABLE: i32 = 69;
+/// Internal software error (`EX_SOFTWARE`).
+pub const EX_SOFTWARE: i32 = 70;
+/// Insufficient permission (`EX_NOPERM`).
+pub const EX_NOPERM: i32 = 77;
+/// Configuration error (`EX_CONFIG`).
+pub const EX_CONFIG: i32 = 78;
+
+impl AppError {
+    /// Process exit code for this error, following `sysexits.h`.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AppError::Annotated(annotated) => annotated.error.exit_code(),
+            AppError::Validation(_)
+            | AppError::ValidationField { .. }
+            | AppError::BadRequest(_)
+            | AppError::InvalidCursor { .. }
+            | AppError::InvalidContentEncoding { .. }
+            | AppError::NotAcceptable { .. }
+            | AppError::MethodNotAllowed { .. }
+            | AppError::HeadersTooLarge { .. }
+            | AppError::UriTooLong { .. }
+            | AppError::HttpVersionNotSupported
+            | AppError::UpgradeRequired { .. } => EX_USAGE,
+            AppError::NotFound { .. } => EX_NOINPUT,
+            AppError::Conflict { .. }
+            | AppError::IdempotencyConflict { .. }
+            | AppError::IdempotencyInProgress { .. }
+            | AppError::PreconditionFailed { .. }
+            | AppError::NotModified { .. }
+            | AppError::PaymentDeclined { .. }
+            | AppError::BodyTooLarge { .. }
+            | AppError::ExpiredCursor => EX_DATAERR,
+            AppError::Unauthorized
+            | AppError::Forbidden { .. }
+            | AppError::TenantMismatch { .. }
+            | AppError::InvalidSignature { .. }
+            | AppError::CsrfFailure
+            | AppError::InvalidOrigin { .. } => EX_NOPERM,
+            AppError::ConfigError { .. } => EX_CONFIG,
+            AppError::ExternalServiceError { .. }
+            | AppError::ServiceUnavailable(_)
+            | AppError::GatewayTimeout(_)
+            | AppError::DeadlineExceeded
+            | AppError::ClientDisconnected
+            | AppError::BodyReadTimeout
+            | AppError::Overloaded { .. }
+            | AppError::RateLimited { .. }
+            | AppError::Draining { .. } => EX_UNAVAILABLE,
+            AppError::DatabaseError(_) | AppError::InternalServerError(_) => EX_SOFTWARE,
+            AppError::Remote(envelope) => match envelope.status {
+                400 | 422 => EX_USAGE,
+                401 | 403 => EX_NOPERM,
+                404 | 410 => EX_NOINPUT,
+                409 => EX_DATAERR,
+                502..=504 => EX_UNAVAILABLE,
+                _ => EX_SOFTWARE,
+            },
+        }
+    }
+
+    /// Print a [`Report`](crate::Report) to stderr and exit with
+    /// [`AppError::exit_code`].
+    ///
+    /// Colors are used when stderr is a terminal and `NO_COLOR` is not set.
+    #[track_caller]
+    pub fn report_and_exit(&self) -> ! {
+        let stderr = std::io::stderr();
+        let color = stderr.is_terminal() && std::env::var_os("NO_COLOR").is_none();
+
+        eprint!("{}", self.report().color(color));
+        std::process::exit(self.exit_code())
+    }
+}
@@ -0,0 +1,93 @@
+//! Reading problems on the client side.
+//!
+//! Frontends and service clients receive the problem bodies rendered by this
+//! crate; [`Problem`] parses one and exposes what a UI needs: the code to
+//! pick a message by, and the field errors to show next to form inputs.
+//!
+//! The module only uses the serde types of the crate, none of its server
+//! integrations: with `default-features = false, features = ["chrono",
+//! "client"]` it builds without tokio or axum, e.g. for WebAssembly.
+//!
+//! # Example
+//! ```ignore
+//! let body = response.bytes().await?;
+//! if let Some(problem) = Problem::parse(&body) {
+//!     let messages = problem.field_messages();
+//!     email_error.set(messages.get("email").and_then(|m| m.first()).map(|m| m.to_string()));
+//!     toast(problem.message_or(|code| translations.get(code).cloned()));
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use super::problem::ProblemDetails;
+
+/// A problem received from a service.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem(ProblemDetails);
+
+impl Problem {
+    /// Parse a problem body; `None` if it is not a problem.
+    ///
+    /// Bodies from newer or older versions of this crate parse (see
+    /// [`ProblemDetails`]); only the `status` member is required.
+    pub fn parse(body: &[u8]) -> Option<Self> {
+        serde_json::from_slice(body).ok().map(Self)
+    }
+
+    /// Machine-readable error code (e.g. `NOT_FOUND`); empty if the service
+    /// sent none.
+    pub fn code(&self) -> &str {
+        &self.0.code
+    }
+
+    /// HTTP status code.
+    pub fn status(&self) -> u16 {
+        self.0.status
+    }
+
+    /// Explanation of the problem: its `detail`, or its `title` if the detail
+    /// is empty.
+    pub fn message(&self) -> &str {
+        if self.0.detail.is_empty() {
+            &self.0.title
+        } else {
+            &self.0.detail
+        }
+    }
+
+    /// Text to show for the problem: `lookup` of its code (typically a
+    /// translation table), falling back to [`Problem::message`].
+    pub fn message_or(&self, lookup: impl FnOnce(&str) -> Option<String>) -> String {
+        lookup(self.code()).unwrap_or_else(|| self.message().to_string())
+    }
+
+    /// Messages of the field errors, by field, in the order they were
+    /// reported.
+    pub fn field_messages(&self) -> HashMap<&str, Vec<&str>> {
+        let mut messages: HashMap<&str, Vec<&str>> = HashMap::new();
+        for error in &self.0.errors {
+            messages
+                .entry(error.field.as_str())
+                .or_default()
+                .push(error.message.as_str());
+        }
+        messages
+    }
+
+    /// The parsed problem.
+    pub fn problem(&self) -> &ProblemDetails {
+        &self.0
+    }
+
+    /// Unwrap the parsed problem.
+    pub fn into_problem(self) -> ProblemDetails {
+        self.0
+    }
+}
+
+impl From<ProblemDetails> for Problem {
+    fn from(problem: ProblemDetails) -> Self {
+        Self(problem)
+    }
+}
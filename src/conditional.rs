@@ -0,0 +1,145 @@
+//! Conditional request evaluation (RFC 7232).
+//!
+//! [`check_preconditions`] evaluates `If-Match`, `If-Unmodified-Since`,
+//! `If-None-Match`, and `If-Modified-Since` in the order RFC 7232 §6
+//! prescribes and fails with `PreconditionFailed` (412) or `NotModified`
+//! (304), both rendered with the current `ETag` (and `Last-Modified` on 304).
+//!
+//! # Example
+//! ```ignore
+//! async fn update_user(method: Method, headers: HeaderMap, ...) -> Result<Json<User>, AppError> {
+//!     let user = repo.get(id).await?;
+//!     check_preconditions(&method, &headers, Some(&user.etag()), Some(user.updated_at))?;
+//!     ...
+//! }
+//! ```
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::http::{HeaderMap, Method, header};
+
+use super::app_error::AppError;
+
+/// Evaluate the request's preconditions against the current state of the
+/// resource.
+///
+/// `current_etag` is the entity tag as sent in `ETag` (e.g. `"v42"` or
+/// `W/"v42"`; a bare `v42` is quoted), `None` if the resource does not
+/// exist. `method` is the request method: a match on `If-None-Match` is a
+/// 304 for `GET`/`HEAD` and a 412 otherwise.
+pub fn check_preconditions(
+    method: &Method,
+    headers: &HeaderMap,
+    current_etag: Option<&str>,
+    last_modified: Option<SystemTime>,
+) -> Result<(), AppError> {
+    let safe = method == Method::GET || method == Method::HEAD;
+    let current_etag = current_etag.map(quoted);
+    let current = current_etag.as_deref();
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    let date = |name| header(name).and_then(|value| httpdate::parse_http_date(value).ok());
+
+    let precondition_failed = || AppError::PreconditionFailed {
+        etag: current_etag.clone(),
+    };
+
+    // 1-2: the client's copy must still be current for the request to apply.
+    if let Some(if_match) = header(header::IF_MATCH) {
+        if !matches_any(if_match, current, strong_eq) {
+            return Err(precondition_failed());
+        }
+    } else if let Some(since) = date(header::IF_UNMODIFIED_SINCE)
+        && let Some(modified) = last_modified
+        && whole_seconds(modified) > whole_seconds(since)
+    {
+        return Err(precondition_failed());
+    }
+
+    // 3-4: the client's cached copy may already be current.
+    let not_modified = || AppError::NotModified {
+        etag: current_etag.clone(),
+        last_modified,
+    };
+    if let Some(if_none_match) = header(header::IF_NONE_MATCH) {
+        if matches_any(if_none_match, current, weak_eq) {
+            return Err(if safe {
+                not_modified()
+            } else {
+                precondition_failed()
+            });
+        }
+    } else if safe
+        && let Some(since) = date(header::IF_MODIFIED_SINCE)
+        && let Some(modified) = last_modified
+        && whole_seconds(modified) <= whole_seconds(since)
+    {
+        return Err(not_modified());
+    }
+
+    Ok(())
+}
+
+/// Whether an `If-Match` / `If-None-Match` value matches the current tag.
+fn matches_any(value: &str, current: Option<&str>, eq: fn(&str, &str) -> bool) -> bool {
+    let Some(current) = current else {
+        return false;
+    };
+    if value.trim() == "*" {
+        return true;
+    }
+    value
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| eq(candidate, current))
+}
+
+/// Strong comparison: both tags strong and identical.
+fn strong_eq(a: &str, b: &str) -> bool {
+    !a.starts_with("W/") && !b.starts_with("W/") && a == b
+}
+
+/// Weak comparison: opaque tags identical, ignoring weakness.
+fn weak_eq(a: &str, b: &str) -> bool {
+    a.trim_start_matches("W/") == b.trim_start_matches("W/")
+}
+
+fn quoted(etag: &str) -> String {
+    if etag.starts_with('"') || etag.starts_with("W/") {
+        etag.to_string()
+    } else {
+        format!("\"{etag}\"")
+    }
+}
+
+/// HTTP dates have one-second resolution.
+fn whole_seconds(at: SystemTime) -> u64 {
+    at.duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn if_none_match(etag: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static(etag));
+        headers
+    }
+
+    #[test]
+    fn matching_if_none_match_depends_on_the_method() {
+        let headers = if_none_match("\"v1\"");
+
+        let error = check_preconditions(&Method::GET, &headers, Some("v1"), None).unwrap_err();
+        assert!(matches!(error, AppError::NotModified { .. }));
+
+        let error = check_preconditions(&Method::PUT, &headers, Some("v1"), None).unwrap_err();
+        assert!(matches!(error, AppError::PreconditionFailed { .. }));
+
+        assert!(check_preconditions(&Method::PUT, &headers, Some("v2"), None).is_ok());
+    }
+}
@@ -0,0 +1,542 @@
+//! Process-wide configuration for error rendering and logging.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::{Duration, SystemTime};
+
+use axum::http::{HeaderValue, StatusCode};
+
+use super::app_error::ProblemDetails;
+use super::audit::{AuditEvent, AuditHook};
+use super::cors::CorsPolicy;
+use super::limits::BodyLimits;
+use super::locale::Localizer;
+use super::log_throttle::LogThrottle;
+use super::problem_type::ProblemType;
+use super::profile::ProfileSerializer;
+use super::scrub::Scrubber;
+use super::severity::Severity;
+use super::slo::SloPolicy;
+use super::template;
+use super::timestamp::{Clock, TimestampFormat, TimestampPrecision};
+
+/// Environment variable selecting the preset loaded by
+/// [`ErrorsConfig::from_env`]: `production`, `staging`, or `development`.
+pub const ERRORS_MODE_ENV: &str = "EYWA_ERRORS_MODE";
+
+static ERRORS_CONFIG: LazyLock<RwLock<Arc<ErrorsConfig>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(ErrorsConfig::default())));
+
+/// Configuration consulted by `AppError` when it is rendered into a response.
+///
+/// Install it once at startup with [`set_errors_config`].
+///
+/// # Example
+/// ```ignore
+/// use std::time::Duration;
+/// use eywa_errors::{set_errors_config, ErrorsConfig, LogThrottle};
+///
+/// set_errors_config(
+///     ErrorsConfig::new().log_throttle(LogThrottle::new(10, Duration::from_secs(60))),
+/// );
+/// ```
+#[derive(Clone, Default)]
+pub struct ErrorsConfig {
+    pub(crate) log_throttle: Option<LogThrottle>,
+    pub(crate) audit_hook: Option<AuditHook>,
+    pub(crate) scrubber: Option<Scrubber>,
+    pub(crate) detail_policies: HashMap<String, DetailExposure>,
+    pub(crate) timestamp_format: TimestampFormat,
+    pub(crate) clock: Option<Clock>,
+    pub(crate) signature_statuses: HashMap<String, StatusCode>,
+    pub(crate) error_format: ErrorFormat,
+    pub(crate) profiles: HashMap<String, ProfileSerializer>,
+    pub(crate) field_naming: FieldNaming,
+    pub(crate) field_paths: FieldPathStyle,
+    pub(crate) type_base: Option<String>,
+    pub(crate) cache_policies: HashMap<StatusCode, HeaderValue>,
+    pub(crate) cors: Option<CorsPolicy>,
+    pub(crate) localizer: Option<Localizer>,
+    pub(crate) keep_canonical_text: bool,
+    pub(crate) body_limits: BodyLimits,
+    pub(crate) min_log_severity: Severity,
+    pub(crate) log_at_creation: bool,
+    pub(crate) causes: bool,
+    pub(crate) backtraces: bool,
+    pub(crate) slo: Option<SloPolicy>,
+    pub(crate) error_stats: bool,
+    pub(crate) recent_errors: usize,
+    pub(crate) header_size_limit: Option<usize>,
+    pub(crate) uri_length_limit: Option<usize>,
+}
+
+/// Casing of member names in serialized problems and field errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FieldNaming {
+    /// `request_id`, `queue_depth` (the default).
+    #[default]
+    SnakeCase,
+    /// `requestId`, `queueDepth`.
+    CamelCase,
+}
+
+/// Rendering of nested field error paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FieldPathStyle {
+    /// `address.street`, `items[0].name` (the default).
+    #[default]
+    Dotted,
+    /// `/address/street`, `/items/0/name` (RFC 6901).
+    JsonPointer,
+}
+
+/// Body format of rendered error responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorFormat {
+    /// RFC 7807 `application/problem+json` (the default).
+    #[default]
+    Problem,
+    /// The deprecated `ErrorResponse` shape as `application/json`, for
+    /// clients that have not migrated yet.
+    #[cfg(feature = "legacy")]
+    Legacy,
+    /// RFC 7807 members encoded as `application/problem+msgpack`, for
+    /// binary protocols.
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+    /// RFC 7807 members encoded as `application/problem+cbor`, for binary
+    /// protocols and constrained devices.
+    #[cfg(feature = "cbor")]
+    Cbor,
+}
+
+impl ErrorFormat {
+    /// Parse an `X-Error-Format` header value: `problem`, or `legacy`,
+    /// `msgpack`, and `cbor` with the feature of the same name.
+    pub fn from_header_value(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("problem") {
+            return Some(ErrorFormat::Problem);
+        }
+        #[cfg(feature = "legacy")]
+        if value.eq_ignore_ascii_case("legacy") {
+            return Some(ErrorFormat::Legacy);
+        }
+        #[cfg(feature = "msgpack")]
+        if value.eq_ignore_ascii_case("msgpack") {
+            return Some(ErrorFormat::MessagePack);
+        }
+        #[cfg(feature = "cbor")]
+        if value.eq_ignore_ascii_case("cbor") {
+            return Some(ErrorFormat::Cbor);
+        }
+        None
+    }
+}
+
+/// Whether an error's Display text is returned to clients as `detail`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetailExposure {
+    /// Return the error's Display text (the default).
+    Expose,
+    /// Return this fixed message instead. The full text is still logged.
+    Canned(String),
+}
+
+impl ErrorsConfig {
+    /// Create a configuration with the default behavior.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Safe defaults for production: secrets scrubbed, internal details
+    /// replaced with canned messages, logging throttled and limited to
+    /// warnings and above.
+    pub fn production() -> Self {
+        let internal = DetailExposure::Canned("An internal error occurred".into());
+
+        Self::new()
+            .scrubber(Scrubber::new())
+            .log_throttle(LogThrottle::new(10, Duration::from_secs(60)))
+            .min_log_severity(Severity::Warning)
+            .detail_exposure("INTERNAL_ERROR", internal.clone())
+            .detail_exposure("DATABASE_ERROR", internal)
+            .detail_exposure(
+                "CONFIG_ERROR",
+                DetailExposure::Canned("The service is misconfigured".into()),
+            )
+    }
+
+    /// Production-like, but with details and their causes exposed and every
+    /// error logged, so failures can be diagnosed from the response.
+    pub fn staging() -> Self {
+        Self::new().scrubber(Scrubber::new()).causes(true)
+    }
+
+    /// Nothing hidden or throttled, with the causes and creation backtrace of
+    /// each error in the response.
+    pub fn development() -> Self {
+        Self::new().causes(true).backtraces(true)
+    }
+
+    /// The preset named by [`ERRORS_MODE_ENV`], falling back to
+    /// [`production`](Self::production), with a log event, when it is unset or
+    /// unknown.
+    ///
+    /// # Example
+    /// ```ignore
+    /// set_errors_config(
+    ///     ErrorsConfig::from_env().type_base_uri("https://errors.internal.example"),
+    /// );
+    /// ```
+    pub fn from_env() -> Self {
+        let Ok(mode) = std::env::var(ERRORS_MODE_ENV) else {
+            tracing::info!("{ERRORS_MODE_ENV} is not set, using the production preset");
+            return Self::production();
+        };
+
+        match mode.trim().to_ascii_lowercase().as_str() {
+            "development" | "dev" => Self::development(),
+            "staging" => Self::staging(),
+            "production" | "prod" => Self::production(),
+            other => {
+                tracing::warn!(
+                    mode = other,
+                    "Unknown {ERRORS_MODE_ENV}, using the production preset"
+                );
+                Self::production()
+            }
+        }
+    }
+
+    /// Only log errors of at least `severity` when they are rendered.
+    pub fn min_log_severity(mut self, severity: Severity) -> Self {
+        self.min_log_severity = severity;
+        self
+    }
+
+    /// Log internal, configuration, database, and external service errors
+    /// when their helpers create them, inside the span where they happened,
+    /// instead of when they are rendered. See [`AppError::log_here`].
+    ///
+    /// [`AppError::log_here`]: crate::AppError::log_here
+    pub fn log_at_creation(mut self) -> Self {
+        self.log_at_creation = true;
+        self
+    }
+
+    /// Return the source chain of an error as a `causes` member (an array of
+    /// messages, outermost first). Off by default; errors whose detail is
+    /// canned never carry causes.
+    pub fn causes(mut self, enabled: bool) -> Self {
+        self.causes = enabled;
+        self
+    }
+
+    /// Capture a backtrace when the helpers for internal, database, and
+    /// external service errors create one, shown by the `{:#}` format and
+    /// returned as a `backtrace` member. Off by default: capturing is slow,
+    /// and backtraces reveal the service's code layout.
+    pub fn backtraces(mut self, enabled: bool) -> Self {
+        self.backtraces = enabled;
+        self
+    }
+
+    /// Track the error budget of each route against an availability objective.
+    pub fn slo(mut self, policy: SloPolicy) -> Self {
+        self.slo = Some(policy);
+        self
+    }
+
+    /// Count rendered errors by route, status, and code for
+    /// [`stats::snapshot`](crate::stats::snapshot).
+    pub fn error_stats(mut self) -> Self {
+        self.error_stats = true;
+        self
+    }
+
+    /// Keep the last `capacity` rendered problems for
+    /// [`stats::recent`](crate::stats::recent). Only enable this where the
+    /// scrubber and detail exposure policies make the bodies safe to show.
+    pub fn recent_errors(mut self, capacity: usize) -> Self {
+        self.recent_errors = capacity;
+        self
+    }
+
+    /// The server's header size limit in bytes (e.g. hyper's
+    /// `max_buf_size`), reported in `HeadersTooLarge` errors converted from
+    /// connection errors.
+    pub fn header_size_limit(mut self, limit: usize) -> Self {
+        self.header_size_limit = Some(limit);
+        self
+    }
+
+    /// The server's URI length limit in bytes, reported in `UriTooLong`
+    /// errors converted from connection errors.
+    pub fn uri_length_limit(mut self, limit: usize) -> Self {
+        self.uri_length_limit = Some(limit);
+        self
+    }
+
+    /// Rate-limit error logging per error fingerprint.
+    pub fn log_throttle(mut self, policy: LogThrottle) -> Self {
+        self.log_throttle = Some(policy);
+        self
+    }
+
+    /// Receive an audit event whenever a 401 or 403 response is rendered.
+    pub fn audit_hook(mut self, hook: impl Fn(&AuditEvent) + Send + Sync + 'static) -> Self {
+        self.audit_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Mask secrets in error payloads before they are logged or serialized.
+    pub fn scrubber(mut self, scrubber: Scrubber) -> Self {
+        self.scrubber = Some(scrubber);
+        self
+    }
+
+    /// Set the detail exposure policy for an error code (e.g. `CONFIG_ERROR`).
+    ///
+    /// # Example
+    /// ```ignore
+    /// use eywa_errors::{DetailExposure, ErrorsConfig};
+    ///
+    /// let config = ErrorsConfig::new().detail_exposure(
+    ///     "CONFIG_ERROR",
+    ///     DetailExposure::Canned("The service is misconfigured".into()),
+    /// );
+    /// ```
+    pub fn detail_exposure(mut self, code: impl Into<String>, policy: DetailExposure) -> Self {
+        self.detail_policies.insert(code.into(), policy);
+        self
+    }
+
+    /// Render timestamps as RFC 3339 with the given fractional-second precision.
+    pub fn timestamp_precision(mut self, precision: TimestampPrecision) -> Self {
+        self.timestamp_format = TimestampFormat::Rfc3339(precision);
+        self
+    }
+
+    /// Set how response timestamps are rendered (or omitted).
+    pub fn timestamp_format(mut self, format: TimestampFormat) -> Self {
+        self.timestamp_format = format;
+        self
+    }
+
+    /// Read the current time from `clock` instead of the system clock.
+    pub fn clock(mut self, clock: impl Fn() -> SystemTime + Send + Sync + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Set the status of `InvalidSignature` errors for a webhook provider
+    /// (default 401), to trigger or suppress the provider's redelivery.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let config = ErrorsConfig::new()
+    ///     .signature_failure_status("stripe", StatusCode::BAD_REQUEST);
+    /// ```
+    pub fn signature_failure_status(
+        mut self,
+        provider: impl Into<String>,
+        status: StatusCode,
+    ) -> Self {
+        self.signature_statuses.insert(provider.into(), status);
+        self
+    }
+
+    /// Set the default body format; requests can override it with the
+    /// `X-Error-Format` header (see [`RequestContext::error_format_from_headers`]).
+    ///
+    /// [`RequestContext::error_format_from_headers`]: crate::RequestContext::error_format_from_headers
+    pub fn error_format(mut self, format: ErrorFormat) -> Self {
+        self.error_format = format;
+        self
+    }
+
+    /// Serve type URIs from a different docs host, e.g.
+    /// `https://errors.internal.example`. Types become `{base}/{slug}`.
+    pub fn type_base_uri(mut self, base: impl Into<String>) -> Self {
+        self.type_base = Some(base.into());
+        self
+    }
+
+    /// Emit relative type URIs (`not-found`), resolved by clients against the
+    /// request URL as RFC 9457 allows.
+    pub fn relative_type_uris(mut self) -> Self {
+        self.type_base = Some(String::new());
+        self
+    }
+
+    /// Set the `Cache-Control` directive of error responses with `status`.
+    ///
+    /// Without a policy, 5xx responses are sent with `no-store` and other
+    /// statuses without `Cache-Control`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let config = ErrorsConfig::new().cache_control(
+    ///     StatusCode::GONE,
+    ///     HeaderValue::from_static("public, max-age=86400"),
+    /// );
+    /// ```
+    pub fn cache_control(mut self, status: StatusCode, directive: HeaderValue) -> Self {
+        self.cache_policies.insert(status, directive);
+        self
+    }
+
+    /// Let caches keep 404 responses for `ttl`, so repeated lookups of
+    /// missing resources are absorbed by the CDN without sticking for long.
+    pub fn not_found_ttl(self, ttl: Duration) -> Self {
+        let directive = HeaderValue::from_str(&format!("max-age={}", ttl.as_secs()))
+            .expect("max-age directive is a valid header value");
+        self.cache_control(StatusCode::NOT_FOUND, directive)
+    }
+
+    /// Attach CORS headers to error responses for allowed origins, read from
+    /// [`RequestContext::origin`](crate::RequestContext::origin).
+    pub fn cors(mut self, policy: CorsPolicy) -> Self {
+        self.cors = Some(policy);
+        self
+    }
+
+    /// Translate problems into the languages accepted by the request (see
+    /// [`RequestContext::languages_from_headers`]) and set `Content-Language`.
+    ///
+    /// [`RequestContext::languages_from_headers`]: crate::RequestContext::languages_from_headers
+    pub fn localizer(mut self, localizer: Localizer) -> Self {
+        self.localizer = Some(localizer);
+        self
+    }
+
+    /// Keep the canonical English `title` and `detail` when localizing and add
+    /// the translations as `title_localized` and `detail_localized`.
+    pub fn keep_canonical_text(mut self) -> Self {
+        self.keep_canonical_text = true;
+        self
+    }
+
+    /// Set the maximum sizes of `detail` and `received` values in problems.
+    pub fn body_limits(mut self, limits: BodyLimits) -> Self {
+        self.body_limits = limits;
+        self
+    }
+
+    /// The `Cache-Control` directive for an error response with `status`.
+    pub(crate) fn cache_control_for(&self, status: StatusCode) -> Option<HeaderValue> {
+        match self.cache_policies.get(&status) {
+            Some(directive) => Some(directive.clone()),
+            None if status.is_server_error() => Some(HeaderValue::from_static("no-store")),
+            None => None,
+        }
+    }
+
+    /// The `type` URI of a problem type under the configured base.
+    pub(crate) fn type_uri(&self, problem_type: &ProblemType) -> Cow<'static, str> {
+        match &self.type_base {
+            Some(base) => problem_type.uri_with_base(base),
+            None => Cow::Borrowed(problem_type.uri),
+        }
+    }
+
+    /// Set the casing of problem and field error member names.
+    ///
+    /// Applies to the top-level members (including extensions) and the
+    /// members of each field error; nested extension values are unchanged.
+    pub fn field_naming(mut self, naming: FieldNaming) -> Self {
+        self.field_naming = naming;
+        self
+    }
+
+    /// Set how nested field error paths are rendered.
+    pub fn field_paths(mut self, style: FieldPathStyle) -> Self {
+        self.field_paths = style;
+        self
+    }
+
+    /// Register the serializer for a problem profile URI, selected by clients
+    /// with `Accept: application/problem+json; profile="<uri>"`.
+    pub fn problem_profile(
+        mut self,
+        uri: impl Into<String>,
+        serializer: impl Fn(&ProblemDetails) -> serde_json::Value + Send + Sync + 'static,
+    ) -> Self {
+        self.profiles.insert(uri.into(), Arc::new(serializer));
+        self
+    }
+
+    /// The first requested profile with a registered serializer.
+    pub(crate) fn negotiate_profile<'a>(
+        &'a self,
+        accepted: &'a [String],
+    ) -> Option<(&'a str, &'a ProfileSerializer)> {
+        accepted.iter().find_map(|uri| {
+            self.profiles
+                .get(uri)
+                .map(|serializer| (uri.as_str(), serializer))
+        })
+    }
+
+    /// Get the `InvalidSignature` status for a provider, if one is configured.
+    pub(crate) fn signature_failure_status_for(&self, provider: &str) -> Option<StatusCode> {
+        self.signature_statuses.get(provider).copied()
+    }
+
+    /// Get the detail exposure policy for an error code, if one is configured.
+    pub(crate) fn detail_exposure_for(&self, code: &str) -> Option<&DetailExposure> {
+        self.detail_policies.get(code)
+    }
+}
+
+impl std::fmt::Debug for ErrorsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErrorsConfig")
+            .field("log_throttle", &self.log_throttle)
+            .field("audit_hook", &self.audit_hook.is_some())
+            .field("scrubber", &self.scrubber)
+            .field("detail_policies", &self.detail_policies)
+            .field("timestamp_format", &self.timestamp_format)
+            .field("clock", &self.clock.is_some())
+            .field("signature_statuses", &self.signature_statuses)
+            .field("error_format", &self.error_format)
+            .field("field_naming", &self.field_naming)
+            .field("field_paths", &self.field_paths)
+            .field("type_base", &self.type_base)
+            .field("profiles", &self.profiles.keys().collect::<Vec<_>>())
+            .field("cache_policies", &self.cache_policies)
+            .field("cors", &self.cors)
+            .field("localizer", &self.localizer.is_some())
+            .field("keep_canonical_text", &self.keep_canonical_text)
+            .field("body_limits", &self.body_limits)
+            .field("min_log_severity", &self.min_log_severity)
+            .field("log_at_creation", &self.log_at_creation)
+            .field("causes", &self.causes)
+            .field("backtraces", &self.backtraces)
+            .field("slo", &self.slo)
+            .field("error_stats", &self.error_stats)
+            .field("recent_errors", &self.recent_errors)
+            .field("header_size_limit", &self.header_size_limit)
+            .field("uri_length_limit", &self.uri_length_limit)
+            .finish()
+    }
+}
+
+/// Installs the process-wide errors configuration.
+pub fn set_errors_config(config: ErrorsConfig) {
+    let mut current = ERRORS_CONFIG
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *current = Arc::new(config);
+    template::invalidate();
+}
+
+/// Gets the current process-wide errors configuration.
+pub fn get_errors_config() -> Arc<ErrorsConfig> {
+    ERRORS_CONFIG
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
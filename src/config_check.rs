@@ -0,0 +1,84 @@
+//! Startup configuration checks.
+//!
+//! A [`ConfigError`](crate::AppError::ConfigError) names the key that failed
+//! and how ([`ConfigErrorKind`]), never its value, so it is safe to log even
+//! for secrets. Collect them in [`StartupErrors`](crate::StartupErrors) so a
+//! misconfigured deployment reports all of its problems in one go instead of
+//! one per restart.
+//!
+//! # Example
+//! ```ignore
+//! let mut errors = StartupErrors::new();
+//! let database_url = errors.check("config", env_var("DATABASE_URL"));
+//! let port = errors.check("config", parse_env::<u16>("PORT"));
+//! errors.exit_on_failure();
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use super::app_error::AppError;
+
+/// How a configuration key failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConfigErrorKind {
+    /// The key is not set.
+    Missing,
+
+    /// The key is set, but its value could not be parsed or was rejected.
+    Invalid,
+
+    /// The key is set, but must not be in this environment (e.g. a debug
+    /// switch in production).
+    Forbidden,
+}
+
+impl fmt::Display for ConfigErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConfigErrorKind::Missing => "missing",
+            ConfigErrorKind::Invalid => "invalid",
+            ConfigErrorKind::Forbidden => "not allowed",
+        })
+    }
+}
+
+/// Read an environment variable.
+pub fn env_var(key: &str) -> Result<String, AppError> {
+    std::env::var(key).map_err(|error| {
+        let kind = match error {
+            std::env::VarError::NotPresent => ConfigErrorKind::Missing,
+            std::env::VarError::NotUnicode(_) => ConfigErrorKind::Invalid,
+        };
+        unlogged(key, kind, None)
+    })
+}
+
+/// Read and parse an environment variable.
+///
+/// The parse error becomes the source; the value itself is not kept.
+pub fn parse_env<T>(key: &str) -> Result<T, AppError>
+where
+    T: FromStr,
+    T::Err: Error + Send + Sync + 'static,
+{
+    env_var(key)?
+        .parse()
+        .map_err(|error| unlogged(key, ConfigErrorKind::Invalid, Some(Arc::new(error))))
+}
+
+/// A configuration error that is not logged at creation; startup checks are
+/// reported together.
+fn unlogged(
+    key: &str,
+    kind: ConfigErrorKind,
+    source: Option<Arc<dyn Error + Send + Sync>>,
+) -> AppError {
+    AppError::ConfigError {
+        key: key.to_string(),
+        kind,
+        source,
+    }
+}
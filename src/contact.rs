@@ -0,0 +1,67 @@
+//! Phone number and email address validators.
+//!
+//! Both return the normalized value on success, so the stored form is
+//! consistent (E.164 for phone numbers, lowercase domains for email), and a
+//! [`FieldError`] whose params tell clients what format was expected on
+//! failure.
+//!
+//! # Example
+//! ```ignore
+//! let phone = require_phone("phone", &input.phone, "DE")?;
+//! let email = require_email("email", &input.email)?;
+//! ```
+
+use std::str::FromStr;
+
+use email_address::{EmailAddress, Options};
+use phonenumber::{Mode, country};
+
+use super::app_error::FieldError;
+
+/// Field error code for invalid phone numbers.
+pub const INVALID_PHONE: &str = "invalid_phone";
+
+/// Field error code for invalid email addresses.
+pub const INVALID_EMAIL: &str = "invalid_email";
+
+/// Require a valid phone number, returning it in E.164 form.
+///
+/// Numbers without a country code are read as numbers of `default_region`
+/// (an ISO 3166 alpha-2 code such as `US`).
+pub fn require_phone(field: &str, value: &str, default_region: &str) -> Result<String, FieldError> {
+    let region = country::Id::from_str(&default_region.to_ascii_uppercase()).ok();
+
+    match phonenumber::parse(region, value.trim()) {
+        Ok(number) if phonenumber::is_valid(&number) => {
+            Ok(number.format().mode(Mode::E164).to_string())
+        }
+        _ => Err(FieldError::with_received(
+            field,
+            INVALID_PHONE,
+            "Must be a valid phone number",
+            value,
+        )
+        .with_param("expected_format", "E.164")
+        .with_param("default_region", default_region)),
+    }
+}
+
+/// Require a valid email address with a top-level domain, returning it with
+/// surrounding whitespace removed and the domain lowercased.
+pub fn require_email(field: &str, value: &str) -> Result<String, FieldError> {
+    match EmailAddress::parse_with_options(value.trim(), Options::default().with_required_tld()) {
+        Ok(address) => Ok(format!(
+            "{}@{}",
+            address.local_part(),
+            address.domain().to_lowercase()
+        )),
+        Err(error) => Err(FieldError::with_received(
+            field,
+            INVALID_EMAIL,
+            "Must be a valid email address",
+            value,
+        )
+        .with_param("expected_format", "local@domain.tld")
+        .with_param("reason", error.to_string())),
+    }
+}
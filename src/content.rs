@@ -0,0 +1,75 @@
+//! Content-level validation errors for uploaded files and images.
+//!
+//! Upload endpoints check more than the request size: decoded image
+//! dimensions, the actual format behind the extension, archive integrity.
+//! These helpers build the field errors with the measured values and limits
+//! as params, so clients can say "4000×3000 exceeds 2048×2048".
+//!
+//! # Example
+//! ```ignore
+//! let (width, height) = image.dimensions();
+//! if width > 2048 || height > 2048 {
+//!     return Err(image_too_large("avatar", (width, height), (2048, 2048)).into());
+//! }
+//! ```
+
+use super::app_error::FieldError;
+
+/// Field error code for images exceeding the allowed dimensions.
+pub const IMAGE_TOO_LARGE: &str = "image_too_large";
+
+/// Field error code for images in a format that is not accepted.
+pub const UNSUPPORTED_IMAGE_FORMAT: &str = "unsupported_image_format";
+
+/// Field error code for archives that cannot be read.
+pub const CORRUPT_ARCHIVE: &str = "corrupt_archive";
+
+/// Field error code for files exceeding the allowed size.
+pub const FILE_TOO_LARGE: &str = "file_too_large";
+
+/// An image whose `(width, height)` exceeds `(max_width, max_height)`.
+pub fn image_too_large(field: &str, dimensions: (u32, u32), limits: (u32, u32)) -> FieldError {
+    let (width, height) = dimensions;
+    let (max_width, max_height) = limits;
+
+    FieldError::new(
+        field,
+        IMAGE_TOO_LARGE,
+        format!("Image is {width}×{height}; the maximum is {max_width}×{max_height}"),
+    )
+    .with_param("width", width)
+    .with_param("height", height)
+    .with_param("max_width", max_width)
+    .with_param("max_height", max_height)
+}
+
+/// An image in `format` (e.g. `tiff`), which is not one of `supported`.
+pub fn unsupported_image_format(field: &str, format: &str, supported: &[&str]) -> FieldError {
+    FieldError::new(
+        field,
+        UNSUPPORTED_IMAGE_FORMAT,
+        format!(
+            "Image format {format} is not supported; use one of: {}",
+            supported.join(", ")
+        ),
+    )
+    .with_param("format", format)
+    .with_param("supported", supported)
+}
+
+/// An archive that could not be read, with the reason from the decoder.
+pub fn corrupt_archive(field: &str, reason: impl Into<String>) -> FieldError {
+    FieldError::new(field, CORRUPT_ARCHIVE, "Archive is corrupt or truncated")
+        .with_param("reason", reason.into())
+}
+
+/// A file of `size` bytes exceeding `max_size` bytes.
+pub fn file_too_large(field: &str, size: u64, max_size: u64) -> FieldError {
+    FieldError::new(
+        field,
+        FILE_TOO_LARGE,
+        format!("File is {size} bytes; the maximum is {max_size} bytes"),
+    )
+    .with_param("size", size)
+    .with_param("max_size", max_size)
+}
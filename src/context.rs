@@ -0,0 +1,306 @@
+//! Request context shared between the middleware and error rendering.
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use axum::http::{HeaderMap, HeaderName, HeaderValue, header};
+use uuid::Uuid;
+
+use super::app_error::{AppError, CURRENT_REQUEST_ID};
+use super::config::ErrorFormat;
+use super::locale::accepted_languages;
+use super::profile::accepted_profiles;
+
+/// Header carrying the request ID, echoed on success responses.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Header carrying the ID shared by every request of one end-to-end flow.
+pub const CORRELATION_ID_HEADER: &str = "x-correlation-id";
+
+/// Absolute deadline header: Unix time in milliseconds.
+pub const REQUEST_DEADLINE_HEADER: &str = "x-request-deadline";
+
+/// gRPC-style relative timeout header (e.g. `250m`, `5S`).
+pub const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+
+/// Per-request error body format override (`problem` or `legacy`).
+pub const ERROR_FORMAT_HEADER: &str = "x-error-format";
+
+/// Route recorded for requests without a route in their context.
+pub(crate) const UNKNOWN_ROUTE: &str = "unknown";
+
+tokio::task_local! {
+    /// Task-local storage for the current request context.
+    /// Set by the request_context middleware in eywa-axum.
+    pub static CURRENT_REQUEST_CONTEXT: RequestContext;
+}
+
+/// Information about the request being served, beyond its request ID.
+///
+/// Every field is optional so middleware can populate whatever it knows.
+#[derive(Debug, Clone, Default)]
+pub struct RequestContext {
+    /// Authenticated principal (user id, service account, API key id).
+    pub actor: Option<String>,
+
+    /// Tenant the request is acting on behalf of.
+    pub tenant: Option<String>,
+
+    /// HTTP method of the request.
+    pub method: Option<String>,
+
+    /// Request path, used as the resource in audit records.
+    pub path: Option<String>,
+
+    /// Matched route template (e.g. `/users/{id}`), used to aggregate error
+    /// statistics without the cardinality of raw paths.
+    pub route: Option<String>,
+
+    /// When the caller stops waiting for the response.
+    pub deadline: Option<Instant>,
+
+    /// Error body format requested by the client, overriding the configured one.
+    pub error_format: Option<ErrorFormat>,
+
+    /// Problem profiles from the `Accept` header, in preference order.
+    pub accepted_profiles: Vec<String>,
+
+    /// `Origin` of a cross-origin browser request.
+    pub origin: Option<String>,
+
+    /// Languages from the `Accept-Language` header, in preference order.
+    pub accepted_languages: Vec<String>,
+
+    /// ID of the end-to-end flow the request belongs to, from the caller.
+    pub correlation_id: Option<String>,
+}
+
+impl RequestContext {
+    /// Create an empty request context.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the deadline from the incoming request headers.
+    ///
+    /// `X-Request-Deadline` (absolute, Unix milliseconds) takes precedence
+    /// over `grpc-timeout` (relative). Unparseable headers are ignored.
+    pub fn deadline_from_headers(mut self, headers: &HeaderMap) -> Self {
+        let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+
+        self.deadline = header(REQUEST_DEADLINE_HEADER)
+            .and_then(parse_unix_millis_deadline)
+            .or_else(|| header(GRPC_TIMEOUT_HEADER).and_then(parse_grpc_timeout))
+            .map(|budget| Instant::now() + budget);
+        self
+    }
+
+    /// Set the requested error format from the `X-Error-Format` header.
+    pub fn error_format_from_headers(mut self, headers: &HeaderMap) -> Self {
+        self.error_format = headers
+            .get(ERROR_FORMAT_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(ErrorFormat::from_header_value);
+        self
+    }
+
+    /// Set the accepted problem profiles from the `Accept` header.
+    pub fn profiles_from_headers(mut self, headers: &HeaderMap) -> Self {
+        self.accepted_profiles = headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(accepted_profiles)
+            .collect();
+        self
+    }
+
+    /// Set the origin from the `Origin` header.
+    pub fn origin_from_headers(mut self, headers: &HeaderMap) -> Self {
+        self.origin = headers
+            .get(header::ORIGIN)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        self
+    }
+
+    /// Set the accepted languages from the `Accept-Language` header.
+    pub fn languages_from_headers(mut self, headers: &HeaderMap) -> Self {
+        self.accepted_languages = headers
+            .get_all(header::ACCEPT_LANGUAGE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(accepted_languages)
+            .collect();
+        self
+    }
+
+    /// Set the correlation ID from the `X-Correlation-Id` header.
+    pub fn correlation_id_from_headers(mut self, headers: &HeaderMap) -> Self {
+        self.correlation_id = headers
+            .get(CORRELATION_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| !id.is_empty())
+            .map(str::to_string);
+        self
+    }
+
+    /// Time left until the deadline, zero once it has passed.
+    pub fn remaining_budget(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+}
+
+/// Budget until an absolute Unix-millisecond deadline.
+fn parse_unix_millis_deadline(value: &str) -> Option<Duration> {
+    let deadline = UNIX_EPOCH + Duration::from_millis(value.trim().parse().ok()?);
+    Some(
+        deadline
+            .duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Parse a gRPC timeout: up to 8 digits followed by a unit (H, M, S, m, u, n).
+fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (amount, unit) = value.split_at_checked(value.len().checked_sub(1)?)?;
+    if amount.is_empty() || amount.len() > 8 {
+        return None;
+    }
+    let amount: u64 = amount.parse().ok()?;
+
+    match unit {
+        "H" => Some(Duration::from_secs(amount * 3600)),
+        "M" => Some(Duration::from_secs(amount * 60)),
+        "S" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_millis(amount)),
+        "u" => Some(Duration::from_micros(amount)),
+        "n" => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+/// Sets the current request context for this task scope.
+/// Called by eywa-axum's request_context middleware.
+pub fn set_request_context<F, R>(context: RequestContext, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    CURRENT_REQUEST_CONTEXT.sync_scope(context, f)
+}
+
+/// Gets a copy of the current request context, if one is set.
+pub fn get_request_context() -> Option<RequestContext> {
+    CURRENT_REQUEST_CONTEXT
+        .try_with(|context| context.clone())
+        .ok()
+}
+
+/// The request ID sent by the caller in `X-Request-Id`, to serve the request
+/// under (see [`set_request_id`]). `None` if absent or not a UUID.
+///
+/// [`set_request_id`]: crate::set_request_id
+pub fn request_id_from_headers(headers: &HeaderMap) -> Option<Uuid> {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|id| Uuid::parse_str(id.trim()).ok())
+}
+
+/// Add the current request and correlation IDs to the headers of an
+/// outbound request, so the callee's logs and errors line up with ours.
+///
+/// The correlation ID is the one received from the caller, or the current
+/// request ID if this request started the flow. Does nothing outside a
+/// request scope.
+///
+/// # Example
+/// ```ignore
+/// let mut headers = HeaderMap::new();
+/// propagate_request_headers(&mut headers);
+///
+/// // reqwest
+/// let response = client.get(url).headers(headers).send().await?;
+///
+/// // tonic
+/// let metadata = MetadataMap::from_headers(headers);
+/// let request = tonic::Request::from_parts(metadata, Default::default(), message);
+/// ```
+pub fn propagate_request_headers(headers: &mut HeaderMap) {
+    let Ok(request_id) = CURRENT_REQUEST_ID.try_with(|id| id.to_string()) else {
+        return;
+    };
+    let correlation_id = CURRENT_REQUEST_CONTEXT
+        .try_with(|context| context.correlation_id.clone())
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| request_id.clone());
+
+    for (name, value) in [
+        (REQUEST_ID_HEADER, request_id),
+        (CORRELATION_ID_HEADER, correlation_id),
+    ] {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(HeaderName::from_static(name), value);
+        }
+    }
+}
+
+/// Time left until the current request's deadline, if it has one.
+///
+/// Downstream calls should use this to shrink their own timeouts.
+pub fn remaining_budget() -> Option<Duration> {
+    CURRENT_REQUEST_CONTEXT
+        .try_with(|context| context.remaining_budget())
+        .ok()
+        .flatten()
+}
+
+/// Fail with `DeadlineExceeded` if the current request's deadline has passed,
+/// otherwise return the remaining budget (`None` without a deadline).
+pub fn check_deadline() -> Result<Option<Duration>, AppError> {
+    match remaining_budget() {
+        Some(Duration::ZERO) => Err(AppError::DeadlineExceeded),
+        budget => Ok(budget),
+    }
+}
+
+/// Error format requested for the current request, if any.
+pub(crate) fn requested_error_format() -> Option<ErrorFormat> {
+    CURRENT_REQUEST_CONTEXT
+        .try_with(|context| context.error_format)
+        .ok()
+        .flatten()
+}
+
+/// Problem profiles accepted by the current request.
+pub(crate) fn requested_profiles() -> Vec<String> {
+    CURRENT_REQUEST_CONTEXT
+        .try_with(|context| context.accepted_profiles.clone())
+        .unwrap_or_default()
+}
+
+/// `Origin` of the current request, if any.
+pub(crate) fn request_origin() -> Option<String> {
+    CURRENT_REQUEST_CONTEXT
+        .try_with(|context| context.origin.clone())
+        .ok()
+        .flatten()
+}
+
+/// Languages accepted by the current request.
+pub(crate) fn requested_languages() -> Vec<String> {
+    CURRENT_REQUEST_CONTEXT
+        .try_with(|context| context.accepted_languages.clone())
+        .unwrap_or_default()
+}
+
+/// Route template of the current request, or [`UNKNOWN_ROUTE`].
+pub(crate) fn request_route() -> String {
+    CURRENT_REQUEST_CONTEXT
+        .try_with(|context| context.route.clone())
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| UNKNOWN_ROUTE.to_string())
+}
@@ -0,0 +1,94 @@
+//! Runtime registry of conversions for error types this crate doesn't know.
+//!
+//! Application bootstrap registers a converter per third-party error type;
+//! [`AppError::from_dyn`] (and [`AppError::from_box_error`] for errors it
+//! does not recognize itself) then consults them, so the mappings live in one
+//! place instead of in every handler.
+//!
+//! # Example
+//! ```ignore
+//! register_converter(|error: &redis::RedisError| {
+//!     if error.is_timeout() {
+//!         gateway_timeout("Cache timed out")
+//!     } else {
+//!         external_service_error("redis")
+//!     }
+//! });
+//!
+//! let error = AppError::from_dyn(&redis_error);
+//! ```
+
+use std::any::TypeId;
+use std::error::Error;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use super::app_error::AppError;
+
+/// Detail of unrecognized errors; their text may contain internals.
+pub(crate) const SANITIZED_DETAIL: &str = "An internal error occurred";
+
+type Converter = Arc<dyn Fn(&(dyn Error + 'static)) -> Option<AppError> + Send + Sync>;
+
+/// Converters in registration order, keyed by the error type they handle.
+static CONVERTERS: LazyLock<Mutex<Vec<(TypeId, Converter)>>> = LazyLock::new(Default::default);
+
+/// Register the conversion of `E` to `AppError`, replacing any earlier one
+/// for the same type.
+pub fn register_converter<E, F>(convert: F)
+where
+    E: Error + 'static,
+    F: Fn(&E) -> AppError + Send + Sync + 'static,
+{
+    let converter: Converter =
+        Arc::new(move |error: &(dyn Error + 'static)| error.downcast_ref::<E>().map(&convert));
+    let mut converters = CONVERTERS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let type_id = TypeId::of::<E>();
+    match converters.iter_mut().find(|(id, _)| *id == type_id) {
+        Some((_, existing)) => *existing = converter,
+        None => converters.push((type_id, converter)),
+    }
+}
+
+/// Convert `error` with the first registered converter that handles it or
+/// one of its sources.
+fn convert(error: &(dyn Error + 'static)) -> Option<AppError> {
+    // Cloned so converters run without the lock and may register others.
+    let converters: Vec<Converter> = CONVERTERS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .iter()
+        .map(|(_, converter)| converter.clone())
+        .collect();
+
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(app_error) = error.downcast_ref::<AppError>() {
+            return Some(app_error.clone());
+        }
+        if let Some(app_error) = converters.iter().find_map(|converter| converter(error)) {
+            return Some(app_error);
+        }
+        current = error.source();
+    }
+    None
+}
+
+impl AppError {
+    /// Map any error to an `AppError` using the registered converters.
+    ///
+    /// The error and then its sources are tried in turn; an `AppError` in
+    /// the chain is returned as is. Anything unhandled becomes a 500 with a
+    /// generic detail, and the original error is logged.
+    pub fn from_dyn(error: &(dyn Error + 'static)) -> AppError {
+        convert(error).unwrap_or_else(|| {
+            tracing::error!(
+                exception.message = %error,
+                "Unrecognized error at the response boundary"
+            );
+            AppError::InternalServerError(SANITIZED_DETAIL.to_string())
+        })
+    }
+}
@@ -0,0 +1,129 @@
+//! CORS headers on error responses.
+//!
+//! Errors raised before the CORS layer runs (auth, body limits, routing)
+//! leave the response without `Access-Control-Allow-Origin`, and browsers
+//! hide the whole response from the page. A [`CorsPolicy`] in the
+//! [`ErrorsConfig`](crate::ErrorsConfig) lets the renderer attach the headers
+//! itself, so SPA clients can read the problem body.
+//!
+//! # Example
+//! ```ignore
+//! set_errors_config(ErrorsConfig::new().cors(
+//!     CorsPolicy::new()
+//!         .allow_origin("https://app.eywa.dev")
+//!         .allow_credentials(),
+//! ));
+//! ```
+
+use axum::http::{HeaderMap, HeaderValue, header};
+
+/// Origins allowed to read error responses.
+#[derive(Debug, Clone, Default)]
+pub struct CorsPolicy {
+    origins: Vec<String>,
+    any_origin: bool,
+    credentials: bool,
+}
+
+/// Response headers browsers hide from scripts unless exposed.
+const EXPOSED_HEADERS: &str = "retry-after, x-request-id";
+
+impl CorsPolicy {
+    /// A policy allowing no origins.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow an origin, e.g. `https://app.eywa.dev`. `*` allows any origin,
+    /// without credentials: origins outside the explicit list get a literal
+    /// `*` and never `Access-Control-Allow-Credentials`.
+    pub fn allow_origin(mut self, origin: impl Into<String>) -> Self {
+        let origin = origin.into();
+        if origin == "*" {
+            self.any_origin = true;
+        } else {
+            self.origins.push(origin);
+        }
+        self
+    }
+
+    /// Send `Access-Control-Allow-Credentials: true` to the explicitly
+    /// allowed origins, for clients using cookies or
+    /// `fetch(..., { credentials: "include" })`.
+    pub fn allow_credentials(mut self) -> Self {
+        self.credentials = true;
+        self
+    }
+
+    /// The CORS headers for a request from `origin`.
+    ///
+    /// Always includes `Vary: Origin`, so a cached response for one origin
+    /// (or for none) is not served to another.
+    pub(crate) fn headers_for(&self, origin: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::VARY, HeaderValue::from_static("origin"));
+        let Some(origin) = origin else {
+            return headers;
+        };
+
+        let listed = self.origins.iter().any(|allowed| allowed == origin);
+        let allow_origin = if listed {
+            match HeaderValue::from_str(origin) {
+                Ok(value) => value,
+                Err(_) => return headers,
+            }
+        } else if self.any_origin {
+            HeaderValue::from_static("*")
+        } else {
+            return headers;
+        };
+
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        headers.insert(
+            header::ACCESS_CONTROL_EXPOSE_HEADERS,
+            HeaderValue::from_static(EXPOSED_HEADERS),
+        );
+        if self.credentials && listed {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+        headers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_origin_never_gets_credentials() {
+        let policy = CorsPolicy::new()
+            .allow_origin("*")
+            .allow_origin("https://app.eywa.dev")
+            .allow_credentials();
+
+        let headers = policy.headers_for(Some("https://evil.example"));
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert!(!headers.contains_key(header::ACCESS_CONTROL_ALLOW_CREDENTIALS));
+
+        let headers = policy.headers_for(Some("https://app.eywa.dev"));
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://app.eywa.dev"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+    }
+
+    #[test]
+    fn disallowed_origin_still_varies() {
+        let policy = CorsPolicy::new().allow_origin("https://app.eywa.dev");
+
+        for origin in [Some("https://evil.example"), None] {
+            let headers = policy.headers_for(origin);
+            assert_eq!(headers[header::VARY], "origin");
+            assert!(!headers.contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Static documentation for the error catalog.
+//!
+//! [`generate_docs`] renders one page per problem type (built-in and
+//! registered) plus an index, ready to publish at the type URIs so they
+//! resolve to real documentation.
+//!
+//! # Example
+//! ```ignore
+//! use eywa_errors::{DocFormat, write_docs};
+//!
+//! // In a small `xtask` or build step:
+//! write_docs("target/error-docs", DocFormat::Html)?;
+//! ```
+
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use super::app_error::{FieldErrors, ProblemDetails};
+use super::config::get_errors_config;
+use super::problem_type::ProblemType;
+use super::timestamp::Timestamp;
+
+/// Output format of the generated pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocFormat {
+    /// CommonMark pages (`.md`).
+    Markdown,
+    /// Standalone HTML pages (`.html`).
+    Html,
+}
+
+impl DocFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            DocFormat::Markdown => "md",
+            DocFormat::Html => "html",
+        }
+    }
+}
+
+/// A generated documentation page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocPage {
+    /// Relative file path, e.g. `not-found.md`.
+    pub path: String,
+
+    /// Page contents.
+    pub contents: String,
+}
+
+/// Render a page per problem type plus an `index` page.
+pub fn generate_docs(format: DocFormat) -> Vec<DocPage> {
+    let types = ProblemType::all();
+    let mut pages: Vec<DocPage> = types
+        .iter()
+        .map(|problem_type| DocPage {
+            path: format!("{}.{}", page_name(problem_type), format.extension()),
+            contents: match format {
+                DocFormat::Markdown => markdown_page(problem_type),
+                DocFormat::Html => html_page(problem_type),
+            },
+        })
+        .collect();
+
+    pages.push(DocPage {
+        path: format!("index.{}", format.extension()),
+        contents: index_page(&types, format),
+    });
+    pages
+}
+
+/// Generate the docs and write them into `dir`, creating it (and the
+/// subdirectories of nested slugs such as `billing/invoice-locked`) if needed.
+pub fn write_docs(dir: impl AsRef<Path>, format: DocFormat) -> std::io::Result<()> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+
+    for page in generate_docs(format) {
+        let path = dir.join(&page.path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, page.contents)?;
+    }
+    Ok(())
+}
+
+/// File name of a type's page: its slug, or its code in kebab case.
+///
+/// Empty, `.` and `..` segments of the slug are dropped, so the page stays
+/// below the docs directory and the index can link to it relatively.
+fn page_name(problem_type: &ProblemType) -> String {
+    let slug = problem_type.slug().map(|slug| {
+        slug.split('/')
+            .filter(|segment| !matches!(*segment, "" | "." | ".."))
+            .collect::<Vec<_>>()
+            .join("/")
+    });
+    match slug {
+        Some(slug) if !slug.is_empty() => slug,
+        _ => problem_type.code.to_ascii_lowercase().replace('_', "-"),
+    }
+}
+
+/// Pretty-printed example response body for a type.
+fn example_payload(problem_type: &ProblemType) -> String {
+    let problem = ProblemDetails {
+        error_type: get_errors_config().type_uri(problem_type),
+        title: Cow::Borrowed(problem_type.title),
+        status: problem_type.default_status.as_u16(),
+        code: Cow::Borrowed(problem_type.code),
+        detail: problem_type.title.to_string(),
+        instance: None,
+        request_id: "00000000-0000-4000-8000-000000000000".to_string(),
+        timestamp: Some(Timestamp::Rfc3339("2024-01-01T00:00:00Z".to_string())),
+        errors: FieldErrors::new(),
+        extensions: serde_json::Map::new(),
+    };
+    serde_json::to_string_pretty(&problem).unwrap_or_default()
+}
+
+/// The status with its reason phrase, falling back to the problem title for
+/// statuses outside the registry (e.g. 499).
+fn status_text(problem_type: &ProblemType) -> String {
+    let status = problem_type.default_status;
+    let reason = status.canonical_reason().unwrap_or(problem_type.title);
+    format!("{} {reason}", status.as_u16())
+}
+
+fn markdown_page(problem_type: &ProblemType) -> String {
+    let mut page = String::new();
+    let _ = writeln!(page, "# {}\n", problem_type.title);
+    let _ = writeln!(page, "- **Code:** `{}`", problem_type.code);
+    let _ = writeln!(page, "- **Status:** {}", status_text(problem_type));
+    let _ = writeln!(
+        page,
+        "- **Type:** `{}`\n",
+        get_errors_config().type_uri(problem_type)
+    );
+    if !problem_type.remediation.is_empty() {
+        let _ = writeln!(page, "## Remediation\n\n{}\n", problem_type.remediation);
+    }
+    let _ = writeln!(
+        page,
+        "## Example\n\n```json\n{}\n```",
+        example_payload(problem_type)
+    );
+    page
+}
+
+fn html_page(problem_type: &ProblemType) -> String {
+    let title = escape_html(problem_type.title);
+    let mut body = String::new();
+    let _ = writeln!(body, "<h1>{title}</h1>");
+    let _ = writeln!(body, "<dl>");
+    let _ = writeln!(
+        body,
+        "<dt>Code</dt><dd><code>{}</code></dd>",
+        escape_html(problem_type.code)
+    );
+    let _ = writeln!(
+        body,
+        "<dt>Status</dt><dd>{}</dd>",
+        escape_html(&status_text(problem_type))
+    );
+    let _ = writeln!(
+        body,
+        "<dt>Type</dt><dd><code>{}</code></dd>",
+        escape_html(&get_errors_config().type_uri(problem_type))
+    );
+    let _ = writeln!(body, "</dl>");
+    if !problem_type.remediation.is_empty() {
+        let _ = writeln!(
+            body,
+            "<h2>Remediation</h2>\n<p>{}</p>",
+            escape_html(problem_type.remediation)
+        );
+    }
+    let _ = writeln!(
+        body,
+        "<h2>Example</h2>\n<pre><code>{}</code></pre>",
+        escape_html(&example_payload(problem_type))
+    );
+    html_document(&title, &body)
+}
+
+fn index_page(types: &[ProblemType], format: DocFormat) -> String {
+    let mut page = String::new();
+
+    match format {
+        DocFormat::Markdown => {
+            let _ = writeln!(page, "# Error catalog\n");
+            let _ = writeln!(page, "| Code | Status | Title |\n| --- | --- | --- |");
+            for problem_type in types {
+                let _ = writeln!(
+                    page,
+                    "| [`{}`]({}.md) | {} | {} |",
+                    problem_type.code,
+                    page_name(problem_type),
+                    problem_type.default_status.as_u16(),
+                    problem_type.title
+                );
+            }
+            page
+        }
+        DocFormat::Html => {
+            let _ = writeln!(page, "<h1>Error catalog</h1>\n<table>");
+            let _ = writeln!(page, "<tr><th>Code</th><th>Status</th><th>Title</th></tr>");
+            for problem_type in types {
+                let _ = writeln!(
+                    page,
+                    "<tr><td><a href=\"{}.html\"><code>{}</code></a></td><td>{}</td><td>{}</td></tr>",
+                    escape_html(&page_name(problem_type)),
+                    escape_html(problem_type.code),
+                    problem_type.default_status.as_u16(),
+                    escape_html(problem_type.title)
+                );
+            }
+            let _ = writeln!(page, "</table>");
+            html_document("Error catalog", &page)
+        }
+    }
+}
+
+fn html_document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n</head>\n<body>\n{body}</body>\n</html>\n"
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+
+    use super::*;
+
+    #[test]
+    fn nested_slugs_get_subdirectories_and_relative_links() {
+        ProblemType::new(
+            "https://errors.eywa.dev/billing/invoice-locked",
+            "billing.invoice_locked",
+            "Invoice Locked",
+            StatusCode::CONFLICT,
+        )
+        .register();
+
+        let dir = std::env::temp_dir().join(format!("eywa-docs-{}", std::process::id()));
+        write_docs(&dir, DocFormat::Markdown).unwrap();
+
+        let page = std::fs::read_to_string(dir.join("billing/invoice-locked.md")).unwrap();
+        assert!(page.starts_with("# Invoice Locked"));
+        let index = std::fs::read_to_string(dir.join("index.md")).unwrap();
+        assert!(index.contains("](billing/invoice-locked.md)"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dot_segments_stay_inside_the_docs_directory() {
+        let problem_type = ProblemType::new(
+            "https://errors.eywa.dev//../../etc/passwd",
+            "ESCAPE",
+            "Escape",
+            StatusCode::BAD_REQUEST,
+        );
+        assert_eq!(page_name(&problem_type), "etc/passwd");
+
+        let problem_type = ProblemType::new(
+            "https://errors.eywa.dev/..",
+            "ONLY_DOTS",
+            "Only Dots",
+            StatusCode::BAD_REQUEST,
+        );
+        assert_eq!(page_name(&problem_type), "only-dots");
+    }
+}
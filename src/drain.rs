@@ -0,0 +1,75 @@
+//! Request draining during graceful shutdown.
+//!
+//! Once the shutdown signal fires, [`drain_guard`] answers new requests with
+//! [`draining`](crate::draining) (503, `Connection: close`, `Retry-After`)
+//! while in-flight requests finish, so rolling deploys behave the same in
+//! every service.
+//!
+//! # Example
+//! ```ignore
+//! use eywa_errors::{drain_guard, DrainSignal};
+//!
+//! let drain = DrainSignal::new();
+//! let app = Router::new()
+//!     .route("/", get(handler))
+//!     .layer(axum::middleware::from_fn_with_state(drain.clone(), drain_guard));
+//!
+//! axum::serve(listener, app)
+//!     .with_graceful_shutdown(drain.drain_on(shutdown_signal()))
+//!     .await?;
+//! ```
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use super::http_errors::draining;
+
+/// Shared flag flipped when the service starts shutting down.
+#[derive(Debug, Clone, Default)]
+pub struct DrainSignal {
+    draining: Arc<AtomicBool>,
+}
+
+impl DrainSignal {
+    /// Create a signal that is not draining.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start draining: new requests are rejected from now on.
+    pub fn drain(&self) {
+        self.draining.store(true, Ordering::Release);
+    }
+
+    /// Whether draining has started.
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Acquire)
+    }
+
+    /// Wait for `shutdown`, then start draining.
+    ///
+    /// Suitable for axum's `with_graceful_shutdown`.
+    pub async fn drain_on(self, shutdown: impl Future<Output = ()>) {
+        shutdown.await;
+        self.drain();
+    }
+}
+
+/// Middleware rejecting new requests once `signal` is draining.
+///
+/// Install with `axum::middleware::from_fn_with_state`.
+pub async fn drain_guard(
+    State(signal): State<DrainSignal>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if signal.is_draining() {
+        return draining().into_response();
+    }
+    next.run(request).await
+}
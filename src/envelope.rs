@@ -0,0 +1,161 @@
+//! Transport form of `AppError` for non-HTTP channels (message buses, queues).
+//!
+//! An [`ErrorEnvelope`] carries everything a problem response would, plus the
+//! originating request ID. Receiving services turn it back into an
+//! `AppError::Remote`, which renders exactly as the sender reported it.
+
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+
+use super::app_error::{AppError, FieldErrors, ProblemDetails};
+
+/// Lossless wire form of an error.
+///
+/// Envelopes from senders on other versions decode as long as they carry a
+/// `code` and `status`: unknown members are ignored and missing ones default.
+///
+/// # Example
+/// ```ignore
+/// // Sender
+/// let payload = error.to_envelope().to_json()?;
+/// client.publish("orders.failed", payload.into()).await?;
+///
+/// // Receiver
+/// let error = AppError::from(ErrorEnvelope::from_json(&message.payload)?);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorEnvelope {
+    /// Machine-readable error code (e.g. `NOT_FOUND`).
+    pub code: String,
+
+    /// HTTP status code the error maps to.
+    pub status: u16,
+
+    /// Problem type URI.
+    #[serde(rename = "type", default = "about_blank")]
+    pub error_type: String,
+
+    /// Short, human-readable summary of the problem type.
+    #[serde(default)]
+    pub title: String,
+
+    /// Human-readable explanation specific to this occurrence.
+    #[serde(default)]
+    pub detail: String,
+
+    /// Field-level validation errors (if applicable).
+    #[serde(default, skip_serializing_if = "SmallVec::is_empty")]
+    pub fields: FieldErrors,
+
+    /// Problem extension members.
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extensions: serde_json::Map<String, serde_json::Value>,
+
+    /// Request ID of the operation that produced the error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ErrorEnvelope {
+    /// Encode as JSON.
+    pub fn to_json(&self) -> serde_json::Result<Vec<u8>> {
+        serde_json::to_vec(self)
+    }
+
+    /// Decode from JSON.
+    pub fn from_json(bytes: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(bytes)
+    }
+
+    /// Encode as MessagePack (with field names, for forward compatibility).
+    #[cfg(feature = "msgpack")]
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+        rmp_serde::to_vec_named(self)
+    }
+
+    /// Decode from MessagePack.
+    #[cfg(feature = "msgpack")]
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Self, rmp_serde::decode::Error> {
+        rmp_serde::from_slice(bytes)
+    }
+
+    /// Encode as CBOR.
+    #[cfg(feature = "cbor")]
+    pub fn to_cbor(&self) -> Result<Vec<u8>, ciborium::ser::Error<std::io::Error>> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(self, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Decode from CBOR.
+    #[cfg(feature = "cbor")]
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, ciborium::de::Error<std::io::Error>> {
+        ciborium::from_reader(bytes)
+    }
+}
+
+impl From<ProblemDetails> for ErrorEnvelope {
+    fn from(problem: ProblemDetails) -> Self {
+        Self {
+            code: problem.code.into_owned(),
+            status: problem.status,
+            error_type: problem.error_type.into_owned(),
+            title: problem.title.into_owned(),
+            detail: problem.detail,
+            fields: problem.errors,
+            extensions: problem.extensions,
+            request_id: Some(problem.request_id),
+        }
+    }
+}
+
+impl From<ErrorEnvelope> for AppError {
+    fn from(envelope: ErrorEnvelope) -> Self {
+        AppError::Remote(Box::new(envelope))
+    }
+}
+
+impl AppError {
+    /// Convert to the transport form, stamped with the current request ID.
+    ///
+    /// Errors that were themselves received from another service keep their
+    /// original envelope, including its request ID.
+    pub fn to_envelope(&self) -> ErrorEnvelope {
+        match self.base() {
+            AppError::Remote(envelope) => envelope.as_ref().clone(),
+            _ => ErrorEnvelope::from(self.to_problem_details()),
+        }
+    }
+
+    /// Consuming variant of [`AppError::to_envelope`].
+    pub fn into_envelope(self) -> ErrorEnvelope {
+        match self {
+            AppError::Remote(envelope) => *envelope,
+            AppError::Annotated(annotated) => annotated.error.into_envelope(),
+            error => ErrorEnvelope::from(error.into_problem_details()),
+        }
+    }
+}
+
+/// Default problem type of RFC 7807, for envelopes that omit `type`.
+fn about_blank() -> String {
+    "about:blank".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_envelopes_from_other_versions() {
+        let envelope = ErrorEnvelope::from_json(
+            br#"{"code": "NOT_FOUND", "status": 404, "retry_policy": "never"}"#,
+        )
+        .unwrap();
+        assert_eq!(envelope.error_type, "about:blank");
+        assert_eq!(envelope.title, "");
+        assert_eq!(envelope.detail, "");
+        assert!(envelope.fields.is_empty());
+        assert_eq!(envelope.request_id, None);
+    }
+}
@@ -0,0 +1,186 @@
+//! Readiness and liveness reporting.
+//!
+//! Health checks report failing components as [`HealthError`]s. A
+//! [`HealthReport`] aggregates them and renders `/readyz`: 200 while every
+//! component is at least degraded-but-serving, 503 with a problem+json body
+//! listing the components once any of them is unhealthy.
+//!
+//! # Example
+//! ```ignore
+//! use eywa_errors::{HealthError, HealthReport};
+//!
+//! async fn readyz(State(state): State<AppState>) -> Response {
+//!     HealthReport::from_checks([
+//!         state.db.ping().await.map_err(|e| HealthError::unhealthy("database", e.to_string())),
+//!         state.cache.ping().await.map_err(|e| HealthError::degraded("cache", e.to_string())),
+//!     ])
+//!     .into_response()
+//! }
+//! ```
+
+use std::borrow::Cow;
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::app_error::{FieldErrors, ProblemDetails, get_request_id};
+use super::config::get_errors_config;
+use super::problem_type::ProblemType;
+use super::render::Rendered;
+use super::timestamp;
+
+/// Severity of a failing component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// Working with reduced capacity or features; the service keeps serving.
+    Degraded,
+
+    /// Not working; the service should be taken out of rotation.
+    Unhealthy,
+}
+
+/// A failing component reported by a health check.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct HealthError {
+    /// Name of the component (e.g. "database").
+    pub component: String,
+
+    /// How badly the component is failing.
+    pub status: HealthStatus,
+
+    /// Human-readable explanation.
+    pub detail: String,
+}
+
+impl HealthError {
+    /// Create a degraded component report.
+    pub fn degraded(component: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            component: component.into(),
+            status: HealthStatus::Degraded,
+            detail: detail.into(),
+        }
+    }
+
+    /// Create an unhealthy component report.
+    pub fn unhealthy(component: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            component: component.into(),
+            status: HealthStatus::Unhealthy,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Aggregated result of the health checks for one probe.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HealthReport {
+    errors: Vec<HealthError>,
+}
+
+impl HealthReport {
+    /// Create an empty (healthy) report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect the failures out of a set of check results.
+    pub fn from_checks(checks: impl IntoIterator<Item = Result<(), HealthError>>) -> Self {
+        Self {
+            errors: checks.into_iter().filter_map(Result::err).collect(),
+        }
+    }
+
+    /// Add a failing component.
+    pub fn push(&mut self, error: HealthError) {
+        self.errors.push(error);
+    }
+
+    /// The failing components, in report order.
+    pub fn errors(&self) -> &[HealthError] {
+        &self.errors
+    }
+
+    /// The worst component status, or `None` if every component is healthy.
+    pub fn status(&self) -> Option<HealthStatus> {
+        self.errors.iter().map(|e| e.status).max()
+    }
+
+    /// Whether the service should receive traffic (nothing is unhealthy).
+    pub fn is_ready(&self) -> bool {
+        self.status() != Some(HealthStatus::Unhealthy)
+    }
+
+    /// Convert to a problem, with the failing components under `components`.
+    ///
+    /// The status is 503 if any component is unhealthy, otherwise 200.
+    pub fn to_problem_details(&self) -> ProblemDetails {
+        let unhealthy = self
+            .errors
+            .iter()
+            .filter(|e| e.status == HealthStatus::Unhealthy)
+            .count();
+        let (problem_type, detail) = if self.is_ready() {
+            (
+                ProblemType::DEGRADED,
+                format!("{} components degraded", self.errors.len()),
+            )
+        } else {
+            (
+                ProblemType::UNHEALTHY,
+                format!("{unhealthy} of {} components unhealthy", self.errors.len()),
+            )
+        };
+
+        let mut extensions = serde_json::Map::new();
+        extensions.insert(
+            "components".into(),
+            serde_json::to_value(&self.errors).unwrap_or_default(),
+        );
+
+        ProblemDetails {
+            error_type: get_errors_config().type_uri(&problem_type),
+            title: Cow::Borrowed(problem_type.title),
+            status: problem_type.default_status.as_u16(),
+            code: Cow::Borrowed(problem_type.code),
+            detail,
+            instance: None,
+            request_id: get_request_id().to_string(),
+            timestamp: timestamp::now(),
+            errors: FieldErrors::new(),
+            extensions,
+        }
+    }
+}
+
+/// 200 with `{"status": ..., "components": [...]}` while ready, otherwise 503
+/// with the problem from [`HealthReport::to_problem_details`].
+///
+/// Probe failures are not logged as errors; they are expected during
+/// dependency outages and polled every few seconds.
+impl IntoResponse for HealthReport {
+    fn into_response(self) -> Response {
+        if !self.is_ready() {
+            let problem = self.to_problem_details();
+            return Rendered::from_problem(
+                StatusCode::SERVICE_UNAVAILABLE,
+                &problem,
+                get_errors_config().field_naming,
+            )
+            .into_response();
+        }
+
+        let status = match self.status() {
+            Some(HealthStatus::Degraded) => "degraded",
+            _ => "ok",
+        };
+        axum::Json(serde_json::json!({
+            "status": status,
+            "components": self.errors,
+        }))
+        .into_response()
+    }
+}
@@ -1,6 +1,22 @@
 //! HTTP error helper functions for common error patterns.
 
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use axum::http::Method;
+
 use super::app_error::{AppError, FieldError, ValidationErrors};
+use super::config_check::ConfigErrorKind;
+use super::envelope::ErrorEnvelope;
+use super::parse::INVALID_FORMAT;
+use super::upstream::UpstreamDetails;
+use super::webhook::SignatureFailure;
+
+/// `Retry-After` sent by [`draining`]: long enough for the load balancer to
+/// take the instance out of rotation.
+const DRAIN_RETRY_AFTER: Duration = Duration::from_secs(5);
 
 /// Create a not found error for a resource.
 pub fn not_found(resource: &str, id: impl Into<String>) -> AppError {
@@ -19,7 +35,11 @@ pub fn validation_error(field: &str, message: impl Into<String>) -> AppError {
 }
 
 /// Create a validation error with a specific code.
-pub fn validation_error_with_code(field: &str, code: &str, message: impl Into<String>) -> AppError {
+pub fn validation_error_with_code(
+    field: &str,
+    code: impl Into<Cow<'static, str>>,
+    message: impl Into<String>,
+) -> AppError {
     let mut errors = ValidationErrors::new();
     errors.add(field, code, message);
     AppError::Validation(errors)
@@ -28,7 +48,7 @@ pub fn validation_error_with_code(field: &str, code: &str, message: impl Into<St
 /// Create a validation error with the received value included.
 pub fn validation_error_with_value(
     field: &str,
-    code: &str,
+    code: impl Into<Cow<'static, str>>,
     message: impl Into<String>,
     received: impl Into<serde_json::Value>,
 ) -> AppError {
@@ -37,6 +57,19 @@ pub fn validation_error_with_value(
     AppError::Validation(errors)
 }
 
+/// Create a tenant mismatch error, rendered as a plain 404.
+pub fn tenant_mismatch(
+    resource: &str,
+    id: impl Into<String>,
+    tenant: impl Into<String>,
+) -> AppError {
+    AppError::TenantMismatch {
+        resource: resource.to_string(),
+        id: id.into(),
+        tenant: tenant.into(),
+    }
+}
+
 /// Create an unauthorized error.
 pub fn unauthorized() -> AppError {
     AppError::Unauthorized
@@ -46,6 +79,52 @@ pub fn unauthorized() -> AppError {
 pub fn forbidden(action: &str) -> AppError {
     AppError::Forbidden {
         action: action.to_string(),
+        required_permissions: Vec::new(),
+        actor_permissions: Vec::new(),
+    }
+}
+
+/// Create a forbidden error naming the permissions the action requires.
+///
+/// `actor_permissions` is recorded in the audit log but not returned.
+///
+/// # Example
+/// ```ignore
+/// return Err(forbidden_missing_permissions(
+///     "delete invoice",
+///     ["invoices:delete"],
+///     user.permissions.clone(),
+/// ));
+/// ```
+pub fn forbidden_missing_permissions(
+    action: &str,
+    required_permissions: impl IntoIterator<Item = impl Into<String>>,
+    actor_permissions: impl IntoIterator<Item = impl Into<String>>,
+) -> AppError {
+    AppError::Forbidden {
+        action: action.to_string(),
+        required_permissions: required_permissions.into_iter().map(Into::into).collect(),
+        actor_permissions: actor_permissions.into_iter().map(Into::into).collect(),
+    }
+}
+
+/// Create a webhook signature verification failure.
+pub fn invalid_signature(provider: impl Into<String>, reason: SignatureFailure) -> AppError {
+    AppError::InvalidSignature {
+        provider: provider.into(),
+        reason,
+    }
+}
+
+/// Create a CSRF verification failure.
+pub fn csrf_failure() -> AppError {
+    AppError::CsrfFailure
+}
+
+/// Create an invalid origin error.
+pub fn invalid_origin(origin: impl Into<String>) -> AppError {
+    AppError::InvalidOrigin {
+        origin: origin.into(),
     }
 }
 
@@ -56,16 +135,109 @@ pub fn conflict(message: impl Into<String>) -> AppError {
     }
 }
 
+/// Create an idempotency conflict for a key reused with a different payload.
+pub fn idempotency_conflict(key: impl Into<String>) -> AppError {
+    AppError::IdempotencyConflict { key: key.into() }
+}
+
+/// Create an error for a key whose first request is still being handled.
+pub fn idempotency_in_progress(key: impl Into<String>) -> AppError {
+    AppError::IdempotencyInProgress { key: key.into() }
+}
+
 /// Create an external service error.
 pub fn external_service_error(service: &str) -> AppError {
+    upstream_error(service, UpstreamDetails::new())
+}
+
+/// Create an external service error with details of the failed call.
+///
+/// # Example
+/// ```ignore
+/// let started = Instant::now();
+/// let response = client.get(url).send().await?;
+/// if !response.status().is_success() {
+///     let upstream = UpstreamDetails::new()
+///         .status(response.status().as_u16())
+///         .latency(started.elapsed())
+///         .snippet(response.text().await.unwrap_or_default());
+///     return Err(upstream_error("billing", upstream));
+/// }
+/// ```
+#[track_caller]
+pub fn upstream_error(service: &str, upstream: UpstreamDetails) -> AppError {
     AppError::ExternalServiceError {
         service: service.to_string(),
+        upstream,
+    }
+    .created()
+}
+
+/// Create a precondition failed error reporting the current entity tag.
+pub fn precondition_failed(etag: Option<&str>) -> AppError {
+    AppError::PreconditionFailed {
+        etag: etag.map(str::to_string),
+    }
+}
+
+/// Create a not modified response for a client whose cached copy is current.
+pub fn not_modified(etag: Option<&str>, last_modified: Option<SystemTime>) -> AppError {
+    AppError::NotModified {
+        etag: etag.map(str::to_string),
+        last_modified,
     }
 }
 
+/// Create a payment declined error carrying the provider's decline code.
+pub fn payment_declined(decline_code: Option<&str>) -> AppError {
+    AppError::PaymentDeclined {
+        decline_code: decline_code.map(str::to_string),
+    }
+}
+
+/// Create a rate limited error, optionally asking clients to retry after
+/// `retry_after`.
+pub fn rate_limited(retry_after: Option<Duration>) -> AppError {
+    AppError::RateLimited { retry_after }
+}
+
+/// Create a database error.
+#[track_caller]
+pub fn database_error(error: sea_orm::DbErr) -> AppError {
+    AppError::from(error)
+}
+
+/// Create a configuration error for `key`.
+#[track_caller]
+pub fn config_error(key: &str, kind: ConfigErrorKind) -> AppError {
+    AppError::ConfigError {
+        key: key.to_string(),
+        kind,
+        source: None,
+    }
+    .created()
+}
+
+/// Create a configuration error for `key` caused by `source` (e.g. a parse
+/// error). The source must not include the value.
+#[track_caller]
+pub fn config_error_with_source(
+    key: &str,
+    kind: ConfigErrorKind,
+    source: impl std::error::Error + Send + Sync + 'static,
+) -> AppError {
+    AppError::ConfigError {
+        key: key.to_string(),
+        kind,
+        source: Some(Arc::new(source)),
+    }
+    .created()
+}
+
 /// Create an internal server error.
+#[track_caller]
 pub fn internal_error(message: impl Into<String>) -> AppError {
-    AppError::InternalServerError(message.into())
+    AppError::InternalServerError(message.into()).created()
 }
 
 /// Create a bad request error.
@@ -73,11 +245,132 @@ pub fn bad_request(message: impl Into<String>) -> AppError {
     AppError::BadRequest(message.into())
 }
 
+/// Create an invalid cursor error for a pagination cursor that could not be
+/// decoded.
+pub fn invalid_cursor(received: impl Into<String>) -> AppError {
+    AppError::InvalidCursor {
+        received: received.into(),
+    }
+}
+
+/// Create an expired cursor error, telling the client to restart pagination.
+pub fn expired_cursor() -> AppError {
+    AppError::ExpiredCursor
+}
+
+/// Create a body too large error, reporting the limit in bytes if known.
+pub fn body_too_large(limit: Option<u64>) -> AppError {
+    AppError::BodyTooLarge { limit }
+}
+
+/// Create a body read timeout error for a client sending its body too slowly.
+pub fn body_read_timeout() -> AppError {
+    AppError::BodyReadTimeout
+}
+
+/// Create a headers too large error, reporting the limit in bytes if known.
+pub fn headers_too_large(limit: Option<usize>) -> AppError {
+    AppError::HeadersTooLarge { limit }
+}
+
+/// Create a URI too long error, reporting the limit in bytes if known.
+pub fn uri_too_long(limit: Option<usize>) -> AppError {
+    AppError::UriTooLong { limit }
+}
+
+/// Create an HTTP version not supported error.
+pub fn http_version_not_supported() -> AppError {
+    AppError::HttpVersionNotSupported
+}
+
+/// Create an upgrade required error naming the protocol to switch to,
+/// e.g. `upgrade_required("h2c")`.
+pub fn upgrade_required(protocol: impl Into<String>) -> AppError {
+    AppError::UpgradeRequired {
+        protocol: protocol.into(),
+    }
+}
+
+/// Create a method not allowed error, sending `allowed` in the `Allow` header.
+///
+/// # Example
+/// ```ignore
+/// let app = Router::new()
+///     .route("/users", get(list_users))
+///     .method_not_allowed_fallback(|| async { method_not_allowed(&[Method::GET]) });
+/// ```
+pub fn method_not_allowed(allowed: &[Method]) -> AppError {
+    AppError::MethodNotAllowed {
+        allowed: allowed.to_vec(),
+    }
+}
+
+/// Create a not acceptable error listing the media types that can be served.
+pub fn not_acceptable(supported: &[&str]) -> AppError {
+    AppError::NotAcceptable {
+        supported: supported
+            .iter()
+            .map(|media_type| media_type.to_string())
+            .collect(),
+    }
+}
+
+/// Create an invalid content encoding error for a body that could not be
+/// decoded (e.g. corrupt gzip), naming the encoding if known.
+pub fn invalid_content_encoding(encoding: Option<&str>) -> AppError {
+    AppError::InvalidContentEncoding {
+        encoding: encoding.map(str::to_string),
+    }
+}
+
 /// Create a service unavailable error.
 pub fn service_unavailable(message: impl Into<String>) -> AppError {
     AppError::ServiceUnavailable(message.into())
 }
 
+/// Create an overloaded error asking clients to retry after `retry_after`.
+pub fn overloaded(retry_after: Duration) -> AppError {
+    AppError::Overloaded {
+        queue_depth: None,
+        retry_after,
+    }
+}
+
+/// Create an overloaded error reporting the current queue depth.
+pub fn overloaded_with_depth(queue_depth: usize, retry_after: Duration) -> AppError {
+    AppError::Overloaded {
+        queue_depth: Some(queue_depth),
+        retry_after,
+    }
+}
+
+/// Create a draining error for requests arriving during shutdown.
+pub fn draining() -> AppError {
+    AppError::Draining {
+        retry_after: DRAIN_RETRY_AFTER,
+    }
+}
+
+/// Create a deadline exceeded error.
+pub fn deadline_exceeded() -> AppError {
+    AppError::DeadlineExceeded
+}
+
+/// Create a client disconnected error for a request the client abandoned.
+pub fn client_disconnected() -> AppError {
+    AppError::ClientDisconnected
+}
+
+/// Re-raise an error received from another service.
+pub fn remote_error(envelope: ErrorEnvelope) -> AppError {
+    AppError::from(envelope)
+}
+
+/// Create a gateway timeout error.
+pub fn gateway_timeout(message: impl Into<String>) -> AppError {
+    AppError::GatewayTimeout(message.into())
+}
+
 // =============================================================================
 // Builder pattern for multiple validation errors
 // =============================================================================
@@ -90,7 +383,9 @@ pub fn service_unavailable(message: impl Into<String>) -> AppError {
 ///
 /// let result = ValidationErrorBuilder::new()
 ///     .field("email", "invalid_format", "Must be a valid email")
-///     .field("name", "too_short", "Must be at least 3 characters")
+///     .field_if(input.name.len() < 3, "name", "too_short", "Must be at least 3 characters")
+///     .maybe_field(check_phone(&input.phone))
+///     .try_field("age", || check_age(input.age))
 ///     .build();
 ///
 /// if let Err(app_error) = result {
@@ -99,6 +394,8 @@ pub fn service_unavailable(message: impl Into<String>) -> AppError {
 /// ```
 pub struct ValidationErrorBuilder {
     errors: ValidationErrors,
+    max_errors_per_field: Option<usize>,
+    fail_fast: bool,
 }
 
 impl ValidationErrorBuilder {
@@ -106,12 +403,54 @@ impl ValidationErrorBuilder {
     pub fn new() -> Self {
         Self {
             errors: ValidationErrors::new(),
+            max_errors_per_field: None,
+            fail_fast: false,
+        }
+    }
+
+    /// Keep at most `max` errors per field; further errors on a full field
+    /// are dropped and its checks skipped.
+    pub fn max_errors_per_field(mut self, max: usize) -> Self {
+        self.max_errors_per_field = Some(max);
+        self
+    }
+
+    /// Keep only the first error; once there is one, further errors are
+    /// dropped and checks skipped.
+    pub fn fail_fast(mut self) -> Self {
+        self.fail_fast = true;
+        self
+    }
+
+    /// Whether errors on `field` are no longer recorded.
+    fn is_full(&self, field: &str) -> bool {
+        if self.fail_fast && !self.errors.is_empty() {
+            return true;
+        }
+        self.max_errors_per_field.is_some_and(|max| {
+            self.errors
+                .iter()
+                .filter(|error| error.field == field)
+                .count()
+                >= max
+        })
+    }
+
+    /// Record an error unless its field is full.
+    fn push(&mut self, error: FieldError) {
+        if !self.is_full(&error.field) {
+            self.errors.extend([error]);
         }
     }
 
     /// Add a field error.
-    pub fn field(mut self, field: &str, code: &str, message: impl Into<String>) -> Self {
-        self.errors.add(field, code, message);
+    pub fn field(
+        mut self,
+        field: &str,
+        code: impl Into<Cow<'static, str>>,
+        message: impl Into<String>,
+    ) -> Self {
+        self.push(FieldError::new(field, code, message));
         self
     }
 
@@ -119,19 +458,103 @@ impl ValidationErrorBuilder {
     pub fn field_with_value(
         mut self,
         field: &str,
-        code: &str,
+        code: impl Into<Cow<'static, str>>,
         message: impl Into<String>,
         received: impl Into<serde_json::Value>,
     ) -> Self {
-        self.errors.add_with_value(field, code, message, received);
+        self.push(FieldError::with_received(field, code, message, received));
         self
     }
 
+    /// Add a field error if `condition` holds.
+    pub fn field_if(
+        self,
+        condition: bool,
+        field: &str,
+        code: impl Into<Cow<'static, str>>,
+        message: impl Into<String>,
+    ) -> Self {
+        if condition {
+            self.field(field, code, message)
+        } else {
+            self
+        }
+    }
+
+    /// Add a field error if there is one, e.g. from a check returning
+    /// `Option<FieldError>`.
+    pub fn maybe_field(mut self, error: Option<FieldError>) -> Self {
+        if let Some(error) = error {
+            self.push(error);
+        }
+        self
+    }
+
+    /// Run a check and add its error, reported under `field`, if it fails.
+    ///
+    /// The check is skipped if `field` can take no more errors.
+    pub fn try_field<T>(
+        mut self,
+        field: &str,
+        check: impl FnOnce() -> Result<T, FieldError>,
+    ) -> Self {
+        if self.is_full(field) {
+            return self;
+        }
+        if let Err(mut error) = check() {
+            error.field = field.to_string();
+            self.push(error);
+        }
+        self
+    }
+
+    /// Parse `raw` with `parser` (e.g. `str::parse::<u32>`), recording an
+    /// `invalid_format` error on `field` with the received value if it fails.
+    ///
+    /// Returns the parsed value, or `None` after a failure, so every field
+    /// can be parsed before the errors are reported together:
+    /// ```ignore
+    /// let mut builder = ValidationErrorBuilder::new();
+    /// let limit = builder.parse("limit", &query.limit, str::parse::<u32>);
+    /// let since = builder.parse("since", &query.since, parse_timestamp);
+    /// builder.build()?;
+    /// ```
+    pub fn parse<T, E: Display>(
+        &mut self,
+        field: &str,
+        raw: &str,
+        parser: impl FnOnce(&str) -> Result<T, E>,
+    ) -> Option<T> {
+        match parser(raw) {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.push(FieldError::with_received(
+                    field,
+                    INVALID_FORMAT,
+                    error.to_string(),
+                    raw,
+                ));
+                None
+            }
+        }
+    }
+
     /// Build the result. Returns Ok(()) if no errors, Err(AppError) otherwise.
     pub fn build(self) -> Result<(), AppError> {
         self.errors.into_result()
     }
 
+    /// Build the result, returning `value` if there are no errors.
+    ///
+    /// ```ignore
+    /// ValidationErrorBuilder::new()
+    ///     .field_if(name.is_empty(), "name", "required", "Name is required")
+    ///     .build_with(User { name })
+    /// ```
+    pub fn build_with<T>(self, value: T) -> Result<T, AppError> {
+        self.build().map(|()| value)
+    }
+
     /// Check if there are any errors.
     pub fn has_errors(&self) -> bool {
         !self.errors.is_empty()
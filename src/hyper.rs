@@ -0,0 +1,63 @@
+//! Conversion from hyper connection errors.
+//!
+//! Most hyper errors on the server side mean the client went away: it
+//! cancelled the request, closed the connection mid-body, or reset the
+//! stream. Those become [`AppError::ClientDisconnected`] (499, logged at
+//! debug) so user cancellations don't show up as 500s; oversized headers and
+//! URIs become 431 and 414, unsupported HTTP versions 505, other malformed
+//! requests 400, and only the rest are server failures.
+
+use std::error::Error;
+use std::io;
+
+use super::app_error::AppError;
+use super::box_error::is_disconnect;
+use super::config::get_errors_config;
+
+impl From<hyper::Error> for AppError {
+    fn from(error: hyper::Error) -> Self {
+        if error.is_canceled()
+            || error.is_closed()
+            || error.is_incomplete_message()
+            || error.is_body_write_aborted()
+            || is_client_io_error(&error)
+        {
+            AppError::ClientDisconnected
+        } else if error.is_parse_too_large() {
+            let config = get_errors_config();
+            // hyper reports both as `Parse` errors, told apart by message.
+            if error.to_string().starts_with("URI too long") {
+                AppError::UriTooLong {
+                    limit: config.uri_length_limit,
+                }
+            } else {
+                AppError::HeadersTooLarge {
+                    limit: config.header_size_limit,
+                }
+            }
+        } else if error.is_parse() && error.to_string().starts_with("invalid HTTP version") {
+            AppError::HttpVersionNotSupported
+        } else if error.is_parse() {
+            AppError::BadRequest("Malformed HTTP request".to_string())
+        } else if error.is_timeout() {
+            AppError::BodyReadTimeout
+        } else {
+            AppError::InternalServerError(error.to_string())
+        }
+    }
+}
+
+/// Whether the error chain contains an I/O error caused by the peer going
+/// away.
+fn is_client_io_error(error: &(dyn Error + 'static)) -> bool {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(io_error) = error.downcast_ref::<io::Error>()
+            && is_disconnect(io_error.kind())
+        {
+            return true;
+        }
+        current = error.source();
+    }
+    false
+}
@@ -0,0 +1,251 @@
+//! Replay of idempotent requests.
+//!
+//! A client retrying a `POST` with the same `Idempotency-Key` must get the
+//! original response instead of a second side effect, and reusing the key
+//! for a different request is a client error. [`check_idempotency`]
+//! atomically reserves the key in an [`IdempotencyStore`] and tells the two
+//! apart by a hash of the request payload, computed by the caller. A retry
+//! that arrives while the first request is still running gets a 409 instead
+//! of running the side effect concurrently.
+//!
+//! # Example
+//! ```ignore
+//! async fn create_payment(
+//!     State(state): State<AppState>,
+//!     headers: HeaderMap,
+//!     body: Bytes,
+//! ) -> Result<Response, AppError> {
+//!     let key = idempotency_key(&headers)?;
+//!     let hash = sha256_hex(&body);
+//!     if let Some(cached) = check_idempotency(&state.store, key, &hash).await? {
+//!         return Ok(cached.into_response());
+//!     }
+//!
+//!     let payment = match payments::create(&body).await {
+//!         Ok(payment) => payment,
+//!         Err(error) => {
+//!             state.store.release(key).await?;
+//!             return Err(error);
+//!         }
+//!     };
+//!     let body = serde_json::to_vec(&payment).map_err(|e| internal_error(e.to_string()))?;
+//!     state.store.save(key, StoredResponse::new(hash, 201, body.clone())).await?;
+//!     Ok((StatusCode::CREATED, body).into_response())
+//! }
+//! ```
+
+use std::future::Future;
+
+use axum::http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use bytes::Bytes;
+
+use super::app_error::AppError;
+use super::http_errors::{bad_request, idempotency_conflict, idempotency_in_progress};
+
+/// Request header carrying the idempotency key.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Response header marking a replayed response.
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// A response kept for replay, with the hash of the payload that produced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredResponse {
+    /// Hash of the request payload, compared on every reuse of the key.
+    pub payload_hash: String,
+    /// Status code of the original response.
+    pub status: u16,
+    /// `Content-Type` of the original response (default `application/json`).
+    pub content_type: String,
+    /// Body of the original response.
+    pub body: Bytes,
+}
+
+impl StoredResponse {
+    /// Create a stored JSON response.
+    pub fn new(payload_hash: impl Into<String>, status: u16, body: impl Into<Bytes>) -> Self {
+        Self {
+            payload_hash: payload_hash.into(),
+            status,
+            content_type: "application/json".to_string(),
+            body: body.into(),
+        }
+    }
+
+    /// Set the content type of the stored response.
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = content_type.into();
+        self
+    }
+}
+
+/// The original response, with `Idempotent-Replayed: true`.
+impl IntoResponse for StoredResponse {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+        let mut response = (status, self.body).into_response();
+        let headers = response.headers_mut();
+        if let Ok(content_type) = HeaderValue::from_str(&self.content_type) {
+            headers.insert(header::CONTENT_TYPE, content_type);
+        }
+        headers.insert(
+            HeaderName::from_static(IDEMPOTENT_REPLAYED_HEADER),
+            HeaderValue::from_static("true"),
+        );
+        response
+    }
+}
+
+/// State of an idempotency key, as found by [`IdempotencyStore::reserve`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reservation {
+    /// The key was unused and is now reserved for this request.
+    Reserved,
+    /// The key is reserved by a request that has not completed yet.
+    InProgress {
+        /// Hash of the payload of the request holding the key.
+        payload_hash: String,
+    },
+    /// The key was used; the response to replay.
+    Completed(StoredResponse),
+}
+
+/// Persistence for responses to idempotent requests, keyed by idempotency
+/// key (typically a table or cache with a TTL of a day or so).
+pub trait IdempotencyStore {
+    /// Reserve `key` for a request with `payload_hash` if it is unused, or
+    /// return its current state.
+    ///
+    /// Must be atomic (`INSERT ... ON CONFLICT DO NOTHING`, `SET NX`, ...) so
+    /// that of two concurrent requests with the same key only one gets
+    /// [`Reservation::Reserved`].
+    fn reserve(
+        &self,
+        key: &str,
+        payload_hash: &str,
+    ) -> impl Future<Output = Result<Reservation, AppError>> + Send;
+
+    /// Store the response to the request holding the reservation for `key`.
+    fn save(
+        &self,
+        key: &str,
+        response: StoredResponse,
+    ) -> impl Future<Output = Result<(), AppError>> + Send;
+
+    /// Drop the reservation for `key` without a response, so the request can
+    /// be retried (e.g. after a failure that should not be replayed).
+    fn release(&self, key: &str) -> impl Future<Output = Result<(), AppError>> + Send;
+}
+
+/// The `Idempotency-Key` of a request; a 400 if it is missing or not text.
+pub fn idempotency_key(headers: &HeaderMap) -> Result<&str, AppError> {
+    headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| bad_request("Missing or invalid Idempotency-Key header"))
+}
+
+/// Reserve `key` before handling a request.
+///
+/// Returns the stored response to replay if the key was used for the same
+/// payload, or `None` if the key was new and is now reserved: the request
+/// should be handled, then [`save`](IdempotencyStore::save)d or
+/// [`release`](IdempotencyStore::release)d. Fails with an
+/// `IdempotencyInProgress` (409) while a request with the same payload holds
+/// the key, and an `IdempotencyConflict` (422) if the key was used for a
+/// different payload.
+pub async fn check_idempotency<S>(
+    store: &S,
+    key: &str,
+    payload_hash: &str,
+) -> Result<Option<StoredResponse>, AppError>
+where
+    S: IdempotencyStore,
+{
+    match store.reserve(key, payload_hash).await? {
+        Reservation::Reserved => Ok(None),
+        Reservation::Completed(stored) if stored.payload_hash == payload_hash => Ok(Some(stored)),
+        Reservation::InProgress { payload_hash: held } if held == payload_hash => {
+            Err(idempotency_in_progress(key))
+        }
+        Reservation::Completed(_) | Reservation::InProgress { .. } => {
+            Err(idempotency_conflict(key))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MemoryStore(Mutex<HashMap<String, Reservation>>);
+
+    impl IdempotencyStore for MemoryStore {
+        async fn reserve(&self, key: &str, payload_hash: &str) -> Result<Reservation, AppError> {
+            let mut keys = self.0.lock().unwrap();
+            Ok(match keys.get(key) {
+                Some(state) => state.clone(),
+                None => {
+                    let held = Reservation::InProgress {
+                        payload_hash: payload_hash.to_string(),
+                    };
+                    keys.insert(key.to_string(), held);
+                    Reservation::Reserved
+                }
+            })
+        }
+
+        async fn save(&self, key: &str, response: StoredResponse) -> Result<(), AppError> {
+            let mut keys = self.0.lock().unwrap();
+            keys.insert(key.to_string(), Reservation::Completed(response));
+            Ok(())
+        }
+
+        async fn release(&self, key: &str) -> Result<(), AppError> {
+            self.0.lock().unwrap().remove(key);
+            Ok(())
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn retry_while_in_progress_is_a_conflict() {
+        block_on(async {
+            let store = MemoryStore::default();
+            assert_eq!(check_idempotency(&store, "k1", "h1").await.unwrap(), None);
+
+            let error = check_idempotency(&store, "k1", "h1").await.unwrap_err();
+            assert!(matches!(error, AppError::IdempotencyInProgress { .. }));
+            assert_eq!(error.status(), StatusCode::CONFLICT);
+            let error = check_idempotency(&store, "k1", "h2").await.unwrap_err();
+            assert!(matches!(error, AppError::IdempotencyConflict { .. }));
+
+            let response = StoredResponse::new("h1", 201, "{}");
+            store.save("k1", response.clone()).await.unwrap();
+            let replayed = check_idempotency(&store, "k1", "h1").await.unwrap();
+            assert_eq!(replayed, Some(response));
+        });
+    }
+
+    #[test]
+    fn released_key_can_be_reserved_again() {
+        block_on(async {
+            let store = MemoryStore::default();
+            assert_eq!(check_idempotency(&store, "k1", "h1").await.unwrap(), None);
+            store.release("k1").await.unwrap();
+            assert_eq!(check_idempotency(&store, "k1", "h1").await.unwrap(), None);
+        });
+    }
+}
@@ -0,0 +1,187 @@
+//! Error semantics for background jobs and queue consumers.
+//!
+//! Jobs share the `AppError` taxonomy but need retry directives instead of
+//! HTTP statuses. [`AppError::retry_directive`] maps each variant to retry,
+//! discard, or dead-letter; [`JobError`] adds attempt-aware backoff and the
+//! headers to attach to dead-lettered messages.
+
+use std::time::Duration;
+
+use super::app_error::{AppError, make_public};
+use super::config::{ErrorsConfig, get_errors_config};
+
+/// Upper bound on the backoff computed by [`JobError::directive`].
+const MAX_BACKOFF: Duration = Duration::from_secs(600);
+
+/// What a job runner should do with a failed message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryDirective {
+    /// Transient failure: retry after `backoff`.
+    Retry { backoff: Duration },
+    /// Permanent failure that needs no follow-up (e.g. the target is gone).
+    Discard,
+    /// Permanent failure that needs inspection: move to the dead-letter queue.
+    DeadLetter,
+}
+
+impl AppError {
+    /// Map this error to a retry directive for background jobs.
+    pub fn retry_directive(&self) -> RetryDirective {
+        let retry = |secs| RetryDirective::Retry {
+            backoff: Duration::from_secs(secs),
+        };
+
+        match self {
+            AppError::Annotated(annotated) => annotated.error.retry_directive(),
+            AppError::NotFound { .. }
+            | AppError::NotModified { .. }
+            | AppError::ClientDisconnected => RetryDirective::Discard,
+            AppError::Conflict { .. }
+            | AppError::IdempotencyInProgress { .. }
+            | AppError::PreconditionFailed { .. } => retry(1),
+            AppError::DatabaseError(_) => retry(5),
+            AppError::ExternalServiceError { upstream, .. }
+                if upstream.transient == Some(false) =>
+            {
+                RetryDirective::DeadLetter
+            }
+            AppError::ExternalServiceError { .. } => retry(10),
+            AppError::ServiceUnavailable(_) => retry(30),
+            AppError::GatewayTimeout(_)
+            | AppError::DeadlineExceeded
+            | AppError::BodyReadTimeout => retry(10),
+            AppError::Overloaded { retry_after, .. } | AppError::Draining { retry_after } => {
+                RetryDirective::Retry {
+                    backoff: *retry_after,
+                }
+            }
+            AppError::RateLimited { retry_after } => match retry_after {
+                Some(retry_after) => RetryDirective::Retry {
+                    backoff: *retry_after,
+                },
+                None => retry(10),
+            },
+            AppError::Validation(_)
+            | AppError::ValidationField { .. }
+            | AppError::BadRequest(_)
+            | AppError::InvalidCursor { .. }
+            | AppError::ExpiredCursor
+            | AppError::BodyTooLarge { .. }
+            | AppError::InvalidContentEncoding { .. }
+            | AppError::NotAcceptable { .. }
+            | AppError::MethodNotAllowed { .. }
+            | AppError::HeadersTooLarge { .. }
+            | AppError::UriTooLong { .. }
+            | AppError::IdempotencyConflict { .. }
+            | AppError::HttpVersionNotSupported
+            | AppError::UpgradeRequired { .. }
+            | AppError::PaymentDeclined { .. }
+            | AppError::Unauthorized
+            | AppError::Forbidden { .. }
+            | AppError::TenantMismatch { .. }
+            | AppError::InvalidSignature { .. }
+            | AppError::CsrfFailure
+            | AppError::InvalidOrigin { .. }
+            | AppError::ConfigError { .. }
+            | AppError::InternalServerError(_) => RetryDirective::DeadLetter,
+            AppError::Remote(envelope) => match envelope.status {
+                404 | 410 => RetryDirective::Discard,
+                409 => retry(1),
+                429 | 502 | 503 | 504 => retry(10),
+                _ => RetryDirective::DeadLetter,
+            },
+        }
+    }
+}
+
+/// An `AppError` raised by a job, together with the attempt that failed.
+#[derive(Debug, Clone)]
+pub struct JobError {
+    /// The underlying error.
+    pub error: AppError,
+
+    /// Number of the failed attempt, starting at 1.
+    pub attempt: u32,
+}
+
+impl JobError {
+    /// Wrap an error raised on the given attempt (starting at 1).
+    pub fn new(error: AppError, attempt: u32) -> Self {
+        Self { error, attempt }
+    }
+
+    /// Retry directive with exponential backoff: the base backoff doubles with
+    /// every attempt, capped at ten minutes.
+    pub fn directive(&self) -> RetryDirective {
+        match self.error.retry_directive() {
+            RetryDirective::Retry { backoff } => {
+                let factor = 2u32.saturating_pow(self.attempt.saturating_sub(1));
+                RetryDirective::Retry {
+                    backoff: backoff.saturating_mul(factor).min(MAX_BACKOFF),
+                }
+            }
+            directive => directive,
+        }
+    }
+
+    /// Headers describing the failure, for dead-lettered messages.
+    ///
+    /// Includes the full problem as JSON under `x-problem`, alongside the
+    /// individual members most useful for filtering. The problem is scrubbed
+    /// and its detail exposed as in a response, since every consumer of the
+    /// dead-letter queue can read it.
+    pub fn dlq_headers(&self) -> Vec<(&'static str, String)> {
+        self.dlq_headers_with(&get_errors_config())
+    }
+
+    fn dlq_headers_with(&self, config: &ErrorsConfig) -> Vec<(&'static str, String)> {
+        let mut problem = self.error.to_problem_details();
+        make_public(config, &mut problem);
+        let problem_json = serde_json::to_string(&problem).unwrap_or_default();
+
+        vec![
+            ("x-error-code", problem.code.into_owned()),
+            ("x-error-status", problem.status.to_string()),
+            ("x-error-type", problem.error_type.into_owned()),
+            ("x-error-attempt", self.attempt.to_string()),
+            ("x-request-id", problem.request_id),
+            ("x-problem", problem_json),
+        ]
+    }
+}
+
+impl std::fmt::Display for JobError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (attempt {})", self.error, self.attempt)
+    }
+}
+
+impl std::error::Error for JobError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn dlq_problem_has_canned_database_detail() {
+        let error = AppError::DatabaseError(Arc::new(sea_orm::DbErr::Custom(
+            "relation \"users\" password=hunter2".into(),
+        )));
+        let headers = JobError::new(error, 3).dlq_headers_with(&ErrorsConfig::production());
+
+        let (_, problem) = headers
+            .iter()
+            .find(|(name, _)| *name == "x-problem")
+            .unwrap();
+        let problem: serde_json::Value = serde_json::from_str(problem).unwrap();
+        assert_eq!(problem["code"], "DATABASE_ERROR");
+        assert_eq!(problem["detail"], "An internal error occurred");
+        assert!(!headers.iter().any(|(_, value)| value.contains("hunter2")));
+    }
+}
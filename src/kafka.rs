@@ -0,0 +1,62 @@
+//! Conversion from rdkafka errors, for producers embedded in HTTP handlers.
+//!
+//! Broker connectivity problems become retryable 503s, oversized messages a
+//! validation error, and authorization failures a 403; everything else is a
+//! 502 from the `kafka` service.
+
+use std::sync::Arc;
+
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+
+use super::app_error::AppError;
+use super::config_check::ConfigErrorKind;
+use super::http_errors::{
+    external_service_error, forbidden, gateway_timeout, service_unavailable,
+    validation_error_with_code,
+};
+
+impl From<KafkaError> for AppError {
+    fn from(error: KafkaError) -> Self {
+        let Some(code) = error.rdkafka_error_code() else {
+            return match error {
+                // The error's Display includes the value, so only the key is kept.
+                KafkaError::ClientConfig(_, _, key, _) => AppError::ConfigError {
+                    key,
+                    kind: ConfigErrorKind::Invalid,
+                    source: None,
+                },
+                KafkaError::ClientCreation(_) => AppError::ConfigError {
+                    key: "kafka".to_string(),
+                    kind: ConfigErrorKind::Invalid,
+                    source: Some(Arc::new(error)),
+                },
+                _ => external_service_error("kafka"),
+            };
+        };
+
+        match code {
+            RDKafkaErrorCode::BrokerTransportFailure
+            | RDKafkaErrorCode::AllBrokersDown
+            | RDKafkaErrorCode::BrokerNotAvailable
+            | RDKafkaErrorCode::LeaderNotAvailable
+            | RDKafkaErrorCode::NotLeaderForPartition
+            | RDKafkaErrorCode::NetworkException
+            | RDKafkaErrorCode::QueueFull => service_unavailable(format!("Kafka: {code}")),
+            RDKafkaErrorCode::MessageTimedOut
+            | RDKafkaErrorCode::RequestTimedOut
+            | RDKafkaErrorCode::OperationTimedOut => gateway_timeout(format!("Kafka: {code}")),
+            RDKafkaErrorCode::MessageSizeTooLarge | RDKafkaErrorCode::InvalidMessageSize => {
+                validation_error_with_code(
+                    "message",
+                    "message_too_large",
+                    "Message exceeds the broker's size limit",
+                )
+            }
+            RDKafkaErrorCode::TopicAuthorizationFailed
+            | RDKafkaErrorCode::GroupAuthorizationFailed
+            | RDKafkaErrorCode::ClusterAuthorizationFailed
+            | RDKafkaErrorCode::TransactionalIdAuthorizationFailed => forbidden("kafka"),
+            _ => external_service_error("kafka"),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Coarse error classification, stable across new variants.
+
+use super::app_error::AppError;
+
+/// The class of an error, for middleware that branches on what went wrong
+/// without matching every `AppError` variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The resource does not exist (or must look like it does not).
+    NotFound,
+    /// Input failed validation.
+    Validation,
+    /// The request is malformed.
+    BadRequest,
+    /// Authentication is missing or invalid.
+    Unauthenticated,
+    /// Authenticated, but not allowed.
+    Forbidden,
+    /// The request conflicts with the current state.
+    Conflict,
+    /// Not an error: the client's cached copy is current.
+    NotModified,
+    /// The service cannot take the request right now.
+    Unavailable,
+    /// A deadline or upstream timeout was hit.
+    Timeout,
+    /// The client went away before the response was sent.
+    Cancelled,
+    /// A dependency failed.
+    Dependency,
+    /// A bug or misconfiguration on our side.
+    Internal,
+}
+
+impl ErrorKind {
+    /// Whether the error is worth retrying unchanged.
+    pub fn is_transient(self) -> bool {
+        matches!(
+            self,
+            ErrorKind::Unavailable | ErrorKind::Timeout | ErrorKind::Dependency
+        )
+    }
+}
+
+impl AppError {
+    /// Classify this error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            AppError::Annotated(annotated) => annotated.error.kind(),
+            AppError::NotFound { .. } | AppError::TenantMismatch { .. } => ErrorKind::NotFound,
+            AppError::Validation(_) | AppError::ValidationField { .. } => ErrorKind::Validation,
+            AppError::BadRequest(_)
+            | AppError::InvalidCursor { .. }
+            | AppError::ExpiredCursor
+            | AppError::BodyTooLarge { .. }
+            | AppError::InvalidContentEncoding { .. }
+            | AppError::NotAcceptable { .. }
+            | AppError::MethodNotAllowed { .. }
+            | AppError::HeadersTooLarge { .. }
+            | AppError::UriTooLong { .. }
+            | AppError::HttpVersionNotSupported
+            | AppError::UpgradeRequired { .. } => ErrorKind::BadRequest,
+            AppError::Unauthorized | AppError::InvalidSignature { .. } => {
+                ErrorKind::Unauthenticated
+            }
+            AppError::Forbidden { .. } | AppError::CsrfFailure | AppError::InvalidOrigin { .. } => {
+                ErrorKind::Forbidden
+            }
+            AppError::Conflict { .. }
+            | AppError::IdempotencyConflict { .. }
+            | AppError::IdempotencyInProgress { .. }
+            | AppError::PreconditionFailed { .. } => ErrorKind::Conflict,
+            AppError::NotModified { .. } => ErrorKind::NotModified,
+            AppError::PaymentDeclined { .. } => ErrorKind::BadRequest,
+            AppError::RateLimited { .. }
+            | AppError::ServiceUnavailable(_)
+            | AppError::Overloaded { .. }
+            | AppError::Draining { .. } => ErrorKind::Unavailable,
+            AppError::GatewayTimeout(_)
+            | AppError::DeadlineExceeded
+            | AppError::BodyReadTimeout => ErrorKind::Timeout,
+            AppError::ClientDisconnected => ErrorKind::Cancelled,
+            AppError::ExternalServiceError { .. } => ErrorKind::Dependency,
+            AppError::DatabaseError(_)
+            | AppError::ConfigError { .. }
+            | AppError::InternalServerError(_) => ErrorKind::Internal,
+            AppError::Remote(envelope) => match envelope.status {
+                401 => ErrorKind::Unauthenticated,
+                403 => ErrorKind::Forbidden,
+                404 | 410 => ErrorKind::NotFound,
+                409 | 412 => ErrorKind::Conflict,
+                422 => ErrorKind::Validation,
+                429 | 503 => ErrorKind::Unavailable,
+                408 | 504 => ErrorKind::Timeout,
+                502 => ErrorKind::Dependency,
+                499 => ErrorKind::Cancelled,
+                400..=498 => ErrorKind::BadRequest,
+                _ => ErrorKind::Internal,
+            },
+        }
+    }
+}
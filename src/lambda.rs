@@ -0,0 +1,67 @@
+//! AWS Lambda (API Gateway / ALB) integration.
+//!
+//! # Example
+//! ```ignore
+//! use eywa_errors::lambda::scope_lambda_request;
+//! use lambda_http::{service_fn, Body, Error, Request, Response};
+//!
+//! async fn handler(request: Request) -> Result<Response<Body>, Error> {
+//!     scope_lambda_request(&request, async {
+//!         match get_user(&request).await {
+//!             Ok(user) => Ok(json_response(user)),
+//!             Err(error) => Ok(error.into_lambda_response()),
+//!         }
+//!     })
+//!     .await
+//! }
+//! ```
+
+use lambda_http::request::RequestContext;
+use lambda_http::{Body, Request, RequestExt, Response};
+use uuid::Uuid;
+
+use super::app_error::{AppError, CURRENT_REQUEST_ID};
+
+impl AppError {
+    /// Render into a Lambda HTTP response with a problem+json body.
+    pub fn into_lambda_response(self) -> Response<Body> {
+        self.into_http_response()
+            .map(|body| match String::from_utf8(body.to_vec()) {
+                Ok(text) => Body::Text(text),
+                Err(error) => Body::Binary(error.into_bytes()),
+            })
+    }
+}
+
+impl From<AppError> for Response<Body> {
+    fn from(error: AppError) -> Self {
+        error.into_lambda_response()
+    }
+}
+
+/// The request ID of a Lambda invocation.
+///
+/// Uses the Lambda runtime request ID, falling back to the API Gateway request
+/// ID when the runtime context is not available.
+pub fn lambda_request_id(request: &Request) -> Option<Uuid> {
+    if let Some(context) = request.lambda_context_ref() {
+        return Uuid::parse_str(&context.request_id).ok();
+    }
+
+    let gateway_id = match request.request_context_ref()? {
+        RequestContext::ApiGatewayV1(context) => context.request_id.as_deref(),
+        RequestContext::ApiGatewayV2(context) => context.request_id.as_deref(),
+        _ => None,
+    };
+    gateway_id.and_then(|id| Uuid::parse_str(id).ok())
+}
+
+/// Run `future` with the invocation's request ID as the current request ID,
+/// so rendered errors carry the ID AWS reports for the invocation.
+pub async fn scope_lambda_request<F>(request: &Request, future: F) -> F::Output
+where
+    F: Future,
+{
+    let request_id = lambda_request_id(request).unwrap_or_else(Uuid::new_v4);
+    CURRENT_REQUEST_ID.scope(request_id, future).await
+}
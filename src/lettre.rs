@@ -0,0 +1,26 @@
+//! Conversion from lettre SMTP transport errors.
+//!
+//! Every failure is an `ExternalServiceError` for the `smtp` service, but
+//! only transient ones are retried: a 4xx reply (greylisting, mailbox busy)
+//! or a dropped connection may succeed later, a 5xx reply (no such mailbox,
+//! message rejected) never will.
+
+use lettre::transport::smtp::Error as SmtpError;
+
+use super::app_error::AppError;
+use super::http_errors::upstream_error;
+use super::outbound::ConnectFailure;
+use super::upstream::UpstreamDetails;
+
+impl From<SmtpError> for AppError {
+    fn from(error: SmtpError) -> Self {
+        let transient = !(error.is_permanent() || error.is_client());
+        let mut upstream = UpstreamDetails::new()
+            .transient(transient)
+            .snippet(error.to_string());
+        if let Some(failure) = ConnectFailure::classify(&error) {
+            upstream = upstream.failure(failure);
+        }
+        upstream_error("smtp", upstream)
+    }
+}
@@ -1,14 +1,264 @@
+// Lets the code generated by `IntoAppError` name this crate in its tests.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as eywa_errors;
+
+mod problem;
+mod timestamp;
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "protobuf")]
+pub mod protobuf;
+
+pub use problem::{FieldError, FieldErrors, ProblemDetails};
+pub use timestamp::{Clock, Timestamp, TimestampFormat, TimestampPrecision};
+
+#[cfg(feature = "server")]
+mod api_response;
+#[cfg(feature = "server")]
 mod app_error;
+#[cfg(feature = "server")]
+mod audit;
+#[cfg(feature = "server")]
+mod body;
+#[cfg(feature = "server")]
+mod box_error;
+#[cfg(feature = "server")]
+pub mod catalog;
+#[cfg(feature = "server")]
+mod cli;
+#[cfg(feature = "server")]
+mod conditional;
+#[cfg(feature = "server")]
+mod config;
+#[cfg(feature = "server")]
+mod config_check;
+#[cfg(feature = "server")]
+mod content;
+#[cfg(feature = "server")]
+mod context;
+#[cfg(feature = "server")]
+mod converter;
+#[cfg(feature = "server")]
+mod cors;
+#[cfg(feature = "server")]
+mod csrf;
+#[cfg(feature = "server")]
+mod docs;
+#[cfg(feature = "server")]
+mod drain;
+#[cfg(feature = "server")]
+mod envelope;
+#[cfg(feature = "server")]
+mod health;
+#[cfg(feature = "server")]
 mod http_errors;
+#[cfg(feature = "server")]
+mod idempotency;
+#[cfg(feature = "server")]
+mod job;
+#[cfg(feature = "server")]
+mod kind;
+#[cfg(feature = "server")]
+mod limits;
+#[cfg(feature = "server")]
+mod locale;
+#[cfg(feature = "server")]
+mod log_throttle;
+#[cfg(feature = "server")]
+mod negotiate;
+#[cfg(feature = "server")]
+mod outbound;
+#[cfg(feature = "server")]
+mod parse;
+#[cfg(feature = "server")]
+mod password;
+#[cfg(feature = "server")]
+mod problem_type;
+#[cfg(feature = "server")]
+mod profile;
+#[cfg(feature = "server")]
+mod query;
+#[cfg(feature = "server")]
+mod render;
+#[cfg(feature = "server")]
+mod report;
+#[cfg(feature = "server")]
+mod scrub;
+#[cfg(feature = "server")]
+mod severity;
+#[cfg(feature = "server")]
+mod slo;
+#[cfg(feature = "server")]
+mod startup;
+#[cfg(feature = "server")]
+pub mod stats;
+#[cfg(feature = "server")]
+mod template;
+#[cfg(feature = "server")]
+mod unique;
+#[cfg(feature = "server")]
+mod upstream;
+#[cfg(feature = "server")]
+mod warnings;
+#[cfg(feature = "server")]
+mod webhook;
+
+#[cfg(feature = "actix")]
+mod actix;
+
+#[cfg(feature = "contact")]
+pub mod contact;
+
+#[cfg(feature = "hyper")]
+mod hyper;
+
+#[cfg(feature = "kafka")]
+mod kafka;
+
+#[cfg(feature = "lambda")]
+pub mod lambda;
+
+#[cfg(feature = "lettre")]
+mod lettre;
+
+#[cfg(feature = "derive")]
+mod mapping;
+
+#[cfg(feature = "decimal")]
+pub mod money;
 
+#[cfg(feature = "reqwest-middleware")]
+mod outbound_middleware;
+
+#[cfg(feature = "async-nats")]
+pub mod nats;
+
+#[cfg(feature = "stripe")]
+pub mod payment;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "tower")]
+mod tower_layer;
+
+#[cfg(feature = "server")]
+pub use api_response::{ApiResponse, ApiResult, accepted, created};
+#[cfg(feature = "server")]
 pub use app_error::{
-    AppError, CURRENT_REQUEST_ID, FieldError, ProblemDetails, ValidationErrors, get_request_id,
-    set_request_id,
+    Annotated, AppError, CURRENT_REQUEST_ID, ValidationErrors, get_request_id, set_request_id,
+};
+#[cfg(feature = "server")]
+pub use audit::{AuditEvent, AuditHook};
+#[cfg(feature = "server")]
+pub use cli::{
+    EX_CONFIG, EX_DATAERR, EX_NOINPUT, EX_NOPERM, EX_SOFTWARE, EX_UNAVAILABLE, EX_USAGE,
+};
+#[cfg(feature = "server")]
+pub use conditional::check_preconditions;
+#[cfg(feature = "server")]
+pub use config::{
+    DetailExposure, ERRORS_MODE_ENV, ErrorFormat, ErrorsConfig, FieldNaming, FieldPathStyle,
+    get_errors_config, set_errors_config,
+};
+#[cfg(feature = "server")]
+pub use config_check::{ConfigErrorKind, env_var, parse_env};
+#[cfg(feature = "server")]
+pub use content::{
+    CORRUPT_ARCHIVE, FILE_TOO_LARGE, IMAGE_TOO_LARGE, UNSUPPORTED_IMAGE_FORMAT, corrupt_archive,
+    file_too_large, image_too_large, unsupported_image_format,
+};
+#[cfg(feature = "server")]
+pub use context::{
+    CORRELATION_ID_HEADER, CURRENT_REQUEST_CONTEXT, ERROR_FORMAT_HEADER, GRPC_TIMEOUT_HEADER,
+    REQUEST_DEADLINE_HEADER, REQUEST_ID_HEADER, RequestContext, check_deadline,
+    get_request_context, propagate_request_headers, remaining_budget, request_id_from_headers,
+    set_request_context,
+};
+#[cfg(feature = "server")]
+pub use converter::register_converter;
+#[cfg(feature = "server")]
+pub use cors::CorsPolicy;
+#[cfg(feature = "server")]
+pub use csrf::check_origin;
+#[cfg(feature = "server")]
+pub use docs::{DocFormat, DocPage, generate_docs, write_docs};
+#[cfg(feature = "server")]
+pub use drain::{DrainSignal, drain_guard};
+#[cfg(feature = "server")]
+pub use envelope::ErrorEnvelope;
+#[cfg(feature = "server")]
+pub use health::{HealthError, HealthReport, HealthStatus};
+#[cfg(feature = "server")]
+pub use idempotency::{
+    IDEMPOTENCY_KEY_HEADER, IDEMPOTENT_REPLAYED_HEADER, IdempotencyStore, Reservation,
+    StoredResponse, check_idempotency, idempotency_key,
+};
+#[cfg(feature = "server")]
+pub use job::{JobError, RetryDirective};
+#[cfg(feature = "server")]
+pub use kind::ErrorKind;
+#[cfg(feature = "server")]
+pub use limits::BodyLimits;
+#[cfg(feature = "server")]
+pub use locale::{Localized, Localizer, accepted_languages};
+#[cfg(feature = "server")]
+pub use log_throttle::LogThrottle;
+#[cfg(feature = "derive")]
+pub use mapping::IntoAppError;
+#[cfg(feature = "server")]
+pub use negotiate::negotiate;
+#[cfg(feature = "server")]
+pub use outbound::{
+    ConnectFailure, forward_upstream_problem, outbound_error, upstream_response_error,
+};
+#[cfg(feature = "reqwest-middleware")]
+pub use outbound_middleware::{OutboundMiddleware, UpstreamLatency};
+#[cfg(feature = "server")]
+pub use parse::{INVALID_ENCODING, INVALID_FORMAT, invalid_format_with_value, parse_field};
+#[cfg(feature = "server")]
+pub use password::{BreachCheck, PasswordPolicy, validate_password};
+#[cfg(feature = "server")]
+pub use problem_type::{DEFAULT_TYPE_BASE, ProblemType};
+#[cfg(feature = "server")]
+pub use profile::{PROFILE_V1, ProfileSerializer, accepted_profiles};
+#[cfg(feature = "server")]
+pub use query::{QUERY_SYNTAX_ERROR, QuerySyntaxError, query_syntax_error};
+#[cfg(feature = "server")]
+pub use report::Report;
+#[cfg(feature = "server")]
+pub use scrub::Scrubber;
+#[cfg(feature = "server")]
+pub use severity::Severity;
+#[cfg(feature = "server")]
+pub use slo::{SloPolicy, error_budget_remaining, record_request};
+#[cfg(feature = "server")]
+pub use startup::{StartupError, StartupErrors};
+#[cfg(feature = "server")]
+pub use template::precompute_error_templates;
+#[cfg(feature = "tower")]
+pub use tower_layer::{ErrorMappingLayer, ErrorMappingService, map_box_error};
+#[cfg(feature = "server")]
+pub use unique::{NOT_UNIQUE, require_unique, require_unique_all, unique_violation};
+#[cfg(feature = "server")]
+pub use upstream::{MAX_SNIPPET_LEN, UpstreamDetails};
+#[cfg(feature = "server")]
+pub use warnings::{
+    CURRENT_WARNINGS, Warning, collect_warnings, get_warnings, push_warning, warnings_middleware,
 };
+#[cfg(feature = "server")]
+pub use webhook::SignatureFailure;
 
+#[cfg(feature = "legacy")]
 #[allow(deprecated)]
 pub use app_error::ErrorResponse;
 
+#[cfg(feature = "server")]
+pub use app_error::prelude;
+#[cfg(feature = "server")]
 pub use http_errors::*;
 
+#[cfg(feature = "server")]
 pub type Result<T> = std::result::Result<T, AppError>;
@@ -0,0 +1,75 @@
+//! Size limits on rendered problems.
+//!
+//! A database error's Display text or a user-supplied value echoed back as
+//! `received` can run to tens of kilobytes. [`BodyLimits`] caps both when a
+//! problem is built: an oversized `detail` is cut with an ellipsis and the
+//! problem gets a `truncated: true` extension; oversized `received` values
+//! are cut the same way.
+
+use super::app_error::ProblemDetails;
+
+/// Marker appended to truncated text.
+const ELLIPSIS: &str = "…";
+
+/// Maximum sizes, in bytes, of the unbounded parts of a problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLimits {
+    /// Maximum length of `detail` (default 4096).
+    pub max_detail_len: usize,
+    /// Maximum length of each field error's `received` value, measured as
+    /// serialized JSON for non-string values (default 256).
+    pub max_received_len: usize,
+}
+
+impl Default for BodyLimits {
+    fn default() -> Self {
+        Self {
+            max_detail_len: 4096,
+            max_received_len: 256,
+        }
+    }
+}
+
+impl BodyLimits {
+    /// Truncate the detail and `received` values of `problem` to the limits.
+    pub(crate) fn enforce(&self, problem: &mut ProblemDetails) {
+        if truncate(&mut problem.detail, self.max_detail_len) {
+            problem
+                .extensions
+                .insert("truncated".into(), serde_json::Value::Bool(true));
+        }
+
+        for error in &mut problem.errors {
+            let Some(received) = &mut error.received else {
+                continue;
+            };
+            match received {
+                serde_json::Value::String(text) => {
+                    truncate(text, self.max_received_len);
+                }
+                value => {
+                    let mut text = value.to_string();
+                    if truncate(&mut text, self.max_received_len) {
+                        *value = serde_json::Value::String(text);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Cut `text` to at most `max_len` bytes on a character boundary, ending in
+/// an ellipsis. Returns whether it was cut.
+fn truncate(text: &mut String, max_len: usize) -> bool {
+    if text.len() <= max_len {
+        return false;
+    }
+    let budget = max_len.saturating_sub(ELLIPSIS.len());
+    let end = (0..=budget)
+        .rev()
+        .find(|&index| text.is_char_boundary(index))
+        .unwrap_or(0);
+    text.truncate(end);
+    text.push_str(ELLIPSIS);
+    true
+}
@@ -0,0 +1,85 @@
+//! Localized problem titles and details.
+//!
+//! A [`Localizer`] installed in the [`ErrorsConfig`](crate::ErrorsConfig)
+//! translates rendered problems into the client's language, picked from the
+//! request's `Accept-Language`. The response carries `Content-Language`, and
+//! logs always get the canonical English text.
+//!
+//! # Example
+//! ```ignore
+//! set_errors_config(
+//!     ErrorsConfig::new()
+//!         .localizer(Arc::new(|problem, languages| catalog.translate(problem, languages)))
+//!         .keep_canonical_text(),
+//! );
+//! ```
+
+use std::sync::Arc;
+
+use super::app_error::ProblemDetails;
+
+/// Translates a problem into the first supported of the accepted languages
+/// (in preference order), or returns `None` to keep the canonical text.
+pub type Localizer = Arc<dyn Fn(&ProblemDetails, &[String]) -> Option<Localized> + Send + Sync>;
+
+/// A problem's title and detail in one language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Localized {
+    /// Language tag sent as `Content-Language`, e.g. `de-CH`.
+    pub language: String,
+    /// Localized title; `None` keeps the canonical title.
+    pub title: Option<String>,
+    /// Localized detail; `None` keeps the canonical detail.
+    pub detail: Option<String>,
+}
+
+/// Language tags from an `Accept-Language` header, most preferred first.
+///
+/// Ranges with `q=0` and the `*` wildcard are dropped.
+pub fn accepted_languages(accept_language: &str) -> Vec<String> {
+    let mut ranges: Vec<(&str, f32)> = accept_language
+        .split(',')
+        .filter_map(|range| {
+            let mut params = range.split(';');
+            let tag = params.next()?.trim();
+            let quality = params
+                .find_map(|param| {
+                    let (name, value) = param.split_once('=')?;
+                    name.trim()
+                        .eq_ignore_ascii_case("q")
+                        .then(|| value.trim().parse::<f32>().ok())
+                        .flatten()
+                })
+                .unwrap_or(1.0);
+            (!tag.is_empty() && tag != "*" && quality > 0.0).then_some((tag, quality))
+        })
+        .collect();
+
+    // Stable, so equal weights keep their listed order.
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranges.into_iter().map(|(tag, _)| tag.to_string()).collect()
+}
+
+/// Apply a translation to `problem`: replace the title and detail, or with
+/// `keep_canonical` add them as `title_localized` / `detail_localized`.
+pub(crate) fn apply(problem: &mut ProblemDetails, localized: Localized, keep_canonical: bool) {
+    if keep_canonical {
+        if let Some(title) = localized.title {
+            problem
+                .extensions
+                .insert("title_localized".into(), title.into());
+        }
+        if let Some(detail) = localized.detail {
+            problem
+                .extensions
+                .insert("detail_localized".into(), detail.into());
+        }
+    } else {
+        if let Some(title) = localized.title {
+            problem.title = title.into();
+        }
+        if let Some(detail) = localized.detail {
+            problem.detail = detail;
+        }
+    }
+}
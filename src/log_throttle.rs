@@ -0,0 +1,301 @@
+//! Token-bucket rate limiting for error log events.
+//!
+//! When a dependency flaps, the same error can be rendered thousands of times a
+//! second. Each distinct error (by fingerprint) gets its own bucket; once the
+//! bucket is empty further events are counted instead of logged, and the count
+//! is reported with the next event that gets through, or once the error has
+//! not recurred for a whole interval. [`LogThrottle::flush`] reports all
+//! pending counts, e.g. from a periodic task or at shutdown.
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{LazyLock, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Upper bound on tracked fingerprints; idle buckets are evicted first, then
+/// the least recently used one.
+const MAX_TRACKED_FINGERPRINTS: usize = 4096;
+
+static THROTTLE: LazyLock<Mutex<Throttle>> = LazyLock::new(Default::default);
+
+/// Log rate-limiting policy: at most `burst` events per `interval` for each
+/// error fingerprint.
+#[derive(Debug, Clone, Copy)]
+pub struct LogThrottle {
+    burst: u32,
+    interval: Duration,
+}
+
+impl LogThrottle {
+    /// Create a policy allowing `burst` events per `interval` per fingerprint.
+    pub fn new(burst: u32, interval: Duration) -> Self {
+        Self {
+            burst: burst.max(1),
+            interval,
+        }
+    }
+
+    fn refill_per_sec(&self) -> f64 {
+        f64::from(self.burst) / self.interval.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// Log the counts of all events suppressed so far, e.g. from a periodic
+    /// task or at shutdown.
+    pub fn flush() {
+        let pending = Throttle::lock().take_all();
+        for expired in pending {
+            expired.emit();
+        }
+    }
+}
+
+/// Outcome of offering an event to the throttle.
+pub(crate) enum Decision {
+    /// Log the event. `suppressed` events were dropped since the last one logged.
+    Emit { suppressed: u64 },
+    /// Drop the event.
+    Suppress,
+}
+
+#[derive(Default)]
+struct Throttle {
+    buckets: HashMap<u64, Bucket>,
+    /// Counts of swept buckets, not reported yet.
+    expired: Vec<Expired>,
+    last_sweep: Option<Instant>,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+    suppressed: u64,
+    status: u16,
+    code: String,
+}
+
+/// Events suppressed for an error, reported on their own.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Expired {
+    pub(crate) status: u16,
+    pub(crate) code: String,
+    pub(crate) suppressed: u64,
+}
+
+impl Expired {
+    pub(crate) fn emit(&self) {
+        tracing::warn!(
+            "error.type" = %self.code,
+            http.response.status_code = self.status,
+            suppressed = self.suppressed,
+            "Suppressed {} similar errors",
+            self.suppressed
+        );
+    }
+}
+
+/// Compute the fingerprint identifying "the same error" for throttling.
+///
+/// The detail is left out: it often carries ids, which would give every
+/// occurrence a bucket of its own.
+pub(crate) fn fingerprint(status: u16, code: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    status.hash(&mut hasher);
+    code.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Offer an event with the given fingerprint to the throttle; `status` and
+/// `code` describe the error when its suppressed count is reported on its
+/// own.
+pub(crate) fn check(policy: &LogThrottle, fingerprint: u64, status: u16, code: &str) -> Decision {
+    Throttle::lock().check(policy, fingerprint, status, code, Instant::now())
+}
+
+/// Take the counts of errors that have not recurred for a whole interval.
+pub(crate) fn take_expired(policy: &LogThrottle) -> Vec<Expired> {
+    Throttle::lock().take_expired(policy, Instant::now())
+}
+
+impl Throttle {
+    fn lock() -> MutexGuard<'static, Throttle> {
+        THROTTLE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn check(
+        &mut self,
+        policy: &LogThrottle,
+        fingerprint: u64,
+        status: u16,
+        code: &str,
+        now: Instant,
+    ) -> Decision {
+        if self.buckets.len() >= MAX_TRACKED_FINGERPRINTS
+            && !self.buckets.contains_key(&fingerprint)
+        {
+            self.sweep(now, policy.interval);
+            if self.buckets.len() >= MAX_TRACKED_FINGERPRINTS {
+                self.evict_least_recent();
+            }
+        }
+
+        let bucket = self.buckets.entry(fingerprint).or_insert_with(|| Bucket {
+            tokens: f64::from(policy.burst),
+            last_refill: now,
+            suppressed: 0,
+            status,
+            code: code.to_string(),
+        });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens =
+            (bucket.tokens + elapsed * policy.refill_per_sec()).min(f64::from(policy.burst));
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Decision::Emit {
+                suppressed: std::mem::take(&mut bucket.suppressed),
+            }
+        } else {
+            bucket.suppressed += 1;
+            Decision::Suppress
+        }
+    }
+
+    /// Sweep the buckets at most once per interval and take the counts of
+    /// the swept ones.
+    fn take_expired(&mut self, policy: &LogThrottle, now: Instant) -> Vec<Expired> {
+        let due = self
+            .last_sweep
+            .is_none_or(|last| now.duration_since(last) >= policy.interval);
+        if due {
+            self.sweep(now, policy.interval);
+        }
+        std::mem::take(&mut self.expired)
+    }
+
+    /// Take every pending count, resetting them.
+    fn take_all(&mut self) -> Vec<Expired> {
+        let mut pending = std::mem::take(&mut self.expired);
+        for bucket in self.buckets.values_mut() {
+            if bucket.suppressed > 0 {
+                pending.push(Expired {
+                    status: bucket.status,
+                    code: bucket.code.clone(),
+                    suppressed: std::mem::take(&mut bucket.suppressed),
+                });
+            }
+        }
+        pending
+    }
+
+    /// Drop the bucket used least recently, keeping its count.
+    fn evict_least_recent(&mut self) {
+        let Some(&fingerprint) = self
+            .buckets
+            .iter()
+            .min_by_key(|(_, bucket)| bucket.last_refill)
+            .map(|(fingerprint, _)| fingerprint)
+        else {
+            return;
+        };
+        if let Some(bucket) = self.buckets.remove(&fingerprint)
+            && bucket.suppressed > 0
+        {
+            self.expired.push(Expired {
+                status: bucket.status,
+                code: bucket.code,
+                suppressed: bucket.suppressed,
+            });
+        }
+    }
+
+    /// Drop the buckets idle for a whole interval, keeping their counts.
+    fn sweep(&mut self, now: Instant, interval: Duration) {
+        let expired = &mut self.expired;
+        self.buckets.retain(|_, bucket| {
+            let idle = now.duration_since(bucket.last_refill) >= interval;
+            if idle && bucket.suppressed > 0 {
+                expired.push(Expired {
+                    status: bucket.status,
+                    code: std::mem::take(&mut bucket.code),
+                    suppressed: bucket.suppressed,
+                });
+            }
+            !idle
+        });
+        self.last_sweep = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FINGERPRINT: u64 = 7;
+
+    fn offer(throttle: &mut Throttle, policy: &LogThrottle, times: usize, now: Instant) {
+        for _ in 0..times {
+            throttle.check(policy, FINGERPRINT, 503, "EXTERNAL_SERVICE_ERROR", now);
+        }
+    }
+
+    fn expired(suppressed: u64) -> Vec<Expired> {
+        vec![Expired {
+            status: 503,
+            code: "EXTERNAL_SERVICE_ERROR".to_string(),
+            suppressed,
+        }]
+    }
+
+    #[test]
+    fn counts_of_errors_that_stopped_are_reported() {
+        let policy = LogThrottle::new(1, Duration::from_secs(60));
+        let mut throttle = Throttle::default();
+        let start = Instant::now();
+        offer(&mut throttle, &policy, 4, start);
+
+        assert_eq!(throttle.take_expired(&policy, start), []);
+        let later = start + Duration::from_secs(30);
+        assert_eq!(throttle.take_expired(&policy, later), []);
+        let idle = start + Duration::from_secs(61);
+        assert_eq!(throttle.take_expired(&policy, idle), expired(3));
+        assert!(throttle.buckets.is_empty());
+    }
+
+    #[test]
+    fn flush_reports_active_errors_once() {
+        let policy = LogThrottle::new(1, Duration::from_secs(60));
+        let mut throttle = Throttle::default();
+        offer(&mut throttle, &policy, 3, Instant::now());
+
+        assert_eq!(throttle.take_all(), expired(2));
+        assert_eq!(throttle.take_all(), []);
+    }
+
+    #[test]
+    fn full_map_evicts_the_least_recent_bucket() {
+        let policy = LogThrottle::new(1, Duration::from_secs(60));
+        let mut throttle = Throttle::default();
+        let start = Instant::now();
+        offer(&mut throttle, &policy, 2, start);
+        for fingerprint in 1..MAX_TRACKED_FINGERPRINTS as u64 {
+            let now = start + Duration::from_millis(fingerprint);
+            throttle.check(
+                &policy,
+                FINGERPRINT + fingerprint,
+                500,
+                "INTERNAL_ERROR",
+                now,
+            );
+        }
+
+        let now = start + Duration::from_secs(1);
+        throttle.check(&policy, 0, 500, "INTERNAL_ERROR", now);
+        assert_eq!(throttle.buckets.len(), MAX_TRACKED_FINGERPRINTS);
+        assert!(!throttle.buckets.contains_key(&FINGERPRINT));
+        assert_eq!(throttle.take_expired(&policy, now), expired(1));
+    }
+}
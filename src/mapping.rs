@@ -0,0 +1,119 @@
+//! `From` impls for service error enums.
+//!
+//! Services usually keep their own thiserror enum and convert it to
+//! `AppError` with one large match. [`IntoAppError`] generates that
+//! conversion from an `#[app_error(...)]` attribute on every variant, next to
+//! the thiserror attributes.
+//!
+//! # Example
+//! ```ignore
+//! use eywa_errors::IntoAppError;
+//!
+//! #[derive(Debug, thiserror::Error, IntoAppError)]
+//! pub enum UserError {
+//!     #[app_error(not_found = "user")]
+//!     #[error("user {0} not found")]
+//!     NotFound(Uuid),
+//!
+//!     #[app_error(conflict)]
+//!     #[error("email {email} is already registered")]
+//!     EmailTaken { email: String },
+//!
+//!     #[app_error(from)]
+//!     #[error(transparent)]
+//!     Database(#[from] sea_orm::DbErr),
+//! }
+//!
+//! async fn get_user(id: Uuid) -> Result<Json<User>, AppError> {
+//!     Ok(Json(users::find(id).await?))
+//! }
+//! ```
+
+/// Derive `From<Enum> for AppError` from an `#[app_error(...)]` attribute on
+/// every variant, which selects the helper the variant maps to:
+///
+/// | attribute              | result                                       |
+/// |------------------------|----------------------------------------------|
+/// | `bad_request`          | that helper, called with the variant's       |
+/// | `conflict`             | `Display` text                               |
+/// | `forbidden`            |                                              |
+/// | `service_unavailable`  |                                              |
+/// | `gateway_timeout`      |                                              |
+/// | `internal_error`       |                                              |
+/// | `unauthorized`         | [`unauthorized()`](crate::unauthorized)      |
+/// | `not_found = "user"`   | [`not_found`](crate::not_found) with the     |
+/// |                        | variant's single field as the id             |
+/// | `from`                 | `AppError::from` on the variant's single     |
+/// |                        | field                                        |
+///
+/// Use `from` for variants wrapping errors that already convert (a
+/// `sea_orm::DbErr`, another mapped enum) so their classification is kept.
+pub use eywa_errors_derive::IntoAppError;
+
+#[cfg(test)]
+mod tests {
+    use std::num::ParseIntError;
+
+    use super::IntoAppError;
+    use crate::AppError;
+
+    #[derive(Debug, thiserror::Error, IntoAppError)]
+    enum UserError {
+        #[app_error(not_found = "user")]
+        #[error("user {0} not found")]
+        NotFound(u64),
+
+        #[app_error(conflict)]
+        #[error("email {email} is already registered")]
+        EmailTaken { email: String },
+
+        #[app_error(forbidden)]
+        #[error("delete users")]
+        Delete,
+
+        #[app_error(unauthorized)]
+        #[error("session expired")]
+        SessionExpired,
+
+        #[app_error(from)]
+        #[error(transparent)]
+        Id(#[from] ParseIntError),
+    }
+
+    fn convert(error: UserError) -> (u16, String, String) {
+        let error = AppError::from(error);
+        (
+            error.status().as_u16(),
+            error.code().to_string(),
+            error.to_string(),
+        )
+    }
+
+    #[test]
+    fn variants_map_to_their_helpers() {
+        let (status, code, _) = convert(UserError::NotFound(7));
+        assert_eq!((status, code.as_str()), (404, "NOT_FOUND"));
+        let AppError::NotFound { resource, id } = AppError::from(UserError::NotFound(7)) else {
+            panic!("expected a not found error");
+        };
+        assert_eq!((resource.as_str(), id.as_str()), ("user", "7"));
+
+        let email = "ada@example.com".to_string();
+        let (status, code, detail) = convert(UserError::EmailTaken { email });
+        assert_eq!((status, code.as_str()), (409, "CONFLICT"));
+        assert!(detail.contains("ada@example.com is already registered"));
+
+        assert_eq!(convert(UserError::Delete).0, 403);
+        assert_eq!(convert(UserError::SessionExpired).0, 401);
+    }
+
+    #[test]
+    fn from_keeps_the_wrapped_classification() {
+        let parse_error = "x".parse::<u64>().unwrap_err();
+        let expected = AppError::from(parse_error.clone());
+
+        let error = AppError::from(UserError::from(parse_error));
+        assert_eq!(error.status(), expected.status());
+        assert_eq!(error.code(), expected.code());
+    }
+}
@@ -0,0 +1,107 @@
+//! Validators for monetary amounts and currency codes.
+//!
+//! Each validator returns the [`FieldError`] to report, so results can be
+//! collected into [`ValidationErrors`](crate::ValidationErrors) alongside
+//! other checks.
+//!
+//! # Example
+//! ```ignore
+//! let mut errors = ValidationErrors::new();
+//! for check in [
+//!     require_currency_code("currency", &input.currency),
+//!     require_positive_amount("amount", input.amount),
+//!     require_amount_scale("amount", input.amount, &input.currency),
+//! ] {
+//!     if let Err(error) = check {
+//!         errors.errors.push(error);
+//!     }
+//! }
+//! errors.into_result()?;
+//! ```
+
+use rust_decimal::Decimal;
+
+use super::app_error::FieldError;
+
+/// Field error code for amounts that are zero or negative.
+pub const MUST_BE_POSITIVE: &str = "must_be_positive";
+
+/// Field error code for malformed currency codes.
+pub const INVALID_CURRENCY: &str = "invalid_currency";
+
+/// Field error code for amounts with more decimals than allowed.
+pub const TOO_MANY_DECIMAL_PLACES: &str = "too_many_decimal_places";
+
+/// ISO 4217 currencies without minor units.
+const ZERO_DECIMAL_CURRENCIES: &[&str] = &[
+    "BIF", "CLP", "DJF", "GNF", "ISK", "JPY", "KMF", "KRW", "PYG", "RWF", "UGX", "UYI", "VND",
+    "VUV", "XAF", "XOF", "XPF",
+];
+
+/// ISO 4217 currencies with three decimal places.
+const THREE_DECIMAL_CURRENCIES: &[&str] = &["BHD", "IQD", "JOD", "KWD", "LYD", "OMR", "TND"];
+
+/// Require an amount greater than zero.
+pub fn require_positive_amount(field: &str, amount: Decimal) -> Result<(), FieldError> {
+    if amount > Decimal::ZERO {
+        Ok(())
+    } else {
+        Err(FieldError::with_received(
+            field,
+            MUST_BE_POSITIVE,
+            "Must be greater than zero",
+            amount.to_string(),
+        ))
+    }
+}
+
+/// Require an ISO 4217 alphabetic code: three uppercase ASCII letters.
+pub fn require_currency_code(field: &str, code: &str) -> Result<(), FieldError> {
+    if code.len() == 3 && code.bytes().all(|byte| byte.is_ascii_uppercase()) {
+        Ok(())
+    } else {
+        Err(FieldError::with_received(
+            field,
+            INVALID_CURRENCY,
+            "Must be a three-letter ISO 4217 currency code",
+            code,
+        ))
+    }
+}
+
+/// Require at most `max_scale` decimal places.
+pub fn require_scale(field: &str, amount: Decimal, max_scale: u32) -> Result<(), FieldError> {
+    if amount.normalize().scale() <= max_scale {
+        Ok(())
+    } else {
+        Err(FieldError::with_received(
+            field,
+            TOO_MANY_DECIMAL_PLACES,
+            format!("Must have at most {max_scale} decimal places"),
+            amount.to_string(),
+        )
+        .with_param("max_scale", max_scale))
+    }
+}
+
+/// Require no more decimal places than `currency` has minor units.
+pub fn require_amount_scale(
+    field: &str,
+    amount: Decimal,
+    currency: &str,
+) -> Result<(), FieldError> {
+    require_scale(field, amount, minor_units(currency))
+}
+
+/// Number of decimal places (minor units) of an ISO 4217 currency.
+///
+/// Defaults to 2 for currencies not known to differ.
+pub fn minor_units(currency: &str) -> u32 {
+    if ZERO_DECIMAL_CURRENCIES.contains(&currency) {
+        0
+    } else if THREE_DECIMAL_CURRENCIES.contains(&currency) {
+        3
+    } else {
+        2
+    }
+}
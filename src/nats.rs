@@ -0,0 +1,125 @@
+//! NATS integration: client error conversion and problem reply headers.
+//!
+//! # Example
+//! ```ignore
+//! use eywa_errors::nats::problem_headers;
+//!
+//! while let Some(request) = subscriber.next().await {
+//!     let Some(reply) = request.reply else { continue };
+//!     match handle(&request.payload).await {
+//!         Ok(body) => client.publish(reply, body).await?,
+//!         Err(error) => {
+//!             let problem = error.into_public_problem_details();
+//!             let body = serde_json::to_vec(&problem)?;
+//!             client
+//!                 .publish_with_headers(reply, problem_headers(&problem), body.into())
+//!                 .await?
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::str::FromStr;
+
+use async_nats::{
+    HeaderMap, HeaderValue, PublishError, RequestError, RequestErrorKind, ServerError,
+    client::PublishErrorKind,
+};
+
+use super::app_error::{AppError, ProblemDetails};
+use super::http_errors::{
+    external_service_error, forbidden, gateway_timeout, service_unavailable,
+    validation_error_with_code,
+};
+
+/// Header carrying the error description, per the NATS services convention.
+pub const SERVICE_ERROR_HEADER: &str = "Nats-Service-Error";
+
+/// Header carrying the numeric error code, per the NATS services convention.
+pub const SERVICE_ERROR_CODE_HEADER: &str = "Nats-Service-Error-Code";
+
+/// Headers for a request-reply error response.
+///
+/// Sets the NATS service error headers (description and HTTP status) so
+/// generic NATS tooling recognizes the reply as an error, plus the problem
+/// code, type, and request ID for callers that want to classify it without
+/// parsing the body.
+///
+/// Pass a problem from [`AppError::into_public_problem_details`], so the
+/// description is scrubbed like a response body. Line breaks in values are
+/// replaced by spaces so they cannot start another header.
+pub fn problem_headers(problem: &ProblemDetails) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(SERVICE_ERROR_HEADER, header_value(&problem.detail));
+    headers.insert(SERVICE_ERROR_CODE_HEADER, problem.status.to_string());
+    headers.insert("Content-Type", "application/problem+json");
+    headers.insert("x-error-code", header_value(&problem.code));
+    headers.insert("x-error-type", header_value(&problem.error_type));
+    headers.insert("x-request-id", header_value(&problem.request_id));
+    headers
+}
+
+/// A header value for `value`, with CR and LF replaced by spaces.
+fn header_value(value: &str) -> HeaderValue {
+    HeaderValue::from_str(value).unwrap_or_else(|_| {
+        let sanitized = value.replace(['\r', '\n'], " ");
+        HeaderValue::from_str(&sanitized).unwrap_or_default()
+    })
+}
+
+impl From<RequestError> for AppError {
+    fn from(error: RequestError) -> Self {
+        match error.kind() {
+            RequestErrorKind::NoResponders => service_unavailable("No NATS responders"),
+            RequestErrorKind::TimedOut => gateway_timeout("NATS request timed out"),
+            RequestErrorKind::Other => external_service_error("nats"),
+        }
+    }
+}
+
+impl From<PublishError> for AppError {
+    fn from(error: PublishError) -> Self {
+        match error.kind() {
+            PublishErrorKind::MaxPayloadExceeded => validation_error_with_code(
+                "message",
+                "message_too_large",
+                "Message exceeds the server's maximum payload",
+            ),
+            PublishErrorKind::BadSubject => AppError::InternalServerError(error.to_string()),
+            PublishErrorKind::Send => service_unavailable("NATS connection unavailable"),
+        }
+    }
+}
+
+impl From<ServerError> for AppError {
+    fn from(error: ServerError) -> Self {
+        match error {
+            ServerError::AuthorizationViolation => forbidden("nats"),
+            _ => external_service_error("nats"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_breaks_cannot_inject_headers() {
+        let mut problem =
+            AppError::BadRequest("bad\r\nx-injected: 1".into()).into_problem_details();
+        problem.code = "CODE\nx-other: 2".into();
+
+        let headers = problem_headers(&problem);
+
+        assert_eq!(
+            headers.get(SERVICE_ERROR_HEADER).unwrap().as_str(),
+            "Bad Request: bad  x-injected: 1"
+        );
+        assert_eq!(
+            headers.get("x-error-code").unwrap().as_str(),
+            "CODE x-other: 2"
+        );
+        assert!(headers.get("x-injected").is_none());
+    }
+}
@@ -0,0 +1,97 @@
+//! Response content negotiation.
+//!
+//! [`negotiate`] picks the representation to send from the media types a
+//! handler can produce and the request's `Accept` header, failing with
+//! `NotAcceptable` (406), which lists the supported types, when none is
+//! acceptable.
+//!
+//! # Example
+//! ```ignore
+//! async fn export(headers: HeaderMap) -> Result<Response, AppError> {
+//!     match negotiate(&headers, &["application/json", "text/csv"])? {
+//!         "text/csv" => Ok(csv_response()),
+//!         _ => Ok(json_response()),
+//!     }
+//! }
+//! ```
+
+use axum::http::{HeaderMap, header};
+
+use super::app_error::AppError;
+use super::http_errors::not_acceptable;
+
+/// The supported media type the client prefers.
+///
+/// Without an `Accept` header the first supported type is chosen. Among
+/// types the client weights equally, the earlier supported type wins.
+pub fn negotiate<'a>(headers: &HeaderMap, supported: &[&'a str]) -> Result<&'a str, AppError> {
+    let ranges: Vec<(String, f32)> = headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(media_range)
+        .collect();
+
+    if ranges.is_empty() {
+        return supported
+            .first()
+            .copied()
+            .ok_or_else(|| not_acceptable(supported));
+    }
+
+    supported
+        .iter()
+        .filter_map(|&media_type| {
+            let quality = quality_of(&ranges, media_type);
+            (quality > 0.0).then_some((media_type, quality))
+        })
+        .fold(None, |best: Option<(&str, f32)>, candidate| match best {
+            Some(best) if best.1 >= candidate.1 => Some(best),
+            _ => Some(candidate),
+        })
+        .map(|(media_type, _)| media_type)
+        .ok_or_else(|| not_acceptable(supported))
+}
+
+/// A media range and its weight, e.g. `text/*;q=0.5`.
+fn media_range(range: &str) -> Option<(String, f32)> {
+    let mut params = range.split(';');
+    let media_range = params.next()?.trim().to_ascii_lowercase();
+    if media_range.is_empty() {
+        return None;
+    }
+    let quality = params
+        .find_map(|param| {
+            let (name, value) = param.split_once('=')?;
+            name.trim()
+                .eq_ignore_ascii_case("q")
+                .then(|| value.trim().parse::<f32>().ok())
+                .flatten()
+        })
+        .unwrap_or(1.0);
+    Some((media_range, quality))
+}
+
+/// Weight of `media_type` under the most specific matching range.
+fn quality_of(ranges: &[(String, f32)], media_type: &str) -> f32 {
+    let media_type = media_type.to_ascii_lowercase();
+    let main_type = media_type.split('/').next().unwrap_or_default();
+
+    ranges
+        .iter()
+        .filter_map(|(range, quality)| {
+            let specificity = if *range == media_type {
+                2
+            } else if range.strip_suffix("/*") == Some(main_type) {
+                1
+            } else if range == "*/*" {
+                0
+            } else {
+                return None;
+            };
+            Some((specificity, *quality))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map_or(0.0, |(_, quality)| quality)
+}
@@ -0,0 +1,412 @@
+//! Classification of connection-level failures on outbound calls.
+//!
+//! "External service error" alone is useless during incident triage. HTTP
+//! clients (hyper, reqwest, rustls) bury the actual cause a few levels down
+//! the source chain; [`ConnectFailure::classify`] walks the chain and picks
+//! out DNS, TLS, refused, and connect-timeout failures. It goes by the types
+//! in the chain: `io::Error` kinds, hyper-util's and reqwest's connect errors
+//! (with the `hyper` and `reqwest-middleware` features), and rustls errors
+//! (with the `rustls` feature). Only resolver failures, which have no type
+//! of their own, fall back to the message of the innermost cause.
+//!
+//! [`upstream_response_error`] does the same for calls that did get a
+//! response, but not a successful one.
+//!
+//! With the `reqwest-middleware` feature, [`OutboundMiddleware`] applies them
+//! to every call of a reqwest client; elsewhere call them where the client's
+//! result is checked.
+//!
+//! [`OutboundMiddleware`]: crate::OutboundMiddleware
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+use http::{HeaderMap, StatusCode, header};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::app_error::{AppError, CURRENT_REQUEST_ID, ProblemDetails};
+use super::envelope::ErrorEnvelope;
+use super::http_errors::upstream_error;
+use super::render::PROBLEM_JSON;
+use super::upstream::{UpstreamDetails, UpstreamFailure};
+
+/// Why a connection to an upstream service could not be established.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectFailure {
+    /// The host name did not resolve.
+    Dns,
+    /// The TLS handshake failed (certificate, protocol, or cipher mismatch).
+    TlsHandshake,
+    /// The host actively refused the connection.
+    ConnectionRefused,
+    /// No connection was established before the connect timeout.
+    ConnectTimeout,
+}
+
+impl ConnectFailure {
+    /// Machine-readable reason, rendered as the problem's `reason` member.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ConnectFailure::Dns => "dns_failure",
+            ConnectFailure::TlsHandshake => "tls_handshake_failed",
+            ConnectFailure::ConnectionRefused => "connection_refused",
+            ConnectFailure::ConnectTimeout => "connect_timeout",
+        }
+    }
+
+    /// Classify an outbound call error by walking its source chain.
+    ///
+    /// Returns `None` when the connection was established and the failure
+    /// happened later (or the cause cannot be told apart). A timeout only
+    /// counts as a connect timeout below a known connect error (hyper-util's
+    /// or reqwest's `is_connect`), so read timeouts are not misreported.
+    pub fn classify(error: &(dyn StdError + 'static)) -> Option<Self> {
+        let mut connecting = false;
+        let mut innermost = error;
+
+        for cause in causes(error) {
+            connecting |= is_connect_error(cause);
+            if let Some(failure) = Self::classify_typed(cause, connecting) {
+                return Some(failure);
+            }
+            innermost = cause;
+        }
+
+        Self::classify_message(&innermost.to_string())
+    }
+
+    fn classify_typed(error: &(dyn StdError + 'static), connecting: bool) -> Option<Self> {
+        #[cfg(feature = "rustls")]
+        if error.downcast_ref::<rustls::Error>().is_some() {
+            return Some(ConnectFailure::TlsHandshake);
+        }
+        #[cfg(feature = "reqwest-middleware")]
+        if let Some(error) = error.downcast_ref::<reqwest::Error>()
+            && error.is_connect()
+            && error.is_timeout()
+        {
+            return Some(ConnectFailure::ConnectTimeout);
+        }
+
+        match error.downcast_ref::<io::Error>()?.kind() {
+            io::ErrorKind::ConnectionRefused => Some(ConnectFailure::ConnectionRefused),
+            io::ErrorKind::TimedOut if connecting => Some(ConnectFailure::ConnectTimeout),
+            _ => None,
+        }
+    }
+
+    /// Last resort for causes without a type to go by, such as resolver
+    /// errors: the message of the innermost cause. Outer errors quote the
+    /// request URL, whose host or path may contain any of the phrases below,
+    /// so they are never matched, and URLs are removed from the message.
+    fn classify_message(message: &str) -> Option<Self> {
+        let message = message
+            .split_whitespace()
+            .filter(|word| !word.contains("://"))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_ascii_lowercase();
+        let mentions = |phrases: &[&str]| phrases.iter().any(|phrase| message.contains(phrase));
+
+        if mentions(&[
+            "failed to lookup address",
+            "name or service not known",
+            "nodename nor servname provided",
+            "no such host",
+        ]) {
+            Some(ConnectFailure::Dns)
+        } else if mentions(&[
+            "invalid peer certificate",
+            "certificate verify failed",
+            "handshake failure",
+        ]) {
+            Some(ConnectFailure::TlsHandshake)
+        } else if mentions(&["connection refused"]) {
+            Some(ConnectFailure::ConnectionRefused)
+        } else {
+            None
+        }
+    }
+}
+
+/// The error and its causes, outermost first.
+fn causes<'a>(
+    error: &'a (dyn StdError + 'static),
+) -> impl Iterator<Item = &'a (dyn StdError + 'static)> {
+    std::iter::successors(Some(error), |&cause| {
+        match cause.downcast_ref::<io::Error>() {
+            // `io::Error::source` skips the wrapped error itself.
+            Some(io_error) => io_error.get_ref().map(|inner| inner as _),
+            None => cause.source(),
+        }
+    })
+}
+
+/// Whether `error` is known to be a failure to establish a connection.
+fn is_connect_error(error: &(dyn StdError + 'static)) -> bool {
+    #[cfg(feature = "hyper")]
+    if let Some(error) = error.downcast_ref::<hyper_util::client::legacy::Error>() {
+        return error.is_connect();
+    }
+    #[cfg(feature = "reqwest-middleware")]
+    if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+        return error.is_connect();
+    }
+    // Only the client features above can tell.
+    let _ = error;
+    false
+}
+
+impl fmt::Display for ConnectFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConnectFailure::Dns => "DNS resolution failed",
+            ConnectFailure::TlsHandshake => "TLS handshake failed",
+            ConnectFailure::ConnectionRefused => "connection refused",
+            ConnectFailure::ConnectTimeout => "connect timeout",
+        })
+    }
+}
+
+/// Create an external service error from an outbound call error,
+/// classifying connection-level failures.
+///
+/// # Example
+/// ```ignore
+/// let response = client
+///     .get(url)
+///     .send()
+///     .await
+///     .map_err(|error| outbound_error("billing", &error))?;
+/// ```
+pub fn outbound_error(service: &str, error: &(dyn StdError + 'static)) -> AppError {
+    upstream_error(service, failure_details(error))
+}
+
+/// Upstream details of an outbound call that got no response.
+pub(crate) fn failure_details(error: &(dyn StdError + 'static)) -> UpstreamDetails {
+    let upstream = UpstreamDetails::new();
+    match ConnectFailure::classify(error) {
+        Some(failure) => upstream.failure(failure),
+        None => upstream,
+    }
+}
+
+/// Create an error from a non-success response of an upstream service.
+///
+/// The result is an external service error (a 502 to our client) whatever
+/// the upstream answered: an upstream 401 or 404 is our dependency failing,
+/// not our client's request. The upstream status, latency, and body snippet
+/// are logged, along with the upstream's problem `code` if it sent
+/// `problem+json`. 5xx, 408, and 429 responses are marked transient.
+///
+/// To return the upstream's problem to our client as is, use
+/// [`forward_upstream_problem`].
+///
+/// # Example
+/// ```ignore
+/// let started = Instant::now();
+/// let response = client.get(url).send().await.map_err(|e| outbound_error("billing", &e))?;
+/// if !response.status().is_success() {
+///     let (status, headers) = (response.status(), response.headers().clone());
+///     let body = response.bytes().await.unwrap_or_default();
+///     return Err(upstream_response_error("billing", status, &headers, &body, started.elapsed()));
+/// }
+/// ```
+pub fn upstream_response_error(
+    service: &str,
+    status: StatusCode,
+    headers: &HeaderMap,
+    body: &[u8],
+    latency: Duration,
+) -> AppError {
+    let mut upstream = upstream_details(status, latency);
+    if let Some(problem) = upstream_problem(headers, body) {
+        upstream = upstream.code(problem.code.into_owned());
+    }
+    upstream_error(
+        service,
+        upstream.snippet(String::from_utf8_lossy(body).into_owned()),
+    )
+}
+
+/// Like [`upstream_response_error`], but a `problem+json` body is returned
+/// to our client verbatim, with the upstream's status, code, and detail (an
+/// `AppError::Remote`).
+///
+/// Only for proxies and gateways whose clients address the upstream
+/// directly; elsewhere upstream statuses such as 401 mislead our client.
+pub fn forward_upstream_problem(
+    service: &str,
+    status: StatusCode,
+    headers: &HeaderMap,
+    body: &[u8],
+    latency: Duration,
+) -> AppError {
+    let Some(problem) = upstream_problem(headers, body) else {
+        return upstream_response_error(service, status, headers, body, latency);
+    };
+
+    // The forwarded problem carries no upstream details, so log them here.
+    let request_id = CURRENT_REQUEST_ID.try_with(|id| id.to_string()).ok();
+    UpstreamFailure {
+        service: service.to_string(),
+        details: upstream_details(status, latency).code(problem.code.to_string()),
+    }
+    .emit(request_id.as_deref());
+    ErrorEnvelope::from(problem).into()
+}
+
+fn upstream_details(status: StatusCode, latency: Duration) -> UpstreamDetails {
+    let transient = status.is_server_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS;
+    UpstreamDetails::new()
+        .status(status.as_u16())
+        .latency(latency)
+        .transient(transient)
+}
+
+/// The problem in a `problem+json` response body, if that is what it is.
+fn upstream_problem(headers: &HeaderMap, body: &[u8]) -> Option<ProblemDetails> {
+    let is_problem = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case(PROBLEM_JSON));
+    if !is_problem {
+        return None;
+    }
+    serde_json::from_slice(body).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderValue;
+
+    use super::*;
+
+    /// An error with a message and an optional cause, like a client's.
+    #[derive(Debug)]
+    struct Wrapped(&'static str, Option<io::Error>);
+
+    impl fmt::Display for Wrapped {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.0)
+        }
+    }
+
+    impl StdError for Wrapped {
+        fn source(&self) -> Option<&(dyn StdError + 'static)> {
+            self.1.as_ref().map(|error| error as _)
+        }
+    }
+
+    #[test]
+    fn urls_are_not_matched() {
+        let error = Wrapped(
+            "error sending request for url (https://tls-proxy.internal/certificate)",
+            Some(io::Error::other("connection reset by peer")),
+        );
+        assert_eq!(ConnectFailure::classify(&error), None);
+
+        let error = Wrapped(
+            "invalid peer certificate for https://no-such-host.internal/",
+            None,
+        );
+        assert_eq!(
+            ConnectFailure::classify(&error),
+            Some(ConnectFailure::TlsHandshake)
+        );
+    }
+
+    #[test]
+    fn io_error_kinds_are_classified() {
+        let error = Wrapped(
+            "error sending request",
+            Some(io::ErrorKind::ConnectionRefused.into()),
+        );
+        assert_eq!(
+            ConnectFailure::classify(&error),
+            Some(ConnectFailure::ConnectionRefused)
+        );
+
+        let error = io::Error::new(io::ErrorKind::TimedOut, "read timed out");
+        assert_eq!(ConnectFailure::classify(&error), None);
+    }
+
+    #[cfg(feature = "rustls")]
+    #[test]
+    fn rustls_errors_are_tls_failures() {
+        let error = io::Error::new(
+            io::ErrorKind::InvalidData,
+            rustls::Error::InvalidCertificate(rustls::CertificateError::Expired),
+        );
+        assert_eq!(
+            ConnectFailure::classify(&error),
+            Some(ConnectFailure::TlsHandshake)
+        );
+    }
+
+    #[test]
+    fn resolver_messages_are_classified() {
+        let error = Wrapped(
+            "dns error",
+            Some(io::Error::other(
+                "failed to lookup address information: Name or service not known",
+            )),
+        );
+        assert_eq!(ConnectFailure::classify(&error), Some(ConnectFailure::Dns));
+    }
+
+    fn problem_response() -> (HeaderMap, &'static [u8]) {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/problem+json; charset=utf-8"),
+        );
+        (
+            headers,
+            br#"{"status":401,"code":"UNAUTHORIZED","detail":"token expired"}"#,
+        )
+    }
+
+    #[test]
+    fn upstream_problem_is_our_dependency_failure() {
+        let (headers, body) = problem_response();
+        let error = upstream_response_error(
+            "billing",
+            StatusCode::UNAUTHORIZED,
+            &headers,
+            body,
+            Duration::from_millis(12),
+        );
+
+        assert_eq!(error.status(), StatusCode::BAD_GATEWAY);
+        let AppError::ExternalServiceError { upstream, .. } = error.base() else {
+            panic!("expected an external service error, got {error:?}");
+        };
+        assert_eq!(upstream.status, Some(401));
+        assert_eq!(upstream.code.as_deref(), Some("UNAUTHORIZED"));
+        assert_eq!(upstream.transient, Some(false));
+    }
+
+    #[test]
+    fn forwarding_keeps_the_upstream_problem() {
+        let (headers, body) = problem_response();
+        let error = forward_upstream_problem(
+            "billing",
+            StatusCode::UNAUTHORIZED,
+            &headers,
+            body,
+            Duration::from_millis(12),
+        );
+
+        assert_eq!(error.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(error.code(), "UNAUTHORIZED");
+    }
+}
@@ -0,0 +1,254 @@
+//! reqwest-middleware integration for outbound calls.
+//!
+//! [`OutboundMiddleware`] standardizes service-to-service calls: it sends
+//! the current request and correlation IDs along, times each call, and turns
+//! failed calls into [`AppError`]s, classifying connection failures with
+//! [`ConnectFailure`](crate::ConnectFailure) and non-success responses with
+//! [`upstream_response_error`] (or [`forward_upstream_problem`]). The error
+//! reaches the caller as a `reqwest_middleware::Error`, which converts back
+//! into the `AppError` with `?`.
+//!
+//! # Example
+//! ```ignore
+//! use eywa_errors::{OutboundMiddleware, outbound_error};
+//! use reqwest_middleware::ClientBuilder;
+//!
+//! let billing = ClientBuilder::new(reqwest::Client::new())
+//!     .with(OutboundMiddleware::new("billing"))
+//!     .build();
+//!
+//! async fn invoice(id: &str) -> eywa_errors::Result<Invoice> {
+//!     let response = billing.get(format!("{BILLING_URL}/invoices/{id}")).send().await?;
+//!     Ok(response.json().await.map_err(|e| outbound_error("billing", &e))?)
+//! }
+//! ```
+
+use std::time::{Duration, Instant};
+
+use http::Extensions;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next};
+
+use super::app_error::AppError;
+use super::context::propagate_request_headers;
+use super::http_errors::{internal_error, upstream_error};
+use super::outbound::{failure_details, forward_upstream_problem, upstream_response_error};
+
+/// Latency of an outbound call, stored in the extensions of the responses
+/// that [`OutboundMiddleware`] lets through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpstreamLatency(pub Duration);
+
+/// Middleware raising an [`AppError`] for every failed call to `service`.
+#[derive(Debug, Clone)]
+pub struct OutboundMiddleware {
+    service: String,
+    forward_problems: bool,
+}
+
+impl OutboundMiddleware {
+    /// Create the middleware for calls to `service`, named in logs and
+    /// errors.
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            forward_problems: false,
+        }
+    }
+
+    /// Return `problem+json` responses of the service to our client as is;
+    /// see [`forward_upstream_problem`].
+    pub fn forward_problems(mut self) -> Self {
+        self.forward_problems = true;
+        self
+    }
+
+    async fn failure(&self, response: Response, latency: Duration) -> AppError {
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await.unwrap_or_default();
+
+        if self.forward_problems {
+            forward_upstream_problem(&self.service, status, &headers, &body, latency)
+        } else {
+            upstream_response_error(&self.service, status, &headers, &body, latency)
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for OutboundMiddleware {
+    async fn handle(
+        &self,
+        mut request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        propagate_request_headers(request.headers_mut());
+
+        let started = Instant::now();
+        let result = next.run(request, extensions).await;
+        let latency = started.elapsed();
+
+        let mut response = match result {
+            Ok(response) => response,
+            Err(reqwest_middleware::Error::Reqwest(error)) => {
+                let upstream = failure_details(&error).latency(latency);
+                let error = upstream_error(&self.service, upstream);
+                return Err(reqwest_middleware::Error::middleware(error));
+            }
+            Err(error) => return Err(error),
+        };
+        if !response.status().is_success() {
+            let error = self.failure(response, latency).await;
+            return Err(reqwest_middleware::Error::middleware(error));
+        }
+
+        tracing::debug!(
+            target: "eywa_errors::upstream",
+            upstream_status = response.status().as_u16(),
+            peer.service = %self.service,
+            latency_ms = latency.as_millis() as u64,
+            "Upstream call succeeded"
+        );
+        response.extensions_mut().insert(UpstreamLatency(latency));
+        Ok(response)
+    }
+}
+
+impl From<reqwest_middleware::Error> for AppError {
+    fn from(error: reqwest_middleware::Error) -> Self {
+        match error {
+            reqwest_middleware::Error::Middleware(error) => match error.downcast::<AppError>() {
+                Ok(error) => error,
+                Err(error) => internal_error(format!("Outbound middleware failed: {error}")),
+            },
+            // Calls made without `OutboundMiddleware`, or failing below it.
+            reqwest_middleware::Error::Reqwest(error) => {
+                let service = error
+                    .url()
+                    .and_then(|url| url.host_str())
+                    .unwrap_or("upstream")
+                    .to_string();
+                upstream_error(&service, failure_details(&error))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+    use uuid::Uuid;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::{CURRENT_REQUEST_ID, ConnectFailure};
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn client(middleware: OutboundMiddleware) -> ClientWithMiddleware {
+        ClientBuilder::new(reqwest::Client::new())
+            .with(middleware)
+            .build()
+    }
+
+    fn problem(status: u16) -> ResponseTemplate {
+        ResponseTemplate::new(status).set_body_raw(
+            format!(r#"{{"status":{status},"code":"NOT_FOUND","detail":"no invoice 7"}}"#),
+            "application/problem+json",
+        )
+    }
+
+    #[test]
+    fn request_id_is_sent_and_latency_recorded() {
+        let request_id = Uuid::new_v4();
+        block_on(CURRENT_REQUEST_ID.scope(request_id, async move {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/invoices/7"))
+                .and(header("x-request-id", request_id.to_string()))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let response = client(OutboundMiddleware::new("billing"))
+                .get(format!("{}/invoices/7", server.uri()))
+                .send()
+                .await
+                .unwrap();
+            assert!(response.extensions().get::<UpstreamLatency>().is_some());
+        }));
+    }
+
+    #[test]
+    fn failed_responses_become_upstream_errors() {
+        block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .respond_with(problem(404))
+                .mount(&server)
+                .await;
+            let url = format!("{}/invoices/7", server.uri());
+
+            let error = client(OutboundMiddleware::new("billing"))
+                .get(&url)
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(AppError::from)
+                .unwrap_err();
+            assert_eq!(error.status().as_u16(), 502);
+            let AppError::ExternalServiceError { service, upstream } = error.base() else {
+                panic!("expected an external service error, got {error:?}");
+            };
+            assert_eq!(service, "billing");
+            assert_eq!(upstream.status, Some(404));
+            assert_eq!(upstream.code.as_deref(), Some("NOT_FOUND"));
+            assert!(upstream.latency.is_some());
+
+            let error = client(OutboundMiddleware::new("billing").forward_problems())
+                .get(&url)
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(AppError::from)
+                .unwrap_err();
+            assert_eq!(error.status().as_u16(), 404);
+            assert_eq!(error.code(), "NOT_FOUND");
+        });
+    }
+
+    #[test]
+    fn refused_connections_are_classified() {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let error = block_on(async {
+            client(OutboundMiddleware::new("billing"))
+                .get(format!("http://127.0.0.1:{port}/"))
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(AppError::from)
+                .unwrap_err()
+        });
+        let AppError::ExternalServiceError { upstream, .. } = error.base() else {
+            panic!("expected an external service error, got {error:?}");
+        };
+        assert_eq!(upstream.failure, Some(ConnectFailure::ConnectionRefused));
+    }
+}
@@ -0,0 +1,131 @@
+//! Conversions from common parse errors, so path and query parsing code can
+//! use `?`.
+//!
+//! Parse errors don't know which field they came from or what the input was,
+//! so the `From` conversions leave the field empty and name the expected
+//! format (`uuid`, `integer`, ...) in the `format` param. Use [`parse_field`]
+//! to name the field and capture the offending value.
+//!
+//! Decode errors for binary encodings (features `base64` and `hex`), common
+//! for cursors, signatures, and binary IDs, use the `invalid_encoding` code.
+
+use std::fmt::Display;
+use std::num::{ParseFloatError, ParseIntError};
+use std::str::{FromStr, Utf8Error};
+
+use super::app_error::{AppError, FieldError};
+use super::http_errors::validation_error_with_value;
+
+/// Field error code for values that could not be parsed.
+pub const INVALID_FORMAT: &str = "invalid_format";
+
+/// Field error code for values that could not be decoded.
+pub const INVALID_ENCODING: &str = "invalid_encoding";
+
+/// Parse `raw` into `T`, reporting failure as an `invalid_format` error on
+/// `field` with the received value.
+///
+/// # Example
+/// ```ignore
+/// let limit: u32 = parse_field("limit", &query.limit)?;
+/// ```
+pub fn parse_field<T>(field: &str, raw: &str) -> Result<T, AppError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    raw.parse()
+        .map_err(|error: T::Err| invalid_format_with_value(field, error, raw))
+}
+
+/// An `invalid_format` error on `field` carrying the received value.
+pub fn invalid_format_with_value(field: &str, error: impl Display, raw: &str) -> AppError {
+    validation_error_with_value(field, INVALID_FORMAT, error.to_string(), raw)
+}
+
+/// A field error for a value of unknown origin: no field, the expected
+/// format as a param.
+fn unattributed(code: &'static str, format: &str, error: impl Display) -> AppError {
+    FieldError::new("", code, error.to_string())
+        .with_param("format", format)
+        .into()
+}
+
+fn invalid_format(format: &str, error: impl Display) -> AppError {
+    unattributed(INVALID_FORMAT, format, error)
+}
+
+impl From<uuid::Error> for AppError {
+    fn from(error: uuid::Error) -> Self {
+        invalid_format("uuid", error)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::ParseError> for AppError {
+    fn from(error: chrono::ParseError) -> Self {
+        invalid_format("datetime", error)
+    }
+}
+
+impl From<ParseIntError> for AppError {
+    fn from(error: ParseIntError) -> Self {
+        invalid_format("integer", error)
+    }
+}
+
+impl From<ParseFloatError> for AppError {
+    fn from(error: ParseFloatError) -> Self {
+        invalid_format("number", error)
+    }
+}
+
+impl From<Utf8Error> for AppError {
+    fn from(error: Utf8Error) -> Self {
+        invalid_format("utf8", error)
+    }
+}
+
+#[cfg(feature = "base64")]
+impl From<base64::DecodeError> for AppError {
+    fn from(error: base64::DecodeError) -> Self {
+        unattributed(INVALID_ENCODING, "base64", error)
+    }
+}
+
+#[cfg(feature = "hex")]
+impl From<hex::FromHexError> for AppError {
+    fn from(error: hex::FromHexError) -> Self {
+        unattributed(INVALID_ENCODING, "hex", error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions_name_the_format_not_the_field() {
+        let error = AppError::from("12a".parse::<u32>().unwrap_err());
+        let AppError::Validation(errors) = &error else {
+            panic!("expected a validation error, got {error:?}");
+        };
+        assert_eq!(errors[0].field, "");
+        assert_eq!(errors[0].code, INVALID_FORMAT);
+        assert_eq!(errors[0].params.as_ref().unwrap()["format"], "integer");
+        assert_eq!(
+            error.to_string(),
+            "Validation error: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn parse_field_names_the_field_and_value() {
+        let error = parse_field::<u32>("limit", "12a").unwrap_err();
+        let AppError::Validation(errors) = &error else {
+            panic!("expected a validation error, got {error:?}");
+        };
+        assert_eq!(errors[0].field, "limit");
+        assert_eq!(errors[0].received, Some(serde_json::json!("12a")));
+    }
+}
@@ -0,0 +1,198 @@
+//! Password policy validation shared by auth services.
+//!
+//! [`validate_password`] reports every violated rule at once, so sign-up
+//! forms can show the full list instead of one rule per round trip.
+//!
+//! # Example
+//! ```ignore
+//! let policy = PasswordPolicy::new()
+//!     .min_length(12)
+//!     .require_uppercase()
+//!     .require_digit()
+//!     .breach_check(Arc::new(|password| breach_index.contains(password)));
+//!
+//! validate_password("password", &input.password, &policy)?;
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use super::app_error::{FieldError, ValidationErrors};
+
+/// Lookup in a breached-password corpus (e.g. a k-anonymity range query or
+/// a local bloom filter); returns `true` if the password is known to be
+/// breached.
+pub type BreachCheck = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Rules a password must satisfy.
+#[derive(Clone)]
+pub struct PasswordPolicy {
+    min_length: usize,
+    max_length: usize,
+    uppercase: bool,
+    lowercase: bool,
+    digit: bool,
+    symbol: bool,
+    breach_check: Option<BreachCheck>,
+}
+
+impl PasswordPolicy {
+    /// A policy requiring 12 to 128 characters and nothing else.
+    pub fn new() -> Self {
+        Self {
+            min_length: 12,
+            max_length: 128,
+            uppercase: false,
+            lowercase: false,
+            digit: false,
+            symbol: false,
+            breach_check: None,
+        }
+    }
+
+    /// Minimum length in characters.
+    pub fn min_length(mut self, min_length: usize) -> Self {
+        self.min_length = min_length;
+        self
+    }
+
+    /// Maximum length in characters, bounding the cost of hashing.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Require at least one uppercase letter.
+    pub fn require_uppercase(mut self) -> Self {
+        self.uppercase = true;
+        self
+    }
+
+    /// Require at least one lowercase letter.
+    pub fn require_lowercase(mut self) -> Self {
+        self.lowercase = true;
+        self
+    }
+
+    /// Require at least one digit.
+    pub fn require_digit(mut self) -> Self {
+        self.digit = true;
+        self
+    }
+
+    /// Require at least one character that is neither a letter nor a digit.
+    pub fn require_symbol(mut self) -> Self {
+        self.symbol = true;
+        self
+    }
+
+    /// Reject passwords found in a breach corpus.
+    pub fn breach_check(mut self, check: BreachCheck) -> Self {
+        self.breach_check = Some(check);
+        self
+    }
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for PasswordPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PasswordPolicy")
+            .field("min_length", &self.min_length)
+            .field("max_length", &self.max_length)
+            .field("uppercase", &self.uppercase)
+            .field("lowercase", &self.lowercase)
+            .field("digit", &self.digit)
+            .field("symbol", &self.symbol)
+            .field("breach_check", &self.breach_check.is_some())
+            .finish()
+    }
+}
+
+/// Check `value` against `policy`, reporting every violated rule on `field`.
+///
+/// Codes: `too_short`, `too_long` (with `min_length` / `max_length` params),
+/// `missing_uppercase`, `missing_lowercase`, `missing_digit`,
+/// `missing_symbol`, and `found_in_breach`. The password itself is never
+/// included in the errors.
+pub fn validate_password(
+    field: &str,
+    value: &str,
+    policy: &PasswordPolicy,
+) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+    let length = value.chars().count();
+
+    if length < policy.min_length {
+        errors.errors.push(
+            FieldError::new(
+                field,
+                "too_short",
+                format!("Must be at least {} characters", policy.min_length),
+            )
+            .with_param("min_length", policy.min_length),
+        );
+    }
+    if length > policy.max_length {
+        errors.errors.push(
+            FieldError::new(
+                field,
+                "too_long",
+                format!("Must be at most {} characters", policy.max_length),
+            )
+            .with_param("max_length", policy.max_length),
+        );
+    }
+
+    let classes = [
+        (
+            policy.uppercase,
+            char::is_uppercase as fn(char) -> bool,
+            "missing_uppercase",
+            "Must contain an uppercase letter",
+        ),
+        (
+            policy.lowercase,
+            char::is_lowercase,
+            "missing_lowercase",
+            "Must contain a lowercase letter",
+        ),
+        (
+            policy.digit,
+            |c: char| c.is_ascii_digit(),
+            "missing_digit",
+            "Must contain a digit",
+        ),
+        (
+            policy.symbol,
+            |c: char| !c.is_alphanumeric(),
+            "missing_symbol",
+            "Must contain a symbol",
+        ),
+    ];
+    for (required, predicate, code, message) in classes {
+        if required && !value.chars().any(predicate) {
+            errors.add(field, code, message);
+        }
+    }
+
+    if let Some(check) = &policy.breach_check
+        && check(value)
+    {
+        errors.add(
+            field,
+            "found_in_breach",
+            "This password appeared in a data breach; choose another",
+        );
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
@@ -0,0 +1,131 @@
+//! Mapping of payment provider failures to actionable statuses.
+//!
+//! Checkout frontends need to tell "try another card" from "try again
+//! later" from "our bug". [`PaymentFailure`] is the provider-neutral
+//! classification; [`PaymentFailure::from_stripe`] reads Stripe's error
+//! `type`, `code`, and `decline_code` fields.
+//!
+//! | Failure                   | Status | Code                     |
+//! |---------------------------|--------|--------------------------|
+//! | `CardDeclined`            | 402    | `PAYMENT_DECLINED`       |
+//! | `RateLimited`             | 429    | `RATE_LIMITED`           |
+//! | `IdempotencyConflict`     | 409    | `CONFLICT`               |
+//! | `Authentication`          | 500    | `CONFIG_ERROR`           |
+//! | `InvalidRequest`          | 502    | `EXTERNAL_SERVICE_ERROR` |
+//! | `Connection`, `Provider`  | 502    | `EXTERNAL_SERVICE_ERROR` |
+//!
+//! A request the provider rejects as malformed is our bug, not the
+//! client's: the provider's message is logged with the upstream details but
+//! not returned.
+//!
+//! # Example
+//! ```ignore
+//! let failure = PaymentFailure::from_stripe(
+//!     &error.type_,
+//!     error.code.as_deref(),
+//!     error.decline_code.as_deref(),
+//!     &error.message,
+//! );
+//! return Err(failure.into_app_error("stripe"));
+//! ```
+
+use super::app_error::AppError;
+use super::config_check::ConfigErrorKind;
+use super::http_errors::{
+    config_error, conflict, external_service_error, payment_declined, rate_limited, upstream_error,
+};
+use super::upstream::UpstreamDetails;
+
+/// What went wrong talking to a payment provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaymentFailure {
+    /// The card (or other method) was declined.
+    CardDeclined { decline_code: Option<String> },
+    /// The provider rate limited us.
+    RateLimited,
+    /// An idempotency key was reused with different parameters.
+    IdempotencyConflict,
+    /// The provider rejected the request as malformed.
+    InvalidRequest(String),
+    /// Our API key was rejected.
+    Authentication,
+    /// The provider could not be reached.
+    Connection,
+    /// The provider failed on its side.
+    Provider,
+}
+
+impl PaymentFailure {
+    /// Classify a Stripe API error from its `type`, `code`, and
+    /// `decline_code` fields.
+    pub fn from_stripe(
+        error_type: &str,
+        code: Option<&str>,
+        decline_code: Option<&str>,
+        message: &str,
+    ) -> Self {
+        match (error_type, code) {
+            ("card_error", _) | (_, Some("card_declined")) => PaymentFailure::CardDeclined {
+                decline_code: decline_code.or(code).map(str::to_string),
+            },
+            (_, Some("rate_limit")) | ("rate_limit_error", _) => PaymentFailure::RateLimited,
+            ("idempotency_error", _) => PaymentFailure::IdempotencyConflict,
+            ("authentication_error", _) => PaymentFailure::Authentication,
+            ("invalid_request_error", _) => PaymentFailure::InvalidRequest(message.to_string()),
+            ("api_connection_error", _) => PaymentFailure::Connection,
+            _ => PaymentFailure::Provider,
+        }
+    }
+
+    /// Convert to an `AppError`; `provider` names the service in logs.
+    pub fn into_app_error(self, provider: &str) -> AppError {
+        match self {
+            PaymentFailure::CardDeclined { decline_code } => {
+                payment_declined(decline_code.as_deref())
+            }
+            PaymentFailure::RateLimited => rate_limited(None),
+            PaymentFailure::IdempotencyConflict => conflict(format!(
+                "{provider} idempotency key reused with different parameters"
+            )),
+            PaymentFailure::InvalidRequest(message) => upstream_error(
+                provider,
+                UpstreamDetails::new()
+                    .status(400)
+                    .code("invalid_request_error")
+                    .snippet(message)
+                    .transient(false),
+            ),
+            PaymentFailure::Authentication => {
+                config_error(&format!("{provider}.api_key"), ConfigErrorKind::Invalid)
+            }
+            PaymentFailure::Connection | PaymentFailure::Provider => {
+                external_service_error(provider)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_request_is_a_bad_gateway_without_the_message() {
+        let message = "No such customer: 'cus_123'";
+        let failure = PaymentFailure::from_stripe(
+            "invalid_request_error",
+            Some("resource_missing"),
+            None,
+            message,
+        );
+        let error = failure.into_app_error("stripe");
+
+        assert_eq!(error.status().as_u16(), 502);
+        assert!(!error.to_string().contains("cus_123"));
+        let AppError::ExternalServiceError { upstream, .. } = error.base() else {
+            panic!("expected an external service error, got {error:?}");
+        };
+        assert_eq!(upstream.snippet.as_deref(), Some(message));
+        assert_eq!(upstream.transient, Some(false));
+    }
+}
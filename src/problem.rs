@@ -0,0 +1,273 @@
+//! RFC 7807 problem bodies.
+//!
+//! The serde types of a problem, independent of the server integrations so
+//! clients (see the `client` feature) can parse problems without them.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use utoipa::ToSchema;
+
+use super::timestamp::Timestamp;
+
+/// RFC 7807 Problem Details response format.
+///
+/// This provides a standardized way to carry machine-readable details of errors
+/// in HTTP responses. See: https://tools.ietf.org/html/rfc7807
+///
+/// # Example Response
+/// ```json
+/// {
+///   "type": "https://api.example.com/errors/validation-error",
+///   "title": "Validation Error",
+///   "status": 400,
+///   "code": "VALIDATION_ERROR",
+///   "detail": "The 'email' field must be a valid email address",
+///   "instance": "/users/abc123",
+///   "request_id": "550e8400-e29b-41d4-a716-446655440000",
+///   "timestamp": "2026-01-06T14:17:00Z",
+///   "errors": [
+///     { "field": "email", "code": "invalid_format", "message": "Must be a valid email" }
+///   ]
+/// }
+/// ```
+///
+/// # Deserializing
+///
+/// Problems from peers running other versions of this crate (or from other
+/// stacks) are accepted as long as they carry a `status`: unknown members
+/// land in `extensions`, missing members default (`type` to `about:blank`,
+/// strings to empty), and a `timestamp` in an unrecognized shape is dropped.
+///
+/// ```
+/// use eywa_errors::ProblemDetails;
+///
+/// let problem: ProblemDetails = serde_json::from_str(r#"{"status": 503, "retry_hint": "soon"}"#)?;
+/// assert_eq!(problem.error_type, "about:blank");
+/// assert_eq!(problem.extensions["retry_hint"], "soon");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ProblemDetails {
+    /// URI reference that identifies the problem type.
+    /// When dereferenced, should provide human-readable documentation.
+    #[serde(rename = "type", default = "about_blank")]
+    #[schema(required = true)]
+    pub error_type: Cow<'static, str>,
+
+    /// Short, human-readable summary of the problem type.
+    #[serde(default)]
+    #[schema(required = true)]
+    pub title: Cow<'static, str>,
+
+    /// HTTP status code.
+    pub status: u16,
+
+    /// Machine-readable error code (e.g. `NOT_FOUND`).
+    #[serde(default)]
+    #[schema(required = true)]
+    pub code: Cow<'static, str>,
+
+    /// Human-readable explanation specific to this occurrence of the problem.
+    #[serde(default)]
+    #[schema(required = true)]
+    pub detail: String,
+
+    /// URI reference that identifies the specific occurrence of the problem.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+
+    /// Unique request identifier for tracing.
+    #[serde(default)]
+    #[schema(required = true)]
+    pub request_id: String,
+
+    /// Timestamp of when the error occurred (RFC 3339 by default).
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "lenient_timestamp"
+    )]
+    pub timestamp: Option<Timestamp>,
+
+    /// Field-level validation errors (if applicable).
+    #[serde(skip_serializing_if = "SmallVec::is_empty", default)]
+    #[schema(value_type = Vec<FieldError>)]
+    pub errors: FieldErrors,
+
+    /// Additional problem-specific members (RFC 7807 extension members).
+    #[serde(flatten, default)]
+    #[schema(value_type = Object, additional_properties)]
+    pub extensions: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ProblemDetails {
+    /// Compare two problems, ignoring the per-occurrence `request_id` and
+    /// `timestamp` members.
+    pub fn matches(&self, other: &ProblemDetails) -> bool {
+        self.error_type == other.error_type
+            && self.title == other.title
+            && self.status == other.status
+            && self.code == other.code
+            && self.detail == other.detail
+            && self.instance == other.instance
+            && self.errors == other.errors
+            && self.extensions == other.extensions
+    }
+}
+
+/// Default problem type of RFC 7807: no further semantics than the status.
+fn about_blank() -> Cow<'static, str> {
+    Cow::Borrowed("about:blank")
+}
+
+/// A timestamp in a shape this version does not know is dropped rather than
+/// failing the whole problem.
+fn lenient_timestamp<'de, D>(deserializer: D) -> Result<Option<Timestamp>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).ok())
+}
+
+/// Field-level error for validation failures.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FieldError {
+    /// The field that caused the error.
+    pub field: String,
+
+    /// Machine-readable error code.
+    pub code: Cow<'static, str>,
+
+    /// Human-readable error message.
+    #[serde(default)]
+    #[schema(required = true)]
+    pub message: String,
+
+    /// The value that was received (for debugging).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub received: Option<serde_json::Value>,
+
+    /// Code-specific parameters (limits, positions, hints) for clients that
+    /// build their own messages. Boxed to keep inline field errors small.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[schema(value_type = Option<Object>, additional_properties)]
+    pub params: Option<Box<serde_json::Map<String, serde_json::Value>>>,
+}
+
+impl FieldError {
+    /// Create a new field error.
+    pub fn new(
+        field: impl Into<String>,
+        code: impl Into<Cow<'static, str>>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            field: field.into(),
+            code: code.into(),
+            message: message.into(),
+            received: None,
+            params: None,
+        }
+    }
+
+    /// Create a new field error with the received value.
+    pub fn with_received(
+        field: impl Into<String>,
+        code: impl Into<Cow<'static, str>>,
+        message: impl Into<String>,
+        received: impl Into<serde_json::Value>,
+    ) -> Self {
+        Self {
+            field: field.into(),
+            code: code.into(),
+            message: message.into(),
+            received: Some(received.into()),
+            params: None,
+        }
+    }
+
+    /// Add a parameter.
+    pub fn with_param(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        self.params
+            .get_or_insert_default()
+            .insert(key.into(), value.into());
+        self
+    }
+}
+
+/// Field errors stored inline for the common single-error case.
+pub type FieldErrors = SmallVec<[FieldError; 1]>;
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn parse(body: serde_json::Value) -> ProblemDetails {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn unknown_members_become_extensions() {
+        let problem = parse(json!({
+            "type": "https://errors.eywa.dev/not-found",
+            "title": "Not Found",
+            "status": 404,
+            "code": "NOT_FOUND",
+            "detail": "User 7 not found",
+            "request_id": "r1",
+            "added_in_v2": { "nested": true },
+        }));
+        assert_eq!(problem.code, "NOT_FOUND");
+        assert_eq!(problem.extensions["added_in_v2"], json!({ "nested": true }));
+    }
+
+    #[test]
+    fn missing_members_default() {
+        let problem = parse(json!({ "status": 502, "title": "Bad Gateway" }));
+        assert_eq!(problem.error_type, "about:blank");
+        assert_eq!(problem.status, 502);
+        assert_eq!(problem.code, "");
+        assert_eq!(problem.detail, "");
+        assert_eq!(problem.request_id, "");
+        assert_eq!(problem.timestamp, None);
+        assert!(problem.errors.is_empty());
+
+        let error: FieldError =
+            serde_json::from_value(json!({ "field": "email", "code": "required" })).unwrap();
+        assert_eq!(error.message, "");
+    }
+
+    #[test]
+    fn timestamps_in_known_shapes_are_kept_and_others_dropped() {
+        let problem = parse(json!({ "status": 500, "timestamp": "2026-01-06T14:17:00Z" }));
+        assert_eq!(
+            problem.timestamp,
+            Some(Timestamp::Rfc3339("2026-01-06T14:17:00Z".to_string()))
+        );
+        let problem = parse(json!({ "status": 500, "timestamp": 1767709020000_i64 }));
+        assert_eq!(
+            problem.timestamp,
+            Some(Timestamp::UnixMillis(1767709020000))
+        );
+
+        let problem = parse(json!({ "status": 500, "timestamp": { "seconds": 1767709020 } }));
+        assert_eq!(problem.timestamp, None);
+        let problem = parse(json!({ "status": 500, "timestamp": 1.5 }));
+        assert_eq!(problem.timestamp, None);
+    }
+
+    #[test]
+    fn status_is_required() {
+        let result = serde_json::from_value::<ProblemDetails>(json!({ "code": "NOT_FOUND" }));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,469 @@
+//! Problem types: the `type` URI, code, title, and default status of an error.
+//!
+//! Services sharing a code space namespace their codes
+//! (`billing.invoice_locked`) and reserve the namespace for the owning team
+//! or crate with [`ProblemType::reserve_namespace`].
+//! [`ProblemType::check_registry`] then reports collisions at startup.
+//!
+//! # Example
+//! ```ignore
+//! ProblemType::reserve_namespace("billing", "billing-service");
+//! INVOICE_LOCKED.register();
+//!
+//! let mut errors = StartupErrors::new();
+//! if let Err(conflicts) = ProblemType::check_registry() {
+//!     errors.append(conflicts);
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use axum::http::StatusCode;
+
+use super::app_error::AppError;
+use super::config_check::ConfigErrorKind;
+use super::startup::StartupErrors;
+
+/// Base of the built-in type URIs, replaced by
+/// [`ErrorsConfig::type_base_uri`](crate::ErrorsConfig::type_base_uri).
+pub const DEFAULT_TYPE_BASE: &str = "https://errors.eywa.dev";
+
+/// A problem type, as identified by the `type` member of a problem response.
+///
+/// Every `AppError` variant maps to one of the built-in constants through
+/// [`AppError::problem_type`](crate::AppError::problem_type). Services can
+/// [`register`](ProblemType::register) their own types so they show up in
+/// [`ProblemType::all`] and [`ProblemType::lookup`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProblemType {
+    /// The `type` URI.
+    pub uri: &'static str,
+
+    /// Machine-readable error code.
+    pub code: &'static str,
+
+    /// Short, human-readable summary.
+    pub title: &'static str,
+
+    /// Status used unless the error overrides it.
+    pub default_status: StatusCode,
+
+    /// What a client or operator should do about the error, for the docs.
+    pub remediation: &'static str,
+}
+
+/// Statuses outside the IANA registry, usable as `StatusCode::NAME` in
+/// [`problem_types!`].
+trait NonStandardStatus {
+    /// 499, nginx's status for requests the client abandoned.
+    const CLIENT_CLOSED_REQUEST: StatusCode;
+}
+
+impl NonStandardStatus for StatusCode {
+    const CLIENT_CLOSED_REQUEST: StatusCode = match StatusCode::from_u16(499) {
+        Ok(status) => status,
+        Err(_) => panic!("499 is a valid status code"),
+    };
+}
+
+macro_rules! problem_types {
+    ($($name:ident => ($uri:expr, $title:expr, $status:ident, $remediation:expr);)*) => {
+        impl ProblemType {
+            $(
+                #[doc = concat!("`", stringify!($name), "` (", $title, ").")]
+                pub const $name: ProblemType = ProblemType::new(
+                    $uri,
+                    stringify!($name),
+                    $title,
+                    StatusCode::$status,
+                )
+                .with_remediation($remediation);
+            )*
+
+            /// The built-in problem types.
+            pub const BUILTIN: &'static [ProblemType] = &[$(ProblemType::$name),*];
+        }
+    };
+}
+
+problem_types! {
+    NOT_FOUND => (
+        "https://errors.eywa.dev/not-found", "Not Found", NOT_FOUND,
+        "Check the resource identifier; the resource may have been deleted."
+    );
+    VALIDATION_ERROR => (
+        "https://errors.eywa.dev/validation-error", "Validation Error", BAD_REQUEST,
+        "Fix the fields listed in `errors` and send the request again."
+    );
+    BAD_REQUEST => (
+        "https://errors.eywa.dev/bad-request", "Bad Request", BAD_REQUEST,
+        "The request is malformed. Correct it as described in `detail` before retrying."
+    );
+    INVALID_CURSOR => (
+        "https://errors.eywa.dev/invalid-cursor", "Invalid Cursor", BAD_REQUEST,
+        "Pass cursors back exactly as received; they are opaque."
+    );
+    EXPIRED_CURSOR => (
+        "https://errors.eywa.dev/expired-cursor", "Cursor Expired", GONE,
+        "Restart pagination from the first page."
+    );
+    BODY_TOO_LARGE => (
+        "https://errors.eywa.dev/body-too-large", "Payload Too Large", PAYLOAD_TOO_LARGE,
+        "Send a smaller body; `max_body_size` gives the limit in bytes when known."
+    );
+    BODY_READ_TIMEOUT => (
+        "https://errors.eywa.dev/body-read-timeout", "Request Timeout", REQUEST_TIMEOUT,
+        "The body was sent too slowly. Retry on a new connection."
+    );
+    INVALID_CONTENT_ENCODING => (
+        "https://errors.eywa.dev/invalid-content-encoding", "Invalid Content Encoding", BAD_REQUEST,
+        "Check that the body is compressed with the algorithm named in `Content-Encoding`."
+    );
+    HEADERS_TOO_LARGE => (
+        "https://errors.eywa.dev/headers-too-large", "Request Header Fields Too Large",
+        REQUEST_HEADER_FIELDS_TOO_LARGE,
+        "Send fewer or smaller headers (often oversized cookies); `max_header_size` gives the \
+         limit when known."
+    );
+    URI_TOO_LONG => (
+        "https://errors.eywa.dev/uri-too-long", "URI Too Long", URI_TOO_LONG,
+        "Shorten the URI, e.g. by moving query parameters into a POST body; `max_uri_length` \
+         gives the limit when known."
+    );
+    HTTP_VERSION_NOT_SUPPORTED => (
+        "https://errors.eywa.dev/http-version-not-supported", "HTTP Version Not Supported",
+        HTTP_VERSION_NOT_SUPPORTED,
+        "Retry the request over HTTP/1.1 or HTTP/2."
+    );
+    UPGRADE_REQUIRED => (
+        "https://errors.eywa.dev/upgrade-required", "Upgrade Required", UPGRADE_REQUIRED,
+        "Retry the request over the protocol in the `Upgrade` header."
+    );
+    METHOD_NOT_ALLOWED => (
+        "https://errors.eywa.dev/method-not-allowed", "Method Not Allowed", METHOD_NOT_ALLOWED,
+        "Use one of the methods in the `Allow` header."
+    );
+    NOT_ACCEPTABLE => (
+        "https://errors.eywa.dev/not-acceptable", "Not Acceptable", NOT_ACCEPTABLE,
+        "Request one of the media types listed in `supported` in the `Accept` header."
+    );
+    UNAUTHORIZED => (
+        "https://errors.eywa.dev/unauthorized", "Unauthorized", UNAUTHORIZED,
+        "Authenticate, or refresh an expired token, and retry."
+    );
+    FORBIDDEN => (
+        "https://errors.eywa.dev/forbidden", "Forbidden", FORBIDDEN,
+        "Ask an administrator to grant the permissions listed in `required_permissions`."
+    );
+    INVALID_SIGNATURE => (
+        "https://errors.eywa.dev/invalid-signature", "Invalid Signature", UNAUTHORIZED,
+        "Check the webhook signing secret and that the receiver's clock is in sync; `reason` \
+         names the failing check."
+    );
+    CSRF_FAILURE => (
+        "https://errors.eywa.dev/csrf-failure", "CSRF Verification Failed", FORBIDDEN,
+        "Reload the page to obtain a fresh CSRF token and submit again."
+    );
+    INVALID_ORIGIN => (
+        "https://errors.eywa.dev/invalid-origin", "Invalid Origin", FORBIDDEN,
+        "Send the request from an allowed origin."
+    );
+    CONFLICT => (
+        "https://errors.eywa.dev/conflict", "Conflict", CONFLICT,
+        "Fetch the current state of the resource, reapply your change, and retry."
+    );
+    IDEMPOTENCY_CONFLICT => (
+        "https://errors.eywa.dev/idempotency-conflict", "Idempotency Key Reused",
+        UNPROCESSABLE_ENTITY,
+        "Send a new `Idempotency-Key` for a different request; reuse a key only to retry the \
+         identical request."
+    );
+    IDEMPOTENCY_IN_PROGRESS => (
+        "https://errors.eywa.dev/idempotency-in-progress", "Request In Progress", CONFLICT,
+        "The first request with this `Idempotency-Key` is still being handled. Retry after a \
+         short delay to get its response."
+    );
+    PRECONDITION_FAILED => (
+        "https://errors.eywa.dev/precondition-failed", "Precondition Failed", PRECONDITION_FAILED,
+        "The resource changed since you fetched it. Fetch it again (see `ETag`) and reapply your \
+         change."
+    );
+    NOT_MODIFIED => (
+        "https://errors.eywa.dev/not-modified", "Not Modified", NOT_MODIFIED,
+        "Not an error: the cached representation is current."
+    );
+    PAYMENT_DECLINED => (
+        "https://errors.eywa.dev/payment-declined", "Payment Declined", PAYMENT_REQUIRED,
+        "Use another payment method; `decline_code` gives the provider's reason."
+    );
+    RATE_LIMITED => (
+        "https://errors.eywa.dev/rate-limited", "Too Many Requests", TOO_MANY_REQUESTS,
+        "Slow down and retry after the number of seconds in the `Retry-After` header, if present."
+    );
+    DATABASE_ERROR => (
+        "https://errors.eywa.dev/database-error", "Database Error", INTERNAL_SERVER_ERROR,
+        "Retry later. If the error persists, report it with the `request_id`."
+    );
+    CONFIG_ERROR => (
+        "https://errors.eywa.dev/config-error", "Configuration Error", INTERNAL_SERVER_ERROR,
+        "The service is misconfigured. Report the error with the `request_id`."
+    );
+    EXTERNAL_SERVICE_ERROR => (
+        "https://errors.eywa.dev/external-service-error", "External Service Error", BAD_GATEWAY,
+        "A dependency failed. Retry with backoff."
+    );
+    INTERNAL_ERROR => (
+        "https://errors.eywa.dev/internal-error", "Internal Server Error", INTERNAL_SERVER_ERROR,
+        "Report the error with the `request_id`."
+    );
+    SERVICE_UNAVAILABLE => (
+        "https://errors.eywa.dev/service-unavailable", "Service Unavailable", SERVICE_UNAVAILABLE,
+        "Retry with backoff."
+    );
+    GATEWAY_TIMEOUT => (
+        "https://errors.eywa.dev/gateway-timeout", "Gateway Timeout", GATEWAY_TIMEOUT,
+        "A dependency did not answer in time. Retry with backoff."
+    );
+    DEADLINE_EXCEEDED => (
+        "https://errors.eywa.dev/deadline-exceeded", "Deadline Exceeded", GATEWAY_TIMEOUT,
+        "The request deadline passed. Retry with a longer deadline."
+    );
+    CLIENT_DISCONNECTED => (
+        "https://errors.eywa.dev/client-disconnected", "Client Closed Request",
+        CLIENT_CLOSED_REQUEST,
+        "Not a server failure: the client went away before the response was sent."
+    );
+    OVERLOADED => (
+        "https://errors.eywa.dev/overloaded", "Service Overloaded", SERVICE_UNAVAILABLE,
+        "Retry after the number of seconds in the `Retry-After` header."
+    );
+    DRAINING => (
+        "https://errors.eywa.dev/draining", "Service Draining", SERVICE_UNAVAILABLE,
+        "The instance is shutting down. Retry on a new connection after `Retry-After`."
+    );
+    UNHEALTHY => (
+        "https://errors.eywa.dev/unhealthy", "Service Unhealthy", SERVICE_UNAVAILABLE,
+        "Check the failing entries in `components`."
+    );
+    DEGRADED => (
+        "https://errors.eywa.dev/degraded", "Service Degraded", OK,
+        "The service is serving with reduced capacity; check `components`."
+    );
+    REMOTE_ERROR => (
+        "about:blank", "Upstream Error", BAD_GATEWAY,
+        "An upstream service failed; see its error code and `detail`."
+    );
+}
+
+static REGISTERED: LazyLock<RwLock<Vec<ProblemType>>> = LazyLock::new(|| RwLock::new(Vec::new()));
+
+/// Codes registered again with a different definition, with both definitions.
+static REDEFINED: LazyLock<RwLock<Vec<(ProblemType, ProblemType)>>> =
+    LazyLock::new(Default::default);
+
+/// Reserved namespaces and their owners, in reservation order.
+static NAMESPACES: LazyLock<RwLock<Vec<(&'static str, &'static str)>>> =
+    LazyLock::new(Default::default);
+
+/// Why a code or namespace failed [`ProblemType::check_registry`].
+#[derive(Debug)]
+struct CodeConflict(String);
+
+impl fmt::Display for CodeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CodeConflict {}
+
+fn code_conflict(key: &str, kind: ConfigErrorKind, reason: String) -> AppError {
+    AppError::ConfigError {
+        key: key.to_string(),
+        kind,
+        source: Some(Arc::new(CodeConflict(reason))),
+    }
+}
+
+impl ProblemType {
+    /// Create a problem type.
+    pub const fn new(
+        uri: &'static str,
+        code: &'static str,
+        title: &'static str,
+        default_status: StatusCode,
+    ) -> Self {
+        Self {
+            uri,
+            code,
+            title,
+            default_status,
+            remediation: "",
+        }
+    }
+
+    /// Set the remediation advice shown in the generated docs.
+    pub const fn with_remediation(mut self, remediation: &'static str) -> Self {
+        self.remediation = remediation;
+        self
+    }
+
+    /// Register a service-specific problem type. Registering a code again
+    /// replaces the earlier type; a different definition is reported by
+    /// [`check_registry`](Self::check_registry).
+    pub fn register(self) {
+        let mut registered = REGISTERED
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(existing) = registered
+            .iter()
+            .find(|existing| existing.code == self.code)
+            && *existing != self
+        {
+            REDEFINED
+                .write()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .push((*existing, self));
+        }
+        registered.retain(|existing| existing.code != self.code);
+        registered.push(self);
+    }
+
+    /// The namespace of a namespaced code (`billing` for
+    /// `billing.invoice_locked`), or `None` for a flat code.
+    pub fn namespace(&self) -> Option<&'static str> {
+        self.code.split_once('.').map(|(namespace, _)| namespace)
+    }
+
+    /// Reserve a code namespace for `owner` (a team or crate name). Only
+    /// reserved namespaces may be used by registered types.
+    pub fn reserve_namespace(namespace: &'static str, owner: &'static str) {
+        let mut namespaces = NAMESPACES
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if !namespaces.contains(&(namespace, owner)) {
+            namespaces.push((namespace, owner));
+        }
+    }
+
+    /// Check the registered codes, for startup:
+    ///
+    /// - a registered code must not shadow a built-in code
+    /// - a code must not be registered twice with different definitions
+    /// - a namespace must be reserved by only one owner
+    /// - a namespaced code must use a reserved namespace
+    ///
+    /// Every violation is reported as a configuration error on the code or
+    /// namespace.
+    pub fn check_registry() -> Result<(), StartupErrors> {
+        let mut errors = StartupErrors::new();
+        let registered = REGISTERED
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let redefined = REDEFINED
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let namespaces = NAMESPACES
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        for problem_type in registered.iter() {
+            if Self::BUILTIN
+                .iter()
+                .any(|builtin| builtin.code == problem_type.code)
+            {
+                errors.add(
+                    "error codes",
+                    code_conflict(
+                        problem_type.code,
+                        ConfigErrorKind::Forbidden,
+                        "shadows a built-in code".to_string(),
+                    ),
+                );
+            }
+        }
+        for (first, second) in redefined.iter() {
+            errors.add(
+                "error codes",
+                code_conflict(
+                    first.code,
+                    ConfigErrorKind::Invalid,
+                    format!("registered as both {} and {}", first.uri, second.uri),
+                ),
+            );
+        }
+        for (index, (namespace, owner)) in namespaces.iter().enumerate() {
+            if let Some((_, other)) = namespaces[..index]
+                .iter()
+                .find(|(reserved, _)| reserved == namespace)
+            {
+                errors.add(
+                    "error codes",
+                    code_conflict(
+                        namespace,
+                        ConfigErrorKind::Invalid,
+                        format!("namespace reserved by both {other} and {owner}"),
+                    ),
+                );
+            }
+        }
+        for problem_type in registered.iter() {
+            if let Some(namespace) = problem_type.namespace()
+                && !namespaces
+                    .iter()
+                    .any(|(reserved, _)| *reserved == namespace)
+            {
+                errors.add(
+                    "error codes",
+                    code_conflict(
+                        problem_type.code,
+                        ConfigErrorKind::Forbidden,
+                        format!("namespace {namespace} is not reserved"),
+                    ),
+                );
+            }
+        }
+
+        errors.into_result()
+    }
+
+    /// All built-in and registered problem types.
+    pub fn all() -> Vec<ProblemType> {
+        let registered = REGISTERED
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        Self::BUILTIN
+            .iter()
+            .chain(registered.iter())
+            .copied()
+            .collect()
+    }
+
+    /// The path of the URI below [`DEFAULT_TYPE_BASE`] (e.g. `not-found`), if
+    /// the URI is under the default base.
+    pub fn slug(&self) -> Option<&'static str> {
+        self.uri
+            .strip_prefix(DEFAULT_TYPE_BASE)
+            .and_then(|rest| rest.strip_prefix('/'))
+    }
+
+    /// The `type` URI composed against `base`: `{base}/{slug}`, or just the
+    /// slug (a relative reference) when `base` is empty. URIs outside the
+    /// default base are returned unchanged.
+    pub fn uri_with_base(&self, base: &str) -> Cow<'static, str> {
+        match self.slug() {
+            Some(slug) if base.is_empty() => Cow::Borrowed(slug),
+            Some(slug) => Cow::Owned(format!("{}/{slug}", base.trim_end_matches('/'))),
+            None => Cow::Borrowed(self.uri),
+        }
+    }
+
+    /// Find a built-in or registered problem type by code.
+    pub fn lookup(code: &str) -> Option<ProblemType> {
+        Self::all()
+            .into_iter()
+            .find(|problem_type| problem_type.code == code)
+    }
+}
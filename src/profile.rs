@@ -0,0 +1,55 @@
+//! Versioned problem formats negotiated through the `Accept` profile.
+//!
+//! Clients pick a schema revision with
+//! `Accept: application/problem+json; profile="https://errors.eywa.dev/v2"`.
+//! Each revision is a serializer registered on the config with
+//! [`ErrorsConfig::problem_profile`]; requests without a profile, or only
+//! with unknown ones, get the current format ([`PROFILE_V1`]). This lets the
+//! payload evolve without a flag day.
+//!
+//! # Example
+//! ```ignore
+//! use eywa_errors::{ErrorsConfig, set_errors_config};
+//!
+//! set_errors_config(ErrorsConfig::new().problem_profile(
+//!     "https://errors.eywa.dev/v2",
+//!     |problem| serde_json::json!({
+//!         "type": problem.error_type,
+//!         "status": problem.status,
+//!         "error": { "code": problem.code, "detail": problem.detail },
+//!     }),
+//! ));
+//! ```
+//!
+//! [`ErrorsConfig::problem_profile`]: crate::ErrorsConfig::problem_profile
+
+use std::sync::Arc;
+
+use super::app_error::ProblemDetails;
+
+/// Profile URI of the current `ProblemDetails` format.
+pub const PROFILE_V1: &str = "https://errors.eywa.dev/v1";
+
+/// Serializer producing the body of one profile from a problem.
+pub type ProfileSerializer = Arc<dyn Fn(&ProblemDetails) -> serde_json::Value + Send + Sync>;
+
+/// Profiles requested for `application/problem+json` in an `Accept` header,
+/// in the order listed.
+pub fn accepted_profiles(accept: &str) -> Vec<String> {
+    accept
+        .split(',')
+        .filter_map(|range| {
+            let mut params = range.split(';');
+            let media_type = params.next()?.trim();
+            if !media_type.eq_ignore_ascii_case(super::render::PROBLEM_JSON) {
+                return None;
+            }
+            params.find_map(|param| {
+                let (name, value) = param.split_once('=')?;
+                name.trim()
+                    .eq_ignore_ascii_case("profile")
+                    .then(|| value.trim().trim_matches('"').to_string())
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,246 @@
+//! Protobuf form of problems, per `proto/eywa/errors/v1/problem_details.proto`.
+//!
+//! The message types are generated from the schema by prost-build and
+//! checked in (`src/protobuf/eywa.errors.v1.rs`), so building the crate
+//! needs no `protoc`; a test regenerates them and fails when they drift from
+//! the schema. This module adds the conversions from and to the JSON
+//! [`ProblemDetails`](crate::ProblemDetails), so gRPC status details and
+//! event payloads share one canonical binary form.
+//!
+//! After changing the schema, update the generated code with
+//! `EYWA_UPDATE_PROTO=1 cargo test --features protobuf generated_code`.
+//!
+//! # Example
+//! ```ignore
+//! use eywa_errors::protobuf::{self, Message};
+//!
+//! let bytes = protobuf::ProblemDetails::from(error.into_public_problem_details()).encode_to_vec();
+//! let status = tonic::Status::with_details(code, message, bytes.into());
+//!
+//! // Receiver
+//! let problem = ProblemDetails::from(protobuf::ProblemDetails::decode(status.details())?);
+//! ```
+
+use std::borrow::Cow;
+
+use prost_types::value::Kind;
+use prost_types::{ListValue, Struct};
+use serde_json::{Map, Number, Value};
+
+use super::problem;
+use super::timestamp;
+
+pub use prost::{DecodeError, Message};
+
+include!("protobuf/eywa.errors.v1.rs");
+
+impl From<problem::ProblemDetails> for ProblemDetails {
+    fn from(problem: problem::ProblemDetails) -> Self {
+        Self {
+            r#type: problem.error_type.into_owned(),
+            title: problem.title.into_owned(),
+            status: u32::from(problem.status),
+            code: problem.code.into_owned(),
+            detail: problem.detail,
+            instance: problem.instance,
+            request_id: problem.request_id,
+            timestamp: problem.timestamp.map(|timestamp| match timestamp {
+                timestamp::Timestamp::Rfc3339(text) => problem_details::Timestamp::Rfc3339(text),
+                timestamp::Timestamp::UnixMillis(millis) => {
+                    problem_details::Timestamp::UnixMillis(millis)
+                }
+            }),
+            errors: problem.errors.into_iter().map(FieldError::from).collect(),
+            extensions: (!problem.extensions.is_empty()).then(|| to_struct(problem.extensions)),
+        }
+    }
+}
+
+impl From<ProblemDetails> for problem::ProblemDetails {
+    fn from(problem: ProblemDetails) -> Self {
+        Self {
+            error_type: if problem.r#type.is_empty() {
+                Cow::Borrowed("about:blank")
+            } else {
+                Cow::Owned(problem.r#type)
+            },
+            title: Cow::Owned(problem.title),
+            status: u16::try_from(problem.status).unwrap_or(500),
+            code: Cow::Owned(problem.code),
+            detail: problem.detail,
+            instance: problem.instance,
+            request_id: problem.request_id,
+            timestamp: problem.timestamp.map(|timestamp| match timestamp {
+                problem_details::Timestamp::Rfc3339(text) => timestamp::Timestamp::Rfc3339(text),
+                problem_details::Timestamp::UnixMillis(millis) => {
+                    timestamp::Timestamp::UnixMillis(millis)
+                }
+            }),
+            errors: problem
+                .errors
+                .into_iter()
+                .map(problem::FieldError::from)
+                .collect(),
+            extensions: problem.extensions.map(from_struct).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<problem::FieldError> for FieldError {
+    fn from(error: problem::FieldError) -> Self {
+        Self {
+            field: error.field,
+            code: error.code.into_owned(),
+            message: error.message,
+            received: error.received.map(to_value),
+            params: error.params.map(|params| to_struct(*params)),
+        }
+    }
+}
+
+impl From<FieldError> for problem::FieldError {
+    fn from(error: FieldError) -> Self {
+        Self {
+            field: error.field,
+            code: Cow::Owned(error.code),
+            message: error.message,
+            received: error.received.map(from_value),
+            params: error.params.map(|params| Box::new(from_struct(params))),
+        }
+    }
+}
+
+fn to_struct(map: Map<String, Value>) -> Struct {
+    Struct {
+        fields: map
+            .into_iter()
+            .map(|(key, value)| (key, to_value(value)))
+            .collect(),
+    }
+}
+
+fn from_struct(message: Struct) -> Map<String, Value> {
+    message
+        .fields
+        .into_iter()
+        .map(|(key, value)| (key, from_value(value)))
+        .collect()
+}
+
+fn to_value(value: Value) -> prost_types::Value {
+    let kind = match value {
+        Value::Null => Kind::NullValue(0),
+        Value::Bool(value) => Kind::BoolValue(value),
+        Value::Number(number) => Kind::NumberValue(number.as_f64().unwrap_or_default()),
+        Value::String(text) => Kind::StringValue(text),
+        Value::Array(items) => Kind::ListValue(ListValue {
+            values: items.into_iter().map(to_value).collect(),
+        }),
+        Value::Object(map) => Kind::StructValue(to_struct(map)),
+    };
+    prost_types::Value { kind: Some(kind) }
+}
+
+fn from_value(value: prost_types::Value) -> Value {
+    match value.kind {
+        None | Some(Kind::NullValue(_)) => Value::Null,
+        Some(Kind::BoolValue(value)) => Value::Bool(value),
+        Some(Kind::NumberValue(value)) => number(value),
+        Some(Kind::StringValue(text)) => Value::String(text),
+        Some(Kind::ListValue(list)) => {
+            Value::Array(list.values.into_iter().map(from_value).collect())
+        }
+        Some(Kind::StructValue(message)) => Value::Object(from_struct(message)),
+    }
+}
+
+/// A JSON number for a protobuf double, integral if it has no fraction.
+fn number(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < 9_007_199_254_740_992.0 {
+        Value::Number(Number::from(value as i64))
+    } else {
+        Number::from_f64(value).map_or(Value::Null, Value::Number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn problem() -> problem::ProblemDetails {
+        let mut errors = problem::FieldErrors::new();
+        errors.push(
+            problem::FieldError::with_received("age", "out_of_range", "Too old", json!(212))
+                .with_param("max", 150)
+                .with_param(
+                    "hint",
+                    json!({ "unit": "years", "tags": [1, 2.5, null, true] }),
+                ),
+        );
+        problem::ProblemDetails {
+            error_type: Cow::Borrowed("https://errors.eywa.dev/validation-error"),
+            title: Cow::Borrowed("Validation Error"),
+            status: 400,
+            code: Cow::Borrowed("VALIDATION_ERROR"),
+            detail: "Validation error: age".to_string(),
+            instance: Some("/users/1".to_string()),
+            request_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            timestamp: Some(timestamp::Timestamp::UnixMillis(-1)),
+            errors,
+            extensions: json!({ "retry_after": 30, "reason": "quota" })
+                .as_object()
+                .cloned()
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn round_trips_through_the_wire_format() {
+        let problem = problem();
+        let bytes = ProblemDetails::from(problem.clone()).encode_to_vec();
+        let decoded = problem::ProblemDetails::from(ProblemDetails::decode(&*bytes).unwrap());
+        assert_eq!(decoded, problem);
+    }
+
+    #[test]
+    fn skips_unknown_fields() {
+        let mut bytes = ProblemDetails::from(problem()).encode_to_vec();
+        // Field 99 (length-delimited) and field 98 (varint) of a newer schema.
+        bytes.extend_from_slice(&[0x9a, 0x06, 3, b'n', b'e', b'w', 0x90, 0x06, 7]);
+
+        let decoded = ProblemDetails::decode(&*bytes).unwrap();
+        assert_eq!(decoded, ProblemDetails::from(problem()));
+    }
+
+    #[test]
+    fn generated_code_matches_the_schema() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let checked_in = root.join("src/protobuf/eywa.errors.v1.rs");
+        let out = std::env::temp_dir().join(format!("eywa-protobuf-{}", std::process::id()));
+        std::fs::create_dir_all(&out).unwrap();
+
+        prost_build::Config::new()
+            .protoc_executable(protoc_bin_vendored::protoc_bin_path().unwrap())
+            .out_dir(&out)
+            .compile_protos(
+                &[root.join("proto/eywa/errors/v1/problem_details.proto")],
+                &[root.join("proto")],
+            )
+            .unwrap();
+        let generated = std::fs::read_to_string(out.join("eywa.errors.v1.rs")).unwrap();
+        std::fs::remove_dir_all(&out).unwrap();
+
+        if std::env::var_os("EYWA_UPDATE_PROTO").is_some() {
+            std::fs::write(&checked_in, generated).unwrap();
+        } else {
+            assert!(
+                std::fs::read_to_string(&checked_in).unwrap() == generated,
+                "src/protobuf/eywa.errors.v1.rs is out of date; rerun with EYWA_UPDATE_PROTO=1"
+            );
+        }
+    }
+}
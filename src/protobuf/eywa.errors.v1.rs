@@ -0,0 +1,67 @@
+// This file is @generated by prost-build.
+/// A problem, as returned in an error response.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProblemDetails {
+    /// URI reference that identifies the problem type (`about:blank` if unset).
+    #[prost(string, tag = "1")]
+    pub r#type: ::prost::alloc::string::String,
+    /// Short, human-readable summary of the problem type.
+    #[prost(string, tag = "2")]
+    pub title: ::prost::alloc::string::String,
+    /// HTTP status code.
+    #[prost(uint32, tag = "3")]
+    pub status: u32,
+    /// Machine-readable error code (e.g. `NOT_FOUND`).
+    #[prost(string, tag = "4")]
+    pub code: ::prost::alloc::string::String,
+    /// Human-readable explanation specific to this occurrence of the problem.
+    #[prost(string, tag = "5")]
+    pub detail: ::prost::alloc::string::String,
+    /// URI reference that identifies the specific occurrence of the problem.
+    #[prost(string, optional, tag = "6")]
+    pub instance: ::core::option::Option<::prost::alloc::string::String>,
+    /// Unique request identifier for tracing.
+    #[prost(string, tag = "7")]
+    pub request_id: ::prost::alloc::string::String,
+    /// Field-level validation errors (if applicable).
+    #[prost(message, repeated, tag = "10")]
+    pub errors: ::prost::alloc::vec::Vec<FieldError>,
+    /// Additional problem-specific members (RFC 7807 extension members).
+    #[prost(message, optional, tag = "11")]
+    pub extensions: ::core::option::Option<::prost_types::Struct>,
+    /// When the error occurred, in the format the sender was configured with.
+    #[prost(oneof = "problem_details::Timestamp", tags = "8, 9")]
+    pub timestamp: ::core::option::Option<problem_details::Timestamp>,
+}
+/// Nested message and enum types in `ProblemDetails`.
+pub mod problem_details {
+    /// When the error occurred, in the format the sender was configured with.
+    #[derive(Clone, PartialEq, Eq, Hash, ::prost::Oneof)]
+    pub enum Timestamp {
+        /// RFC 3339 timestamp, e.g. `2026-01-06T14:17:00Z`.
+        #[prost(string, tag = "8")]
+        Rfc3339(::prost::alloc::string::String),
+        /// Milliseconds since the Unix epoch.
+        #[prost(int64, tag = "9")]
+        UnixMillis(i64),
+    }
+}
+/// Field-level error for validation failures.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FieldError {
+    /// The field that caused the error.
+    #[prost(string, tag = "1")]
+    pub field: ::prost::alloc::string::String,
+    /// Machine-readable error code.
+    #[prost(string, tag = "2")]
+    pub code: ::prost::alloc::string::String,
+    /// Human-readable error message.
+    #[prost(string, tag = "3")]
+    pub message: ::prost::alloc::string::String,
+    /// The value that was received (for debugging).
+    #[prost(message, optional, tag = "4")]
+    pub received: ::core::option::Option<::prost_types::Value>,
+    /// Code-specific parameters (limits, positions, hints).
+    #[prost(message, optional, tag = "5")]
+    pub params: ::core::option::Option<::prost_types::Struct>,
+}
@@ -0,0 +1,94 @@
+//! Syntax errors in filter and search query DSLs.
+//!
+//! Reported as a field error with code `query_syntax_error` whose params
+//! locate the problem, so clients can underline the offending character:
+//!
+//! ```json
+//! {
+//!   "field": "filter",
+//!   "code": "query_syntax_error",
+//!   "message": "Unexpected `)` at position 14, expected one of: value, `(`",
+//!   "params": { "position": 14, "fragment": ")", "expected": ["value", "`(`"] }
+//! }
+//! ```
+
+use std::fmt;
+
+use super::app_error::{AppError, FieldError, ValidationErrors};
+
+/// Field error code for query syntax errors.
+pub const QUERY_SYNTAX_ERROR: &str = "query_syntax_error";
+
+/// Where and why a query failed to parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuerySyntaxError {
+    /// Zero-based character (not byte) offset of the offending input.
+    pub position: usize,
+
+    /// The offending input, e.g. the unexpected token. Empty at end of input.
+    pub fragment: String,
+
+    /// What the parser would have accepted at `position`.
+    pub expected: Vec<String>,
+}
+
+impl QuerySyntaxError {
+    /// Create a syntax error at `position` (in characters).
+    pub fn new(position: usize, fragment: impl Into<String>) -> Self {
+        Self {
+            position,
+            fragment: fragment.into(),
+            expected: Vec::new(),
+        }
+    }
+
+    /// Add something the parser would have accepted.
+    pub fn expected(mut self, expected: impl Into<String>) -> Self {
+        self.expected.push(expected.into());
+        self
+    }
+
+    /// Convert to a field error on `field` carrying the position, fragment,
+    /// and expected tokens as params.
+    pub fn into_field_error(self, field: &str) -> FieldError {
+        FieldError::new(field, QUERY_SYNTAX_ERROR, self.to_string())
+            .with_param("position", self.position)
+            .with_param("fragment", self.fragment)
+            .with_param("expected", self.expected)
+    }
+}
+
+impl fmt::Display for QuerySyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.fragment.is_empty() {
+            write!(f, "Unexpected end of query at position {}", self.position)?;
+        } else {
+            write!(
+                f,
+                "Unexpected `{}` at position {}",
+                self.fragment, self.position
+            )?;
+        }
+        if !self.expected.is_empty() {
+            write!(f, ", expected one of: {}", self.expected.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Create a validation error for a query that failed to parse.
+///
+/// # Example
+/// ```ignore
+/// let filter = parse_filter(&params.filter).map_err(|error| {
+///     query_syntax_error(
+///         "filter",
+///         QuerySyntaxError::new(error.offset, error.token).expected("value"),
+///     )
+/// })?;
+/// ```
+pub fn query_syntax_error(field: &str, error: QuerySyntaxError) -> AppError {
+    let mut errors = ValidationErrors::new();
+    errors.errors.push(error.into_field_error(field));
+    AppError::Validation(errors)
+}
@@ -0,0 +1,315 @@
+//! Low-level rendering of problem responses.
+//!
+//! Error bodies are serialized straight into a per-thread `BytesMut` that is
+//! reused across responses, so rendering does not allocate an intermediate
+//! `Vec` and copy it into the response body.
+//!
+//! Besides JSON, problems can be rendered as MessagePack (`msgpack` feature)
+//! and CBOR (`cbor` feature) for binary protocols. Both encode the same
+//! members, keyed by name, as the JSON body.
+
+use std::cell::RefCell;
+use std::fmt;
+
+use axum::body::Body;
+use axum::response::Response;
+use bytes::{BufMut, Bytes, BytesMut};
+use http::{HeaderMap, HeaderValue, StatusCode, header};
+
+use serde::Serialize;
+use serde::ser::{SerializeMap, Serializer};
+
+#[cfg(feature = "legacy")]
+#[allow(deprecated)]
+use super::app_error::ErrorResponse;
+use super::app_error::ProblemDetails;
+use super::config::FieldNaming;
+use super::profile::ProfileSerializer;
+
+/// Media type for RFC 7807 problem responses.
+pub(crate) const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Media type for problems rendered as MessagePack.
+#[cfg(feature = "msgpack")]
+pub(crate) const PROBLEM_MSGPACK: &str = "application/problem+msgpack";
+
+/// Media type for problems rendered as CBOR.
+#[cfg(feature = "cbor")]
+pub(crate) const PROBLEM_CBOR: &str = "application/problem+cbor";
+
+/// Capacity reserved in the pooled buffer before each serialization.
+const RESERVE_BYTES: usize = 512;
+
+/// Body used if a problem cannot be serialized.
+const FALLBACK_BODY: &[u8] =
+    br#"{"type":"about:blank","title":"Internal Server Error","status":500}"#;
+
+thread_local! {
+    static BUFFER: RefCell<BytesMut> = RefCell::new(BytesMut::with_capacity(RESERVE_BYTES * 8));
+}
+
+/// Media type for legacy `ErrorResponse` bodies.
+#[cfg(feature = "legacy")]
+const JSON: &str = "application/json";
+
+/// Serialize a problem into JSON bytes using the pooled buffer.
+pub(crate) fn problem_json(
+    problem: &ProblemDetails,
+    naming: FieldNaming,
+) -> Result<Bytes, serde_json::Error> {
+    match naming {
+        FieldNaming::SnakeCase => json(problem),
+        FieldNaming::CamelCase => json(&CamelCase(problem)),
+    }
+}
+
+/// Serialize a problem into MessagePack bytes using the pooled buffer.
+#[cfg(feature = "msgpack")]
+pub(crate) fn problem_msgpack(
+    problem: &ProblemDetails,
+    naming: FieldNaming,
+) -> Result<Bytes, rmp_serde::encode::Error> {
+    match naming {
+        FieldNaming::SnakeCase => msgpack(problem),
+        FieldNaming::CamelCase => msgpack(&CamelCase(problem)),
+    }
+}
+
+/// Serialize a problem into CBOR bytes using the pooled buffer.
+#[cfg(feature = "cbor")]
+pub(crate) fn problem_cbor(
+    problem: &ProblemDetails,
+    naming: FieldNaming,
+) -> Result<Bytes, ciborium::ser::Error<std::io::Error>> {
+    match naming {
+        FieldNaming::SnakeCase => cbor(problem),
+        FieldNaming::CamelCase => cbor(&CamelCase(problem)),
+    }
+}
+
+/// Serializes a problem with camelCase member names, in the same member order
+/// as the derived `Serialize` implementation. Field error members are single
+/// words and need no renaming.
+struct CamelCase<'a>(&'a ProblemDetails);
+
+impl Serialize for CamelCase<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let problem = self.0;
+        let mut map = serializer.serialize_map(None)?;
+
+        map.serialize_entry("type", &problem.error_type)?;
+        map.serialize_entry("title", &problem.title)?;
+        map.serialize_entry("status", &problem.status)?;
+        map.serialize_entry("code", &problem.code)?;
+        map.serialize_entry("detail", &problem.detail)?;
+        if let Some(instance) = &problem.instance {
+            map.serialize_entry("instance", instance)?;
+        }
+        map.serialize_entry("requestId", &problem.request_id)?;
+        if let Some(timestamp) = &problem.timestamp {
+            map.serialize_entry("timestamp", timestamp)?;
+        }
+        if !problem.errors.is_empty() {
+            map.serialize_entry("errors", &problem.errors)?;
+        }
+        for (key, value) in &problem.extensions {
+            map.serialize_entry(&camel_case(key), value)?;
+        }
+
+        map.end()
+    }
+}
+
+/// `request_id` → `requestId`.
+fn camel_case(key: &str) -> String {
+    let mut result = String::with_capacity(key.len());
+    let mut upper = false;
+
+    for c in key.chars() {
+        if c == '_' && !result.is_empty() {
+            upper = true;
+        } else if upper {
+            result.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+/// Serialize a value into JSON bytes using the pooled buffer.
+fn json<T: Serialize>(value: &T) -> Result<Bytes, serde_json::Error> {
+    BUFFER.with_borrow_mut(|buffer| {
+        buffer.reserve(RESERVE_BYTES);
+        let result = serde_json::to_writer((&mut *buffer).writer(), value);
+        let bytes = buffer.split().freeze();
+        result.map(|()| bytes)
+    })
+}
+
+/// Serialize a value into MessagePack bytes, with structs as maps keyed by
+/// member name, using the pooled buffer.
+#[cfg(feature = "msgpack")]
+fn msgpack<T: Serialize>(value: &T) -> Result<Bytes, rmp_serde::encode::Error> {
+    BUFFER.with_borrow_mut(|buffer| {
+        buffer.reserve(RESERVE_BYTES);
+        let mut serializer = rmp_serde::Serializer::new((&mut *buffer).writer()).with_struct_map();
+        let result = value.serialize(&mut serializer);
+        let bytes = buffer.split().freeze();
+        result.map(|()| bytes)
+    })
+}
+
+/// Serialize a value into CBOR bytes using the pooled buffer.
+#[cfg(feature = "cbor")]
+fn cbor<T: Serialize>(value: &T) -> Result<Bytes, ciborium::ser::Error<std::io::Error>> {
+    BUFFER.with_borrow_mut(|buffer| {
+        buffer.reserve(RESERVE_BYTES);
+        let result = ciborium::into_writer(value, (&mut *buffer).writer());
+        let bytes = buffer.split().freeze();
+        result.map(|()| bytes)
+    })
+}
+
+/// A rendered problem response, independent of any web framework.
+pub(crate) struct Rendered {
+    pub(crate) status: StatusCode,
+    /// `None` for bodiless responses (`304 Not Modified`).
+    pub(crate) content_type: Option<HeaderValue>,
+    /// Headers besides `Content-Type`.
+    pub(crate) headers: HeaderMap,
+    pub(crate) body: Bytes,
+}
+
+impl Rendered {
+    pub(crate) fn new(status: StatusCode, body: Bytes) -> Self {
+        Self {
+            status,
+            content_type: Some(HeaderValue::from_static(PROBLEM_JSON)),
+            headers: HeaderMap::new(),
+            body,
+        }
+    }
+
+    /// A response without a body or `Content-Type`.
+    pub(crate) fn empty(status: StatusCode) -> Self {
+        Self {
+            content_type: None,
+            ..Self::new(status, Bytes::new())
+        }
+    }
+
+    /// Add response headers.
+    pub(crate) fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    /// Serialize a problem, falling back to a generic 500 body on failure.
+    pub(crate) fn from_problem(
+        status: StatusCode,
+        problem: &ProblemDetails,
+        naming: FieldNaming,
+    ) -> Self {
+        Self::from_result(
+            status,
+            problem_json(problem, naming),
+            HeaderValue::from_static(PROBLEM_JSON),
+        )
+    }
+
+    /// Serialize a problem as MessagePack.
+    #[cfg(feature = "msgpack")]
+    pub(crate) fn from_msgpack(
+        status: StatusCode,
+        problem: &ProblemDetails,
+        naming: FieldNaming,
+    ) -> Self {
+        Self::from_result(
+            status,
+            problem_msgpack(problem, naming),
+            HeaderValue::from_static(PROBLEM_MSGPACK),
+        )
+    }
+
+    /// Serialize a problem as CBOR.
+    #[cfg(feature = "cbor")]
+    pub(crate) fn from_cbor(
+        status: StatusCode,
+        problem: &ProblemDetails,
+        naming: FieldNaming,
+    ) -> Self {
+        Self::from_result(
+            status,
+            problem_cbor(problem, naming),
+            HeaderValue::from_static(PROBLEM_CBOR),
+        )
+    }
+
+    /// Serialize a problem with a registered profile serializer.
+    pub(crate) fn from_profile(
+        status: StatusCode,
+        problem: &ProblemDetails,
+        profile: &str,
+        serializer: &ProfileSerializer,
+    ) -> Self {
+        let content_type = HeaderValue::from_str(&format!("{PROBLEM_JSON}; profile=\"{profile}\""))
+            .unwrap_or_else(|_| HeaderValue::from_static(PROBLEM_JSON));
+        Self::from_json(status, &serializer(problem), content_type)
+    }
+
+    /// Serialize a problem in the legacy `ErrorResponse` shape.
+    #[cfg(feature = "legacy")]
+    #[allow(deprecated)]
+    pub(crate) fn from_legacy(status: StatusCode, problem: &ProblemDetails) -> Self {
+        Self::from_json(
+            status,
+            &ErrorResponse::from(problem),
+            HeaderValue::from_static(JSON),
+        )
+    }
+
+    fn from_json<T: Serialize>(status: StatusCode, value: &T, content_type: HeaderValue) -> Self {
+        Self::from_result(status, json(value), content_type)
+    }
+
+    fn from_result<E: fmt::Display>(
+        status: StatusCode,
+        body: Result<Bytes, E>,
+        content_type: HeaderValue,
+    ) -> Self {
+        match body {
+            Ok(body) => Self {
+                content_type: Some(content_type),
+                ..Self::new(status, body)
+            },
+            Err(error) => {
+                tracing::error!(exception.message = %error, "Failed to serialize problem details");
+                Self::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Bytes::from_static(FALLBACK_BODY),
+                )
+            }
+        }
+    }
+
+    /// Convert into a plain `http` response.
+    pub(crate) fn into_http_response(self) -> http::Response<Bytes> {
+        let mut response = http::Response::new(self.body);
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers;
+        if let Some(content_type) = self.content_type {
+            response
+                .headers_mut()
+                .insert(header::CONTENT_TYPE, content_type);
+        }
+        response
+    }
+
+    /// Convert into an axum response.
+    pub(crate) fn into_response(self) -> Response {
+        self.into_http_response().map(Body::from)
+    }
+}
@@ -0,0 +1,161 @@
+//! Multi-line error reports for logs and CLI output.
+
+use std::error::Error as _;
+use std::fmt;
+use std::panic::Location;
+
+use super::app_error::{AppError, CURRENT_REQUEST_ID};
+
+pub(crate) const RED_BOLD: &str = "\x1b[1;31m";
+pub(crate) const DIM: &str = "\x1b[2m";
+pub(crate) const RESET: &str = "\x1b[0m";
+
+/// Formats an `AppError` as an indented multi-line block with its code,
+/// request ID, location, field errors, source chain, and backtrace (if one
+/// was captured).
+///
+/// # Example
+/// ```ignore
+/// tracing::error!("{}", error.report());
+/// // Database error: connection refused
+/// //   code: DATABASE_ERROR (500 Internal Server Error)
+/// //   request id: 550e8400-e29b-41d4-a716-446655440000
+/// //   at: src/users/service.rs:42:17
+/// //   caused by:
+/// //     0: Connection Error: connection refused
+/// ```
+pub struct Report<'a> {
+    error: &'a AppError,
+    location: Option<&'static Location<'static>>,
+    color: bool,
+}
+
+impl<'a> Report<'a> {
+    /// Create a report located where the error was annotated (see
+    /// [`AppError::log_here`] and [`AppError::with_severity`]), or else at
+    /// the caller.
+    #[track_caller]
+    pub fn new(error: &'a AppError) -> Self {
+        Self::at(error, error.location().or(Some(Location::caller())))
+    }
+
+    /// Create a report with a known location, if any.
+    pub(crate) fn at(error: &'a AppError, location: Option<&'static Location<'static>>) -> Self {
+        Self {
+            error,
+            location,
+            color: false,
+        }
+    }
+
+    /// Use ANSI colors in the output.
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    fn request_id(&self) -> Option<String> {
+        match self.error.base() {
+            AppError::Remote(envelope) => envelope.request_id.clone(),
+            _ => CURRENT_REQUEST_ID.try_with(|id| id.to_string()).ok(),
+        }
+    }
+}
+
+impl Report<'_> {
+    /// Write the report without a trailing newline; the body of the `{:#}`
+    /// format of `AppError`.
+    pub(crate) fn write_block(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (error_style, dim, reset) = if self.color {
+            (RED_BOLD, DIM, RESET)
+        } else {
+            ("", "", "")
+        };
+        let (status, _) = self.error.status_and_title();
+
+        write!(f, "{error_style}{}{reset}", self.error)?;
+        write!(f, "\n  {dim}code:{reset} {} ({status})", self.error.code())?;
+        if let Some(request_id) = self.request_id() {
+            write!(f, "\n  {dim}request id:{reset} {request_id}")?;
+        }
+        if let Some(location) = self.location {
+            write!(f, "\n  {dim}at:{reset} {location}")?;
+        }
+
+        let errors = self.error.to_problem_details().errors;
+        if !errors.is_empty() {
+            write!(f, "\n  {dim}fields:{reset}")?;
+            for error in &errors {
+                write!(
+                    f,
+                    "\n    - {}: {} {dim}({}){reset}",
+                    error.field, error.message, error.code
+                )?;
+            }
+        }
+
+        let mut source = self.error.source();
+        if source.is_some() {
+            write!(f, "\n  {dim}caused by:{reset}")?;
+        }
+        let mut depth = 0;
+        while let Some(cause) = source {
+            write!(f, "\n    {depth}: {cause}")?;
+            source = cause.source();
+            depth += 1;
+        }
+
+        if let Some(backtrace) = self.error.backtrace() {
+            write!(f, "\n  {dim}backtrace:{reset}")?;
+            for line in backtrace.to_string().lines() {
+                write!(f, "\n    {line}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_block(f)?;
+        writeln!(f)
+    }
+}
+
+impl fmt::Debug for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl AppError {
+    /// Build a multi-line [`Report`] for this error, located where it was
+    /// annotated or else at the caller.
+    #[track_caller]
+    pub fn report(&self) -> Report<'_> {
+        Report::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Severity;
+    use crate::http_errors::not_found;
+
+    #[test]
+    fn prefers_the_annotated_location() {
+        let annotated_at = Location::caller();
+        let error = not_found("user", "7").with_severity(Severity::Info);
+        let report = error.report().to_string();
+        let expected = format!("at: {}:{}", annotated_at.file(), annotated_at.line() + 1);
+        assert!(report.contains(&expected), "{report}");
+
+        let error = not_found("user", "7");
+        let reported_at = Location::caller();
+        let report = error.report().to_string();
+        let expected = format!("at: {}:{}", reported_at.file(), reported_at.line() + 1);
+        assert!(report.contains(&expected), "{report}");
+    }
+}
@@ -0,0 +1,236 @@
+//! Secret scrubbing for error payloads.
+//!
+//! Error messages built from foreign errors (a `DbErr` Display, an upstream
+//! response) can carry credentials. The [`Scrubber`] masks well-known secret
+//! patterns in `detail`, field errors, and extension members before the
+//! problem is logged or serialized.
+
+use std::borrow::Cow;
+
+use regex::{Captures, Regex};
+
+use super::app_error::ProblemDetails;
+
+/// Replacement text for masked secrets.
+const DEFAULT_MASK: &str = "[REDACTED]";
+
+#[derive(Debug, Clone)]
+struct Pattern {
+    regex: Regex,
+    /// Only mask matches that pass the Luhn checksum (card numbers).
+    luhn: bool,
+}
+
+/// Masks secrets (tokens, keys, card numbers, credentials) in error text.
+///
+/// When a pattern has a capture group named `secret`, only that group is
+/// masked, so surrounding context such as `Bearer` or a URL scheme survives.
+///
+/// # Example
+/// ```ignore
+/// use eywa_errors::{set_errors_config, ErrorsConfig, Scrubber};
+///
+/// set_errors_config(ErrorsConfig::new().scrubber(Scrubber::new()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scrubber {
+    patterns: Vec<Pattern>,
+    mask: String,
+}
+
+impl Scrubber {
+    /// Create a scrubber with the built-in patterns: bearer tokens, AWS keys,
+    /// card numbers, and credentials in connection strings.
+    pub fn new() -> Self {
+        let builtin = [
+            (r"(?i)\bbearer\s+(?P<secret>[A-Za-z0-9\-._~+/]+=*)", false),
+            (r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b", false),
+            (
+                r"(?i)\baws_secret_access_key\s*[=:]\s*(?P<secret>[A-Za-z0-9/+=]{40})",
+                false,
+            ),
+            (r"\b(?:\d[ -]?){12,18}\d\b", true),
+            (
+                r"[a-zA-Z][a-zA-Z0-9+.\-]*://[^:/@\s]+:(?P<secret>[^@\s]+)@",
+                false,
+            ),
+            (
+                r"(?i)\b(?:password|passwd|pwd)\s*=\s*(?P<secret>[^;\s]+)",
+                false,
+            ),
+        ];
+
+        Self {
+            patterns: builtin
+                .into_iter()
+                .map(|(pattern, luhn)| Pattern {
+                    regex: Regex::new(pattern).expect("built-in scrub pattern is valid"),
+                    luhn,
+                })
+                .collect(),
+            mask: DEFAULT_MASK.to_string(),
+        }
+    }
+
+    /// Create a scrubber without any patterns.
+    pub fn empty() -> Self {
+        Self {
+            patterns: Vec::new(),
+            mask: DEFAULT_MASK.to_string(),
+        }
+    }
+
+    /// Add a custom pattern.
+    pub fn pattern(mut self, regex: Regex) -> Self {
+        self.patterns.push(Pattern { regex, luhn: false });
+        self
+    }
+
+    /// Set the replacement text for masked secrets.
+    pub fn mask(mut self, mask: impl Into<String>) -> Self {
+        self.mask = mask.into();
+        self
+    }
+
+    /// Mask all secrets found in `text`.
+    pub fn scrub<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut result = Cow::Borrowed(text);
+
+        for pattern in &self.patterns {
+            if !pattern.regex.is_match(&result) {
+                continue;
+            }
+            let replaced = pattern
+                .regex
+                .replace_all(&result, |caps: &Captures<'_>| {
+                    self.replacement(pattern, caps)
+                })
+                .into_owned();
+            result = Cow::Owned(replaced);
+        }
+
+        result
+    }
+
+    /// Mask all secrets found in string values nested anywhere in `value`.
+    pub fn scrub_value(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::String(text) => {
+                if let Cow::Owned(scrubbed) = self.scrub(text) {
+                    *text = scrubbed;
+                }
+            }
+            serde_json::Value::Array(items) => {
+                items.iter_mut().for_each(|item| self.scrub_value(item));
+            }
+            serde_json::Value::Object(map) => {
+                map.values_mut().for_each(|item| self.scrub_value(item));
+            }
+            _ => {}
+        }
+    }
+
+    /// Mask secrets in the detail, field messages, received values and
+    /// parameters, and extension members.
+    pub(crate) fn scrub_problem(&self, problem: &mut ProblemDetails) {
+        scrub_in_place(self, &mut problem.detail);
+
+        for error in &mut problem.errors {
+            scrub_in_place(self, &mut error.message);
+            if let Some(received) = &mut error.received {
+                self.scrub_value(received);
+            }
+            if let Some(params) = &mut error.params {
+                params
+                    .values_mut()
+                    .for_each(|value| self.scrub_value(value));
+            }
+        }
+        problem
+            .extensions
+            .values_mut()
+            .for_each(|value| self.scrub_value(value));
+    }
+
+    fn replacement(&self, pattern: &Pattern, caps: &Captures<'_>) -> String {
+        let whole = caps.get(0).expect("capture group 0 always matches");
+
+        if pattern.luhn && !passes_luhn(whole.as_str()) {
+            return whole.as_str().to_string();
+        }
+
+        match caps.name("secret") {
+            Some(secret) => {
+                let start = secret.start() - whole.start();
+                let end = secret.end() - whole.start();
+                let text = whole.as_str();
+                format!("{}{}{}", &text[..start], self.mask, &text[end..])
+            }
+            None => self.mask.clone(),
+        }
+    }
+}
+
+impl Default for Scrubber {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn scrub_in_place(scrubber: &Scrubber, text: &mut String) {
+    if let Cow::Owned(scrubbed) = scrubber.scrub(text) {
+        *text = scrubbed;
+    }
+}
+
+/// Luhn checksum over the digits of `candidate`, ignoring separators.
+fn passes_luhn(candidate: &str) -> bool {
+    let mut sum = 0;
+    let mut double = false;
+
+    for digit in candidate.chars().rev().filter_map(|c| c.to_digit(10)) {
+        let mut value = digit;
+        if double {
+            value *= 2;
+            if value > 9 {
+                value -= 9;
+            }
+        }
+        sum += value;
+        double = !double;
+    }
+
+    sum % 10 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::FieldError;
+
+    #[test]
+    fn extensions_and_params_are_scrubbed() {
+        let mut problem = crate::AppError::Unauthorized.to_problem_details();
+        problem.extensions.insert(
+            "upstream".into(),
+            json!({ "detail": "auth failed for Bearer abc.def", "attempts": [2] }),
+        );
+        problem.errors.push(
+            FieldError::new("dsn", "INVALID", "unreachable")
+                .with_param("url", "postgres://app:hunter2@db/app"),
+        );
+
+        Scrubber::new().scrub_problem(&mut problem);
+
+        assert_eq!(
+            problem.extensions["upstream"],
+            json!({ "detail": "auth failed for Bearer [REDACTED]", "attempts": [2] })
+        );
+        assert_eq!(
+            problem.errors[0].params.as_ref().unwrap()["url"],
+            "postgres://app:[REDACTED]@db/app"
+        );
+    }
+}
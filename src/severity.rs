@@ -0,0 +1,134 @@
+//! How loudly an error should be reported, independent of its HTTP status.
+//!
+//! Not every 500 deserves a page: a flapping dependency is worth a warning,
+//! a misconfigured secret is worth waking someone up. Each variant has a
+//! default, and call sites that know better can override it with
+//! [`AppError::with_severity`].
+
+use std::fmt;
+use std::panic::Location;
+
+use super::app_error::{Annotated, AppError};
+
+/// Operational severity of an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Severity {
+    /// Expected in normal operation (client mistakes, missing resources).
+    #[default]
+    Info,
+    /// Worth noticing, but not actionable on its own.
+    Warning,
+    /// Something failed on our side.
+    Error,
+    /// Needs immediate attention.
+    Critical,
+}
+
+impl Severity {
+    /// Lowercase name, as exported in the `severity` log field.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Critical => "critical",
+        }
+    }
+
+    /// The tracing level error events of this severity are logged at.
+    ///
+    /// `Critical` shares `ERROR` with `Error`; the `severity` field tells
+    /// them apart.
+    pub fn tracing_level(self) -> tracing::Level {
+        match self {
+            Severity::Info => tracing::Level::INFO,
+            Severity::Warning => tracing::Level::WARN,
+            Severity::Error | Severity::Critical => tracing::Level::ERROR,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl AppError {
+    /// The severity of this error: the override set with
+    /// [`AppError::with_severity`], or the variant's default.
+    pub fn severity(&self) -> Severity {
+        match self {
+            AppError::Annotated(annotated) => annotated.severity,
+            AppError::NotFound { .. }
+            | AppError::Validation(_)
+            | AppError::ValidationField { .. }
+            | AppError::BadRequest(_)
+            | AppError::InvalidCursor { .. }
+            | AppError::ExpiredCursor
+            | AppError::BodyTooLarge { .. }
+            | AppError::BodyReadTimeout
+            | AppError::InvalidContentEncoding { .. }
+            | AppError::NotAcceptable { .. }
+            | AppError::MethodNotAllowed { .. }
+            | AppError::HeadersTooLarge { .. }
+            | AppError::UriTooLong { .. }
+            | AppError::HttpVersionNotSupported
+            | AppError::UpgradeRequired { .. }
+            | AppError::Unauthorized
+            | AppError::Conflict { .. }
+            | AppError::IdempotencyConflict { .. }
+            | AppError::IdempotencyInProgress { .. }
+            | AppError::PreconditionFailed { .. }
+            | AppError::NotModified { .. }
+            | AppError::PaymentDeclined { .. }
+            | AppError::RateLimited { .. }
+            | AppError::ClientDisconnected
+            | AppError::Draining { .. } => Severity::Info,
+            AppError::TenantMismatch { .. }
+            | AppError::Forbidden { .. }
+            | AppError::InvalidSignature { .. }
+            | AppError::CsrfFailure
+            | AppError::InvalidOrigin { .. }
+            | AppError::ServiceUnavailable(_)
+            | AppError::GatewayTimeout(_)
+            | AppError::DeadlineExceeded
+            | AppError::Overloaded { .. } => Severity::Warning,
+            AppError::DatabaseError(_)
+            | AppError::ExternalServiceError { .. }
+            | AppError::InternalServerError(_) => Severity::Error,
+            AppError::ConfigError { .. } => Severity::Critical,
+            AppError::Remote(envelope) => match envelope.status {
+                500..=599 => Severity::Error,
+                _ => Severity::Info,
+            },
+        }
+    }
+
+    /// Override the severity of this error.
+    ///
+    /// # Example
+    /// ```ignore
+    /// // A cache miss falling through to a slow path is expected.
+    /// return Err(service_unavailable("cache").with_severity(Severity::Info));
+    /// ```
+    #[track_caller]
+    pub fn with_severity(self, severity: Severity) -> AppError {
+        let (error, logged, location, backtrace) = match self {
+            AppError::Annotated(annotated) => (
+                annotated.error,
+                annotated.logged,
+                annotated.location,
+                annotated.backtrace,
+            ),
+            error => (error, false, None, None),
+        };
+        AppError::Annotated(Box::new(Annotated {
+            error,
+            severity,
+            logged,
+            location: location.or(Some(Location::caller())),
+            backtrace,
+        }))
+    }
+}
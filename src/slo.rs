@@ -0,0 +1,174 @@
+//! Error budget tracking per route.
+//!
+//! With an [`SloPolicy`] installed, every rendered 5xx is counted against the
+//! route it was served on (from [`RequestContext::route`]), and requests are
+//! counted with [`record_request`], typically from middleware. The 5xx rate
+//! over a sliding window gives the remaining error budget, which services can
+//! read with [`error_budget_remaining`] to shed optional work before the SLO
+//! is breached.
+//!
+//! Each failure also emits the current reading as a gauge: a
+//! `eywa_errors::slo` tracing event with an `error_budget_remaining` field.
+//!
+//! [`RequestContext::route`]: crate::RequestContext::route
+//!
+//! # Example
+//! ```ignore
+//! set_errors_config(
+//!     ErrorsConfig::new().slo(SloPolicy::new(0.999, Duration::from_secs(300))),
+//! );
+//!
+//! // In middleware, for every request:
+//! record_request(route);
+//!
+//! // In a handler:
+//! if error_budget_remaining(route).is_some_and(|remaining| remaining < 0.2) {
+//!     return Err(overloaded(Duration::from_secs(5)));
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use super::config::get_errors_config;
+
+/// Upper bound on tracked routes; further routes are not tracked.
+const MAX_TRACKED_ROUTES: usize = 1024;
+
+/// Number of slots the window is divided into.
+const SLOTS: usize = 10;
+
+static START: LazyLock<Instant> = LazyLock::new(Instant::now);
+static WINDOWS: LazyLock<Mutex<HashMap<String, Window>>> = LazyLock::new(Default::default);
+
+/// Availability objective measured over a sliding window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SloPolicy {
+    objective: f64,
+    window: Duration,
+}
+
+impl SloPolicy {
+    /// Create a policy allowing a `1 - objective` share of requests to fail
+    /// with a 5xx within any `window` (e.g. `0.999` over five minutes).
+    pub fn new(objective: f64, window: Duration) -> Self {
+        Self {
+            objective: objective.clamp(0.0, 1.0),
+            window: window.max(Duration::from_secs(SLOTS as u64)),
+        }
+    }
+
+    /// Index of the slot `now` falls into, counted from process start.
+    fn epoch(&self, now: Instant) -> u64 {
+        let slot = self.window.as_millis() / SLOTS as u128;
+        (now.duration_since(*START).as_millis() / slot) as u64
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Slot {
+    epoch: u64,
+    requests: u64,
+    failures: u64,
+}
+
+#[derive(Default)]
+struct Window {
+    slots: [Slot; SLOTS],
+}
+
+impl Window {
+    /// The slot for `epoch`, cleared if it last held an older epoch.
+    fn slot(&mut self, epoch: u64) -> &mut Slot {
+        let slot = &mut self.slots[(epoch % SLOTS as u64) as usize];
+        if slot.epoch != epoch {
+            *slot = Slot {
+                epoch,
+                ..Slot::default()
+            };
+        }
+        slot
+    }
+
+    /// Requests and failures within the window ending at `epoch`.
+    fn totals(&self, epoch: u64) -> (u64, u64) {
+        self.slots
+            .iter()
+            .filter(|slot| slot.epoch + SLOTS as u64 > epoch)
+            .fold((0, 0), |(requests, failures), slot| {
+                (requests + slot.requests, failures + slot.failures)
+            })
+    }
+
+    /// Share of the error budget left, from 1.0 (no failures) down to 0.0.
+    fn remaining(&self, policy: &SloPolicy, epoch: u64) -> Option<f64> {
+        let (requests, failures) = self.totals(epoch);
+        if requests == 0 {
+            return None;
+        }
+        let allowed = 1.0 - policy.objective;
+        if allowed <= 0.0 {
+            return Some(if failures == 0 { 1.0 } else { 0.0 });
+        }
+        let error_rate = failures as f64 / requests as f64;
+        Some((1.0 - error_rate / allowed).clamp(0.0, 1.0))
+    }
+}
+
+/// Apply `update` to the window of `route`, if the route is or can be tracked.
+fn with_window<R>(route: &str, update: impl FnOnce(&mut Window) -> R) -> Option<R> {
+    let mut windows = WINDOWS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    if let Some(window) = windows.get_mut(route) {
+        return Some(update(window));
+    }
+    if windows.len() >= MAX_TRACKED_ROUTES {
+        return None;
+    }
+    Some(update(windows.entry(route.to_string()).or_default()))
+}
+
+/// Count a request served on `route`; a no-op without an [`SloPolicy`].
+pub fn record_request(route: &str) {
+    let Some(policy) = get_errors_config().slo else {
+        return;
+    };
+    let epoch = policy.epoch(Instant::now());
+    with_window(route, |window| window.slot(epoch).requests += 1);
+}
+
+/// Share of the error budget of `route` left in the current window, from
+/// 1.0 down to 0.0 once the objective is breached.
+///
+/// `None` without an [`SloPolicy`] or before any request on the route was
+/// recorded.
+pub fn error_budget_remaining(route: &str) -> Option<f64> {
+    let policy = get_errors_config().slo?;
+    let epoch = policy.epoch(Instant::now());
+    let windows = WINDOWS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    windows.get(route)?.remaining(&policy, epoch)
+}
+
+/// Count a 5xx rendered on `route` and report the remaining budget.
+pub(crate) fn record_failure(policy: &SloPolicy, route: &str) {
+    let epoch = policy.epoch(Instant::now());
+    let remaining = with_window(route, |window| {
+        window.slot(epoch).failures += 1;
+        window.remaining(policy, epoch)
+    })
+    .flatten();
+
+    if let Some(remaining) = remaining {
+        tracing::debug!(
+            target: "eywa_errors::slo",
+            error_budget_remaining = remaining,
+            http.route = route,
+            "Error budget updated"
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! Boot-time checks reported all at once.
+//!
+//! [`StartupErrors`] is to service startup what
+//! [`ValidationErrors`](crate::ValidationErrors) is to a request: each check
+//! (configuration, migrations, connectivity) records its failure and startup
+//! continues, so one run reports every problem instead of dying on the first.
+//!
+//! # Example
+//! ```ignore
+//! let mut errors = StartupErrors::new();
+//! let database_url = errors.check("config", env_var("DATABASE_URL"));
+//! let port = errors.check("config", parse_env::<u16>("PORT"));
+//! if let Some(url) = &database_url {
+//!     errors.check("database", Database::connect(url).await);
+//! }
+//! errors.exit_on_failure();
+//! // 2 startup checks failed:
+//! //   - config: Configuration error: PORT is invalid
+//! //       caused by: invalid digit found in string
+//! //   - database: Database error: connection refused
+//! ```
+
+use std::error::Error as _;
+use std::fmt;
+use std::io::IsTerminal;
+
+use super::app_error::AppError;
+use super::report::{DIM, RED_BOLD, RESET};
+
+/// A failed startup check.
+#[derive(Debug, Clone)]
+pub struct StartupError {
+    /// What was being checked (e.g. "config", "migrations", "database").
+    pub check: String,
+    /// Why it failed.
+    pub error: AppError,
+}
+
+/// Failed startup checks, in the order they ran.
+#[derive(Debug, Clone, Default)]
+pub struct StartupErrors {
+    errors: Vec<StartupError>,
+}
+
+impl StartupErrors {
+    /// Create an empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a failed check.
+    pub fn add(&mut self, check: impl Into<String>, error: impl Into<AppError>) {
+        self.errors.push(StartupError {
+            check: check.into(),
+            error: error.into(),
+        });
+    }
+
+    /// Move the failures of another collection into this one.
+    pub fn append(&mut self, other: StartupErrors) {
+        self.errors.extend(other.errors);
+    }
+
+    /// Keep the value of a successful check, or record its error and return
+    /// `None`.
+    pub fn check<T, E>(&mut self, check: impl Into<String>, result: Result<T, E>) -> Option<T>
+    where
+        E: Into<AppError>,
+    {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.add(check, error);
+                None
+            }
+        }
+    }
+
+    /// Whether every check passed.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Number of failed checks.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// The failed checks.
+    pub fn iter(&self) -> impl Iterator<Item = &StartupError> {
+        self.errors.iter()
+    }
+
+    /// `Ok` if every check passed, otherwise `Err(self)`.
+    pub fn into_result(self) -> Result<(), StartupErrors> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
+
+    /// Process exit code: that of the first failure, following `sysexits.h`.
+    pub fn exit_code(&self) -> i32 {
+        self.errors
+            .first()
+            .map_or(0, |failed| failed.error.exit_code())
+    }
+
+    /// If any check failed, print the report to stderr and exit with
+    /// [`exit_code`](Self::exit_code); otherwise return.
+    ///
+    /// Colors are used when stderr is a terminal and `NO_COLOR` is not set.
+    pub fn exit_on_failure(&self) {
+        if self.is_empty() {
+            return;
+        }
+        let stderr = std::io::stderr();
+        let color = stderr.is_terminal() && std::env::var_os("NO_COLOR").is_none();
+
+        eprint!("{}", Colored(self, color));
+        std::process::exit(self.exit_code())
+    }
+
+    fn write_report(&self, f: &mut fmt::Formatter<'_>, color: bool) -> fmt::Result {
+        let (error_style, dim, reset) = if color {
+            (RED_BOLD, DIM, RESET)
+        } else {
+            ("", "", "")
+        };
+
+        let count = self.errors.len();
+        writeln!(
+            f,
+            "{error_style}{count} startup check{} failed:{reset}",
+            if count == 1 { "" } else { "s" }
+        )?;
+        for failed in &self.errors {
+            writeln!(f, "  - {dim}{}:{reset} {}", failed.check, failed.error)?;
+            let mut source = failed.error.source();
+            while let Some(cause) = source {
+                writeln!(f, "      {dim}caused by:{reset} {cause}")?;
+                source = cause.source();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The report with or without ANSI colors.
+struct Colored<'a>(&'a StartupErrors, bool);
+
+impl fmt::Display for Colored<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.write_report(f, self.1)
+    }
+}
+
+/// A multi-line report of all failed checks, suitable for logs.
+impl fmt::Display for StartupErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_report(f, false)
+    }
+}
+
+impl std::error::Error for StartupErrors {}
+
+impl<'a> IntoIterator for &'a StartupErrors {
+    type Item = &'a StartupError;
+    type IntoIter = std::slice::Iter<'a, StartupError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.iter()
+    }
+}
@@ -0,0 +1,160 @@
+//! In-process error statistics.
+//!
+//! With [`ErrorsConfig::error_stats`](crate::ErrorsConfig::error_stats)
+//! enabled, every rendered error is counted by route (from
+//! [`RequestContext::route`](crate::RequestContext::route)), status, and
+//! code. With [`ErrorsConfig::recent_errors`](crate::ErrorsConfig::recent_errors)
+//! the last problems are kept as they were sent, after scrubbing and detail
+//! exposure policies. [`snapshot`] and [`recent`] read them, and
+//! [`debug_errors_router`] serves both as JSON for environments without a
+//! metrics or logging stack.
+//!
+//! # Example
+//! ```ignore
+//! set_errors_config(ErrorsConfig::new().error_stats().recent_errors(100));
+//!
+//! let app = Router::new()
+//!     .route("/users/{id}", get(get_user))
+//!     .merge(debug_errors_router());
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
+
+use axum::Router;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use serde::Serialize;
+
+use super::app_error::ProblemDetails;
+use super::config::get_errors_config;
+use super::timestamp::Timestamp;
+
+/// Upper bound on tracked (route, status, code) keys; further keys are not
+/// counted.
+const MAX_TRACKED_KEYS: usize = 4096;
+
+/// Path served by [`debug_errors_router`].
+pub const DEBUG_ERRORS_PATH: &str = "/debug/errors";
+
+static COUNTS: LazyLock<Mutex<HashMap<Key, Entry>>> = LazyLock::new(Default::default);
+static RECENT: LazyLock<Mutex<VecDeque<ProblemDetails>>> = LazyLock::new(Default::default);
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Key {
+    route: String,
+    status: u16,
+    code: String,
+}
+
+struct Entry {
+    count: u64,
+    last_seen: SystemTime,
+}
+
+/// Number of errors rendered with one route, status, and code.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorStat {
+    /// Route template, or `unknown` when the request context has none.
+    pub route: String,
+    /// HTTP status code.
+    pub status: u16,
+    /// Error code (e.g. `NOT_FOUND`).
+    pub code: String,
+    /// Errors counted since startup.
+    pub count: u64,
+    /// When the last of them was rendered, in the configured format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<Timestamp>,
+}
+
+/// Count a rendered error.
+pub(crate) fn record(route: &str, status: u16, code: &str) {
+    let now = SystemTime::now();
+    let mut counts = COUNTS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let key = Key {
+        route: route.to_string(),
+        status,
+        code: code.to_string(),
+    };
+    if let Some(entry) = counts.get_mut(&key) {
+        entry.count += 1;
+        entry.last_seen = now;
+    } else if counts.len() < MAX_TRACKED_KEYS {
+        counts.insert(
+            key,
+            Entry {
+                count: 1,
+                last_seen: now,
+            },
+        );
+    }
+}
+
+/// Current error counts, most frequent first.
+pub fn snapshot() -> Vec<ErrorStat> {
+    let format = get_errors_config().timestamp_format;
+    let counts = COUNTS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let mut stats: Vec<ErrorStat> = counts
+        .iter()
+        .map(|(key, entry)| ErrorStat {
+            route: key.route.clone(),
+            status: key.status,
+            code: key.code.clone(),
+            count: entry.count,
+            last_seen: Timestamp::format(entry.last_seen, format),
+        })
+        .collect();
+    stats.sort_by_key(|stat| std::cmp::Reverse(stat.count));
+    stats
+}
+
+/// Keep a rendered problem, dropping the oldest beyond `capacity`.
+pub(crate) fn remember(problem: &ProblemDetails, capacity: usize) {
+    let mut recent = RECENT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    while recent.len() >= capacity {
+        recent.pop_front();
+    }
+    recent.push_back(problem.clone());
+}
+
+/// The most recently rendered problems, newest first.
+pub fn recent() -> Vec<ProblemDetails> {
+    RECENT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .iter()
+        .rev()
+        .cloned()
+        .collect()
+}
+
+/// A router serving the error statistics and recent problems at
+/// [`DEBUG_ERRORS_PATH`].
+///
+/// Mount it only on an internal listener or behind authentication; it
+/// exposes which routes fail and how.
+pub fn debug_errors_router<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new().route(DEBUG_ERRORS_PATH, get(debug_errors))
+}
+
+async fn debug_errors() -> Response {
+    axum::Json(serde_json::json!({
+        "stats": snapshot(),
+        "recent": recent(),
+    }))
+    .into_response()
+}
@@ -0,0 +1,252 @@
+//! Precomputed bodies for parameterless errors.
+//!
+//! Errors such as `Unauthorized` always render the same body apart from the
+//! request ID and timestamp. Their JSON is serialized once per configuration
+//! with placeholders, split into literal segments, and afterwards rendered by
+//! splicing the two dynamic values between those segments.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use super::app_error::{AppError, apply_exposure};
+use super::config::{ErrorsConfig, get_errors_config};
+use super::render;
+use super::timestamp::Timestamp;
+
+const REQUEST_ID_PLACEHOLDER: &str = "__eywa_request_id__";
+const TIMESTAMP_PLACEHOLDER: &str = "__eywa_timestamp__";
+/// The timestamp slot replaces the whole JSON string, quotes included, so
+/// numeric timestamps can be spliced in as well.
+const QUOTED_TIMESTAMP_PLACEHOLDER: &str = "\"__eywa_timestamp__\"";
+
+static TEMPLATES: LazyLock<RwLock<Templates>> = LazyLock::new(Default::default);
+
+/// Templates built for the current configuration.
+#[derive(Default)]
+struct Templates {
+    /// Bumped by [`invalidate`], so templates built from a replaced
+    /// configuration are not inserted afterwards.
+    generation: u64,
+    by_code: HashMap<&'static str, Arc<Template>>,
+}
+
+impl Templates {
+    /// Cache a template built while `generation` was current.
+    fn insert(&mut self, generation: u64, code: &'static str, template: Arc<Template>) {
+        if self.generation == generation {
+            self.by_code.insert(code, template);
+        }
+    }
+
+    fn invalidate(&mut self) {
+        self.generation += 1;
+        self.by_code.clear();
+    }
+}
+
+enum Segment {
+    Literal(Bytes),
+    RequestId,
+    Timestamp,
+}
+
+/// A serialized problem body with slots for the request ID and timestamp.
+pub(crate) struct Template {
+    /// Error code, kept for logging.
+    pub(crate) code: String,
+    /// Detail before exposure policies are applied, kept for logging.
+    pub(crate) detail: String,
+    segments: Vec<Segment>,
+    literal_len: usize,
+}
+
+impl Template {
+    fn build(config: &ErrorsConfig, error: &AppError) -> Self {
+        let mut problem = error.to_problem_details();
+        problem.request_id = REQUEST_ID_PLACEHOLDER.to_string();
+        if problem.timestamp.is_some() {
+            problem.timestamp = Some(Timestamp::Rfc3339(TIMESTAMP_PLACEHOLDER.to_string()));
+        }
+
+        if let Some(scrubber) = &config.scrubber {
+            scrubber.scrub_problem(&mut problem);
+        }
+        let code = problem.code.to_string();
+        let detail = problem.detail.clone();
+        apply_exposure(config, &mut problem);
+
+        let body = render::problem_json(&problem, config.field_naming).unwrap_or_default();
+        let segments = split_segments(&body);
+        let literal_len = segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(bytes) => bytes.len(),
+                _ => 0,
+            })
+            .sum();
+
+        Self {
+            code,
+            detail,
+            segments,
+            literal_len,
+        }
+    }
+
+    /// Render the body for one response.
+    pub(crate) fn render(&self, request_id: &str, timestamp: Option<&Timestamp>) -> Bytes {
+        let mut body = BytesMut::with_capacity(self.literal_len + request_id.len() + 32);
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(bytes) => body.put_slice(bytes),
+                Segment::RequestId => body.put_slice(request_id.as_bytes()),
+                Segment::Timestamp => {
+                    // Timestamps never need escaping.
+                    match timestamp {
+                        Some(Timestamp::Rfc3339(text)) => {
+                            body.put_u8(b'"');
+                            body.put_slice(text.as_bytes());
+                            body.put_u8(b'"');
+                        }
+                        Some(Timestamp::UnixMillis(millis)) => {
+                            body.put_slice(millis.to_string().as_bytes())
+                        }
+                        None => body.put_slice(b"null"),
+                    }
+                }
+            }
+        }
+
+        body.freeze()
+    }
+}
+
+/// Split a serialized body at the placeholders.
+fn split_segments(body: &Bytes) -> Vec<Segment> {
+    let text = std::str::from_utf8(body).unwrap_or_default();
+    let mut segments = Vec::new();
+    let mut offset = 0;
+
+    loop {
+        let request_id = text[offset..]
+            .find(REQUEST_ID_PLACEHOLDER)
+            .map(|position| (offset + position, REQUEST_ID_PLACEHOLDER.len(), true));
+        let timestamp = text[offset..]
+            .find(QUOTED_TIMESTAMP_PLACEHOLDER)
+            .map(|position| (offset + position, QUOTED_TIMESTAMP_PLACEHOLDER.len(), false));
+
+        let Some((position, len, is_request_id)) = [request_id, timestamp]
+            .into_iter()
+            .flatten()
+            .min_by_key(|(position, _, _)| *position)
+        else {
+            break;
+        };
+
+        segments.push(Segment::Literal(body.slice(offset..position)));
+        segments.push(if is_request_id {
+            Segment::RequestId
+        } else {
+            Segment::Timestamp
+        });
+        offset = position + len;
+    }
+
+    segments.push(Segment::Literal(body.slice(offset..)));
+    segments
+}
+
+/// Get the template for a parameterless error, building it on first use.
+///
+/// Only templates built from the installed configuration are cached; one
+/// built while the configuration is being replaced is returned but dropped.
+pub(crate) fn get_or_build(config: &ErrorsConfig, error: &AppError) -> Arc<Template> {
+    let code = error.variant_code();
+
+    let generation = {
+        let templates = TEMPLATES
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(template) = templates.by_code.get(code) {
+            return template.clone();
+        }
+        templates.generation
+    };
+
+    let template = Arc::new(Template::build(config, error));
+    if !std::ptr::eq(config, &*get_errors_config()) {
+        return template;
+    }
+
+    TEMPLATES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(generation, code, template.clone());
+    template
+}
+
+/// Drop all templates; called when the configuration changes.
+pub(crate) fn invalidate() {
+    TEMPLATES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .invalidate();
+}
+
+/// Precompute the bodies of all parameterless errors.
+///
+/// Templates are otherwise built on first use. Call this at startup, after
+/// [`set_errors_config`](crate::set_errors_config), to keep that cost off the
+/// first request.
+pub fn precompute_error_templates() {
+    let config = get_errors_config();
+    get_or_build(&config, &AppError::Unauthorized);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_built_before_invalidate_are_not_cached() {
+        let config = ErrorsConfig::new();
+        let mut templates = Templates::default();
+        let generation = templates.generation;
+        let template = Arc::new(Template::build(&config, &AppError::Unauthorized));
+
+        templates.invalidate();
+        templates.insert(generation, "UNAUTHORIZED", template.clone());
+        assert!(templates.by_code.is_empty());
+
+        templates.insert(templates.generation, "UNAUTHORIZED", template);
+        assert!(templates.by_code.contains_key("UNAUTHORIZED"));
+    }
+
+    #[test]
+    fn templates_render_the_full_body() {
+        let config = ErrorsConfig::new();
+        let timestamp = Timestamp::Rfc3339("2026-01-06T14:17:00Z".to_string());
+
+        for error in [
+            AppError::Unauthorized,
+            AppError::CsrfFailure,
+            AppError::ExpiredCursor,
+            AppError::BodyReadTimeout,
+            AppError::HttpVersionNotSupported,
+            AppError::DeadlineExceeded,
+            AppError::ClientDisconnected,
+        ] {
+            assert!(error.is_parameterless());
+            let mut problem = error.to_problem_details();
+            problem.request_id = "req-1".to_string();
+            problem.timestamp = Some(timestamp.clone());
+            let expected = render::problem_json(&problem, config.field_naming).unwrap();
+
+            let template = Template::build(&config, &error);
+            assert_eq!(template.render("req-1", Some(&timestamp)), expected);
+        }
+    }
+}
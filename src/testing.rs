@@ -0,0 +1,197 @@
+//! Helpers for asserting on error responses in tests.
+//!
+//! Enabled with the `testing` feature, typically as a dev-dependency:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! eywa-errors = { version = "*", features = ["testing"] }
+//! ```
+//!
+//! # Example
+//! ```ignore
+//! use eywa_errors::{assert_field_error, assert_problem};
+//!
+//! let response = app.oneshot(request).await.unwrap();
+//! let problem = assert_problem!(response, status = 400, code = "VALIDATION_ERROR");
+//! assert_field_error!(problem, "email", "invalid_format");
+//! ```
+
+use std::time::SystemTime;
+
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use uuid::Uuid;
+
+use super::app_error::{AppError, CURRENT_REQUEST_ID, ProblemDetails};
+use super::render::PROBLEM_JSON;
+use super::timestamp::FIXED_NOW;
+
+/// Runs `f` with a pinned request ID and clock, so rendered problems are
+/// byte-stable across runs (for snapshot tests).
+///
+/// ```ignore
+/// let problem = with_fixed_context(Uuid::nil(), SystemTime::UNIX_EPOCH, || {
+///     not_found("user", "42").into_problem_details()
+/// });
+/// insta::assert_json_snapshot!(problem);
+/// ```
+pub fn with_fixed_context<F, R>(request_id: Uuid, timestamp: SystemTime, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    CURRENT_REQUEST_ID.sync_scope(request_id, || FIXED_NOW.sync_scope(timestamp, f))
+}
+
+/// Async variant of [`with_fixed_context`], for driving a router or handler.
+pub async fn scope_fixed_context<F>(request_id: Uuid, timestamp: SystemTime, future: F) -> F::Output
+where
+    F: Future,
+{
+    CURRENT_REQUEST_ID
+        .scope(request_id, FIXED_NOW.scope(timestamp, future))
+        .await
+}
+
+/// Read a response body and parse it as ProblemDetails.
+///
+/// # Panics
+/// Panics if the response is not `application/problem+json` or the body does
+/// not parse.
+pub async fn read_problem(response: Response) -> ProblemDetails {
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    assert!(
+        content_type.starts_with(PROBLEM_JSON),
+        "expected a {PROBLEM_JSON} response, got content-type {content_type:?}"
+    );
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body");
+
+    serde_json::from_slice(&body).unwrap_or_else(|error| {
+        panic!(
+            "response body is not a valid problem: {error}\nbody: {}",
+            String::from_utf8_lossy(&body)
+        )
+    })
+}
+
+/// Anything [`assert_problem!`](crate::assert_problem) can inspect.
+pub trait ProblemSource {
+    /// Resolve to the problem carried by this value.
+    fn into_problem(self) -> impl Future<Output = ProblemDetails> + Send;
+}
+
+impl ProblemSource for Response {
+    async fn into_problem(self) -> ProblemDetails {
+        read_problem(self).await
+    }
+}
+
+impl ProblemSource for AppError {
+    async fn into_problem(self) -> ProblemDetails {
+        read_problem(self.into_response()).await
+    }
+}
+
+impl ProblemSource for ProblemDetails {
+    async fn into_problem(self) -> ProblemDetails {
+        self
+    }
+}
+
+/// Assert on the members of a problem response and return the parsed problem.
+///
+/// Accepts a `Response`, an `AppError`, or a `ProblemDetails`. Must be used in
+/// an async context. Supported members: `status`, `code`, `title`, `detail`,
+/// `error_type`, `instance`.
+///
+/// ```ignore
+/// let problem = assert_problem!(response, status = 404, code = "NOT_FOUND");
+/// ```
+#[macro_export]
+macro_rules! assert_problem {
+    ($source:expr $(, $member:ident = $expected:expr)* $(,)?) => {{
+        let problem = $crate::testing::ProblemSource::into_problem($source).await;
+        $( $crate::__assert_problem_member!(problem, $member, $expected); )*
+        problem
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_problem_member {
+    ($problem:ident, status, $expected:expr) => {
+        assert_eq!(
+            $problem.status, $expected,
+            "problem status mismatch: {:#?}",
+            $problem
+        )
+    };
+    ($problem:ident, code, $expected:expr) => {
+        assert_eq!(
+            $problem.code, $expected,
+            "problem code mismatch: {:#?}",
+            $problem
+        )
+    };
+    ($problem:ident, title, $expected:expr) => {
+        assert_eq!(
+            $problem.title, $expected,
+            "problem title mismatch: {:#?}",
+            $problem
+        )
+    };
+    ($problem:ident, detail, $expected:expr) => {
+        assert_eq!(
+            $problem.detail, $expected,
+            "problem detail mismatch: {:#?}",
+            $problem
+        )
+    };
+    ($problem:ident, error_type, $expected:expr) => {
+        assert_eq!(
+            $problem.error_type, $expected,
+            "problem type mismatch: {:#?}",
+            $problem
+        )
+    };
+    ($problem:ident, instance, $expected:expr) => {
+        assert_eq!(
+            $problem.instance.as_deref(),
+            Some($expected),
+            "problem instance mismatch: {:#?}",
+            $problem
+        )
+    };
+}
+
+/// Assert that a problem contains a field error with the given field and code.
+///
+/// ```ignore
+/// assert_field_error!(problem, "email", "invalid_format");
+/// ```
+#[macro_export]
+macro_rules! assert_field_error {
+    ($problem:expr, $field:expr, $code:expr $(,)?) => {{
+        let problem: &$crate::ProblemDetails = &$problem;
+        let (field, code): (&str, &str) = ($field, $code);
+        assert!(
+            problem
+                .errors
+                .iter()
+                .any(|error| error.field == field && error.code == code),
+            "no field error {field:?} with code {code:?}; got: {:?}",
+            problem
+                .errors
+                .iter()
+                .map(|error| (error.field.as_str(), error.code.as_ref()))
+                .collect::<Vec<_>>()
+        );
+    }};
+}
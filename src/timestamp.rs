@@ -0,0 +1,214 @@
+//! Timestamp generation for problem responses.
+//!
+//! Formatting, part of the `server` feature, is backed by `chrono` (the
+//! default) or `time`, selected with the crate features of the same name. The
+//! format and the clock are configured through `ErrorsConfig`.
+
+use std::sync::Arc;
+use std::time::SystemTime;
+#[cfg(feature = "server")]
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[cfg(feature = "server")]
+use super::config::get_errors_config;
+
+#[cfg(all(feature = "server", not(any(feature = "chrono", feature = "time"))))]
+compile_error!(
+    "the `server` feature of eywa-errors requires either the `chrono` or the `time` feature"
+);
+
+#[cfg(feature = "testing")]
+tokio::task_local! {
+    /// Task-local time override set by `testing::with_fixed_context`.
+    pub(crate) static FIXED_NOW: SystemTime;
+}
+
+/// Source of the current time, injectable for deterministic tests.
+pub type Clock = Arc<dyn Fn() -> SystemTime + Send + Sync>;
+
+/// Fractional-second precision of RFC 3339 timestamps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampPrecision {
+    /// As many fractional digits as the time needs (none, 3, 6, or 9) and a
+    /// `+00:00` offset, e.g. `2026-01-06T14:17:00.123456789+00:00`: the
+    /// output of chrono's `to_rfc3339`, as rendered by earlier versions.
+    #[default]
+    Auto,
+    /// Whole seconds, e.g. `2026-01-06T14:17:00Z`.
+    Seconds,
+    /// Milliseconds, e.g. `2026-01-06T14:17:00.123Z`.
+    Millis,
+}
+
+/// How the `timestamp` member of a problem response is rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// RFC 3339 string in UTC.
+    Rfc3339(TimestampPrecision),
+    /// Milliseconds since the Unix epoch, as a JSON number.
+    UnixMillis,
+    /// No `timestamp` member.
+    Omitted,
+}
+
+impl Default for TimestampFormat {
+    fn default() -> Self {
+        TimestampFormat::Rfc3339(TimestampPrecision::default())
+    }
+}
+
+/// Timestamp of when an error occurred, as rendered in a problem response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum Timestamp {
+    /// RFC 3339 timestamp, e.g. `2026-01-06T14:17:00Z`.
+    Rfc3339(String),
+    /// Milliseconds since the Unix epoch.
+    UnixMillis(i64),
+}
+
+#[cfg(feature = "server")]
+impl Timestamp {
+    /// Render a point in time in the given format.
+    pub fn format(at: SystemTime, format: TimestampFormat) -> Option<Self> {
+        match format {
+            TimestampFormat::Rfc3339(precision) => {
+                Some(Timestamp::Rfc3339(format_rfc3339(at, precision)))
+            }
+            TimestampFormat::UnixMillis => Some(Timestamp::UnixMillis(unix_millis(at))),
+            TimestampFormat::Omitted => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Timestamp::Rfc3339(text) => f.write_str(text),
+            Timestamp::UnixMillis(millis) => write!(f, "{millis}"),
+        }
+    }
+}
+
+/// Current timestamp as rendered in problem responses.
+#[cfg(feature = "server")]
+pub(crate) fn now() -> Option<Timestamp> {
+    let config = get_errors_config();
+    let at = match &config.clock {
+        Some(clock) => clock(),
+        None => SystemTime::now(),
+    };
+    #[cfg(feature = "testing")]
+    let at = FIXED_NOW.try_with(|fixed| *fixed).unwrap_or(at);
+
+    Timestamp::format(at, config.timestamp_format)
+}
+
+#[cfg(feature = "server")]
+fn unix_millis(at: SystemTime) -> i64 {
+    match at.duration_since(UNIX_EPOCH) {
+        Ok(since) => i64::try_from(since.as_millis()).unwrap_or(i64::MAX),
+        Err(before) => -i64::try_from(before.duration().as_millis()).unwrap_or(i64::MAX),
+    }
+}
+
+/// Format a point in time as an RFC 3339 UTC timestamp.
+#[cfg(all(feature = "server", feature = "time"))]
+fn format_rfc3339(at: SystemTime, precision: TimestampPrecision) -> String {
+    use time::OffsetDateTime;
+    use time::macros::format_description;
+
+    let at = OffsetDateTime::from(at);
+    let formatted = match precision {
+        TimestampPrecision::Auto => at
+            .format(format_description!(
+                "[year]-[month]-[day]T[hour]:[minute]:[second]"
+            ))
+            .map(|seconds| format!("{seconds}{}+00:00", auto_fraction(at.nanosecond()))),
+        TimestampPrecision::Seconds => at.format(format_description!(
+            "[year]-[month]-[day]T[hour]:[minute]:[second]Z"
+        )),
+        TimestampPrecision::Millis => at.format(format_description!(
+            "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
+        )),
+    };
+
+    formatted.unwrap_or_default()
+}
+
+/// Fractional seconds as chrono's `SecondsFormat::AutoSi` renders them.
+#[cfg(all(feature = "server", feature = "time"))]
+fn auto_fraction(nanos: u32) -> String {
+    if nanos == 0 {
+        String::new()
+    } else if nanos.is_multiple_of(1_000_000) {
+        format!(".{:03}", nanos / 1_000_000)
+    } else if nanos.is_multiple_of(1_000) {
+        format!(".{:06}", nanos / 1_000)
+    } else {
+        format!(".{nanos:09}")
+    }
+}
+
+/// Format a point in time as an RFC 3339 UTC timestamp.
+#[cfg(all(feature = "server", feature = "chrono", not(feature = "time")))]
+fn format_rfc3339(at: SystemTime, precision: TimestampPrecision) -> String {
+    use chrono::{DateTime, SecondsFormat, Utc};
+
+    let at = DateTime::<Utc>::from(at);
+    match precision {
+        TimestampPrecision::Auto => at.to_rfc3339(),
+        TimestampPrecision::Seconds => at.to_rfc3339_opts(SecondsFormat::Secs, true),
+        TimestampPrecision::Millis => at.to_rfc3339_opts(SecondsFormat::Millis, true),
+    }
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn at(nanos: u32) -> SystemTime {
+        UNIX_EPOCH + Duration::new(1_767_709_020, nanos)
+    }
+
+    #[test]
+    fn default_precision_matches_earlier_versions() {
+        let format = TimestampFormat::default();
+        let rendered = |nanos| Timestamp::format(at(nanos), format).unwrap().to_string();
+
+        assert_eq!(rendered(0), "2026-01-06T14:17:00+00:00");
+        assert_eq!(rendered(120_000_000), "2026-01-06T14:17:00.120+00:00");
+        assert_eq!(rendered(123_456_000), "2026-01-06T14:17:00.123456+00:00");
+        assert_eq!(rendered(123_456_789), "2026-01-06T14:17:00.123456789+00:00");
+    }
+
+    #[cfg(all(feature = "chrono", feature = "time"))]
+    #[test]
+    fn time_backend_matches_chrono_to_rfc3339() {
+        for nanos in [0, 5_000_000, 5_000, 5, 999_999_999] {
+            let expected = chrono::DateTime::<chrono::Utc>::from(at(nanos)).to_rfc3339();
+            assert_eq!(
+                format_rfc3339(at(nanos), TimestampPrecision::Auto),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn explicit_precisions_use_z() {
+        let rendered = |precision| format_rfc3339(at(123_456_789), precision);
+        assert_eq!(
+            rendered(TimestampPrecision::Seconds),
+            "2026-01-06T14:17:00Z"
+        );
+        assert_eq!(
+            rendered(TimestampPrecision::Millis),
+            "2026-01-06T14:17:00.123Z"
+        );
+    }
+}
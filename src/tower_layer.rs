@@ -0,0 +1,133 @@
+//! Tower middleware turning boxed errors into problem responses.
+//!
+//! Tower middleware such as `Timeout`, `LoadShed`, and `Buffer` fail with a
+//! `BoxError` instead of a response, which axum cannot render on its own.
+//! [`ErrorMappingLayer`] sits outside those layers and maps each failure to an
+//! [`AppError`] response.
+//!
+//! # Example
+//! ```ignore
+//! use std::time::Duration;
+//! use eywa_errors::ErrorMappingLayer;
+//! use tower::ServiceBuilder;
+//!
+//! let app = Router::new().route("/", get(handler)).layer(
+//!     ServiceBuilder::new()
+//!         .layer(ErrorMappingLayer::new())
+//!         .load_shed()
+//!         .timeout(Duration::from_secs(10)),
+//! );
+//! ```
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use axum::response::{IntoResponse, Response};
+use tower::{BoxError, Layer, Service};
+
+use super::app_error::AppError;
+
+/// Map a boxed middleware error to an [`AppError`]; see
+/// [`AppError::from_box_error`].
+pub fn map_box_error(error: BoxError) -> AppError {
+    AppError::from_box_error(error)
+}
+
+/// Layer mapping `Err(BoxError)` from inner services to problem responses.
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorMappingLayer<F = fn(BoxError) -> AppError> {
+    mapper: F,
+}
+
+impl ErrorMappingLayer {
+    /// Create a layer using [`map_box_error`].
+    pub fn new() -> Self {
+        Self {
+            mapper: map_box_error,
+        }
+    }
+}
+
+impl Default for ErrorMappingLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F> ErrorMappingLayer<F>
+where
+    F: Fn(BoxError) -> AppError + Clone,
+{
+    /// Create a layer using a custom mapper.
+    ///
+    /// Custom mappers can fall back to [`map_box_error`] for errors they do
+    /// not recognize.
+    pub fn with_mapper(mapper: F) -> Self {
+        Self { mapper }
+    }
+}
+
+impl<S, F: Clone> Layer<S> for ErrorMappingLayer<F> {
+    type Service = ErrorMappingService<S, F>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ErrorMappingService {
+            inner,
+            mapper: self.mapper.clone(),
+            not_ready: None,
+        }
+    }
+}
+
+/// Service produced by [`ErrorMappingLayer`].
+#[derive(Debug, Clone)]
+pub struct ErrorMappingService<S, F> {
+    inner: S,
+    mapper: F,
+    /// Readiness failure of the inner service, answered by the next `call`.
+    not_ready: Option<AppError>,
+}
+
+impl<S, F, Request> Service<Request> for ErrorMappingService<S, F>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+    F: Fn(BoxError) -> AppError + Clone + Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Readiness errors (e.g. a closed buffer) are answered by `call` with
+        // an error response instead of tearing down the connection.
+        match self.inner.poll_ready(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Ok(())) => Poll::Ready(Ok(())),
+            Poll::Ready(Err(error)) => {
+                self.not_ready = Some((self.mapper)(error.into()));
+                Poll::Ready(Ok(()))
+            }
+        }
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        if let Some(error) = self.not_ready.take() {
+            return Box::pin(std::future::ready(Ok(error.into_response())));
+        }
+
+        let future = self.inner.call(request);
+        let mapper = self.mapper.clone();
+
+        Box::pin(async move {
+            match future.await {
+                Ok(response) => Ok(response.into_response()),
+                Err(error) => Ok(mapper(error.into()).into_response()),
+            }
+        })
+    }
+}
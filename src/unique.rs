@@ -0,0 +1,78 @@
+//! Uniqueness checks reported as validation errors.
+//!
+//! "Email already taken" should be a 422 with a `not_unique` field error, not
+//! a 500 from the database's unique constraint. [`require_unique`] and
+//! [`require_unique_all`] check up front; [`unique_violation`] maps the
+//! constraint violation of a concurrent insert that got there first to the
+//! same error.
+//!
+//! # Example
+//! ```ignore
+//! require_unique("email", &input.email, |email| users::email_exists(&db, email)).await?;
+//!
+//! users::insert(&db, &input)
+//!     .await
+//!     .map_err(|error| unique_violation("email", error))?;
+//! ```
+
+use std::future::Future;
+
+use sea_orm::{DbErr, SqlErr};
+
+use super::app_error::{AppError, FieldError, ValidationErrors};
+
+/// Field error code for values that are already taken.
+pub const NOT_UNIQUE: &str = "not_unique";
+
+fn not_unique(field: &str) -> FieldError {
+    FieldError::new(field, NOT_UNIQUE, format!("{field} is already taken"))
+}
+
+/// Fail with a `not_unique` error on `field` if `exists` reports that
+/// `value` is taken.
+pub async fn require_unique<F, Fut>(field: &str, value: &str, exists: F) -> Result<(), AppError>
+where
+    F: FnOnce(&str) -> Fut,
+    Fut: Future<Output = Result<bool, AppError>>,
+{
+    if exists(value).await? {
+        return Err(not_unique(field).into());
+    }
+    Ok(())
+}
+
+/// Check a batch of values with one query.
+///
+/// `existing` receives all values and returns those already taken. Values
+/// that are taken, or repeated within the batch, get a `not_unique` error on
+/// `field[index]`.
+pub async fn require_unique_all<F, Fut>(
+    field: &str,
+    values: &[&str],
+    existing: F,
+) -> Result<(), AppError>
+where
+    F: FnOnce(&[&str]) -> Fut,
+    Fut: Future<Output = Result<Vec<String>, AppError>>,
+{
+    let taken = existing(values).await?;
+
+    let errors: ValidationErrors = values
+        .iter()
+        .enumerate()
+        .filter(|&(index, value)| {
+            taken.iter().any(|taken| taken == value) || values[..index].contains(value)
+        })
+        .map(|(index, _)| not_unique(&format!("{field}[{index}]")))
+        .collect();
+    errors.into_result()
+}
+
+/// Map a unique constraint violation to a `not_unique` error on `field`;
+/// other database errors convert as usual.
+pub fn unique_violation(field: &str, error: DbErr) -> AppError {
+    match error.sql_err() {
+        Some(SqlErr::UniqueConstraintViolation(_)) => not_unique(field).into(),
+        _ => AppError::from(error),
+    }
+}
@@ -0,0 +1,136 @@
+//! Details of a failed call to an upstream service.
+//!
+//! `AppError::ExternalServiceError` always renders as a plain 502; the
+//! upstream status, latency, and the start of the response body are only
+//! logged, so dashboards can slice 502s by upstream cause without leaking
+//! upstream internals to clients. Only the connection failure class, if any,
+//! is returned as the problem's `reason` member.
+
+use std::time::Duration;
+
+use super::app_error::AppError;
+use super::outbound::ConnectFailure;
+
+/// Longest response snippet kept, in bytes.
+pub const MAX_SNIPPET_LEN: usize = 512;
+
+/// What is known about a failed upstream call.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UpstreamDetails {
+    /// HTTP status the upstream responded with, if it responded.
+    pub status: Option<u16>,
+
+    /// Time from sending the request to the failure.
+    pub latency: Option<Duration>,
+
+    /// Start of the upstream response body, at most [`MAX_SNIPPET_LEN`] bytes.
+    pub snippet: Option<String>,
+
+    /// Why no connection was established, if that is how the call failed.
+    pub failure: Option<ConnectFailure>,
+
+    /// Whether the upstream reported the failure as transient (worth
+    /// retrying) or permanent, when it said either.
+    pub transient: Option<bool>,
+
+    /// Error code from the upstream's problem body, if it sent one.
+    pub code: Option<String>,
+}
+
+impl UpstreamDetails {
+    /// Create empty upstream details.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the upstream HTTP status.
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Set the call latency.
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Set the response snippet, truncated to [`MAX_SNIPPET_LEN`] bytes on a
+    /// character boundary.
+    pub fn snippet(mut self, body: impl Into<String>) -> Self {
+        let mut body = body.into();
+        if body.len() > MAX_SNIPPET_LEN {
+            let end = (0..=MAX_SNIPPET_LEN)
+                .rev()
+                .find(|&index| body.is_char_boundary(index))
+                .unwrap_or(0);
+            body.truncate(end);
+        }
+        self.snippet = Some(body);
+        self
+    }
+
+    /// Set the connection failure class.
+    pub fn failure(mut self, failure: ConnectFailure) -> Self {
+        self.failure = Some(failure);
+        self
+    }
+
+    /// Set whether the failure is worth retrying.
+    pub fn transient(mut self, transient: bool) -> Self {
+        self.transient = Some(transient);
+        self
+    }
+
+    /// Set the upstream's error code.
+    pub fn code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// Whether nothing is known beyond the service name.
+    pub fn is_empty(&self) -> bool {
+        self.status.is_none()
+            && self.latency.is_none()
+            && self.snippet.is_none()
+            && self.failure.is_none()
+            && self.transient.is_none()
+            && self.code.is_none()
+    }
+}
+
+/// An upstream failure to be logged alongside the error event.
+pub(crate) struct UpstreamFailure {
+    pub(crate) service: String,
+    pub(crate) details: UpstreamDetails,
+}
+
+impl UpstreamFailure {
+    pub(crate) fn for_error(error: &AppError) -> Option<Self> {
+        match error.base() {
+            AppError::ExternalServiceError { service, upstream } if !upstream.is_empty() => {
+                Some(Self {
+                    service: service.clone(),
+                    details: upstream.clone(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Log the upstream details under the `eywa_errors::upstream` target.
+    pub(crate) fn emit(self, request_id: Option<&str>) {
+        tracing::warn!(
+            target: "eywa_errors::upstream",
+            request_id,
+            peer.service = %self.service,
+            upstream_status = self.details.status,
+            latency_ms = self.details.latency.map(|latency| latency.as_millis() as u64),
+            snippet = self.details.snippet.as_deref(),
+            failure = self.details.failure.map(|failure| failure.code()),
+            transient = self.details.transient,
+            upstream_code = self.details.code.as_deref(),
+            "Upstream call failed"
+        );
+    }
+}
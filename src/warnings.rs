@@ -0,0 +1,123 @@
+//! Non-fatal warnings attached to a response.
+//!
+//! A request can succeed with caveats: a deprecated parameter was used, or
+//! part of the data could not be loaded. Code anywhere in the request calls
+//! [`push_warning`]; [`warnings_middleware`] collects the warnings for the
+//! request and returns them as `Warning` headers. Error responses also carry
+//! them in a `warnings` problem member.
+//!
+//! # Example
+//! ```ignore
+//! use eywa_errors::{push_warning, warnings_middleware};
+//!
+//! let app = Router::new()
+//!     .route("/search", get(search))
+//!     .layer(axum::middleware::from_fn(warnings_middleware));
+//!
+//! async fn search(Query(params): Query<SearchParams>) -> Json<Results> {
+//!     if params.sort_by.is_some() {
+//!         push_warning("deprecated_param", "sort_by is deprecated; use order");
+//!     }
+//!     // ...
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+
+use axum::extract::Request;
+use axum::http::{HeaderValue, header};
+use axum::middleware::Next;
+use axum::response::Response;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::app_error::ProblemDetails;
+
+tokio::task_local! {
+    /// Task-local collector for the current request's warnings.
+    /// Set by [`warnings_middleware`] or [`collect_warnings`].
+    pub static CURRENT_WARNINGS: RefCell<Vec<Warning>>;
+}
+
+/// A non-fatal caveat about a response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Warning {
+    /// Machine-readable warning code (e.g. "deprecated_param").
+    #[schema(value_type = String)]
+    pub code: Cow<'static, str>,
+
+    /// Human-readable explanation.
+    pub message: String,
+}
+
+impl Warning {
+    /// Create a new warning.
+    pub fn new(code: impl Into<Cow<'static, str>>, message: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Record a warning for the current request.
+///
+/// Does nothing outside a collecting scope.
+pub fn push_warning(code: impl Into<Cow<'static, str>>, message: impl Into<String>) {
+    let warning = Warning::new(code, message);
+    let _ = CURRENT_WARNINGS.try_with(|warnings| warnings.borrow_mut().push(warning));
+}
+
+/// Gets a copy of the warnings recorded so far in the current scope.
+pub fn get_warnings() -> Vec<Warning> {
+    CURRENT_WARNINGS
+        .try_with(|warnings| warnings.borrow().clone())
+        .unwrap_or_default()
+}
+
+/// Whether any warning has been recorded in the current scope.
+pub(crate) fn has_warnings() -> bool {
+    CURRENT_WARNINGS
+        .try_with(|warnings| !warnings.borrow().is_empty())
+        .unwrap_or(false)
+}
+
+/// Run `future` in a collecting scope, returning its output and warnings.
+pub async fn collect_warnings<F: Future>(future: F) -> (F::Output, Vec<Warning>) {
+    CURRENT_WARNINGS
+        .scope(RefCell::new(Vec::new()), async {
+            let output = future.await;
+            (output, CURRENT_WARNINGS.with(|warnings| warnings.take()))
+        })
+        .await
+}
+
+/// Add the recorded warnings to a problem as its `warnings` member.
+pub(crate) fn attach(problem: &mut ProblemDetails) {
+    let warnings = get_warnings();
+    if warnings.is_empty() {
+        return;
+    }
+    if let Ok(value) = serde_json::to_value(warnings) {
+        problem.extensions.insert("warnings".into(), value);
+    }
+}
+
+/// Middleware collecting warnings for each request into `Warning` headers
+/// (`299 - "code: message"`).
+///
+/// Install with `axum::middleware::from_fn`.
+pub async fn warnings_middleware(request: Request, next: Next) -> Response {
+    let (mut response, warnings) = collect_warnings(next.run(request)).await;
+
+    for warning in warnings {
+        let text = format!("{}: {}", warning.code, warning.message).replace(['"', '\\'], "'");
+        // Messages that are not valid header text only appear in the body.
+        if let Ok(value) = HeaderValue::from_str(&format!("299 - \"{text}\"")) {
+            response.headers_mut().append(header::WARNING, value);
+        }
+    }
+
+    response
+}
@@ -0,0 +1,50 @@
+//! Webhook signature verification failures.
+//!
+//! Receivers for provider webhooks (Stripe, GitHub, ...) report verification
+//! failures as `AppError::InvalidSignature`. It renders as 401 by default;
+//! providers differ in which statuses they retry, so the status can be set
+//! per provider with [`ErrorsConfig::signature_failure_status`].
+//!
+//! [`ErrorsConfig::signature_failure_status`]: crate::ErrorsConfig::signature_failure_status
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Why a webhook signature was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureFailure {
+    /// No signature header was sent.
+    Missing,
+    /// The signature header could not be parsed.
+    Malformed,
+    /// The signature does not match the payload.
+    Mismatch,
+    /// The signed timestamp is outside the tolerance window (replay guard).
+    TimestampTooOld,
+}
+
+impl SignatureFailure {
+    /// Machine-readable reason, rendered as the problem's `reason` member.
+    pub fn code(&self) -> &'static str {
+        match self {
+            SignatureFailure::Missing => "signature_missing",
+            SignatureFailure::Malformed => "signature_malformed",
+            SignatureFailure::Mismatch => "signature_mismatch",
+            SignatureFailure::TimestampTooOld => "timestamp_too_old",
+        }
+    }
+}
+
+impl fmt::Display for SignatureFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SignatureFailure::Missing => "signature missing",
+            SignatureFailure::Malformed => "signature malformed",
+            SignatureFailure::Mismatch => "signature mismatch",
+            SignatureFailure::TimestampTooOld => "timestamp too old",
+        })
+    }
+}